@@ -0,0 +1,528 @@
+//! Generic Address Structure
+
+use crate::Error;
+use core::fmt;
+
+/// Generic Address Structure
+///
+/// Describes the location of a register within one of several address spaces. Tables store
+/// these as 12-byte arrays, which are decoded with [`GenericAddress::from_bytes()`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct GenericAddress {
+    /// Address space in which the register resides
+    pub address_space: AddressSpace,
+    /// Size of the register in bits
+    pub bit_width: u8,
+    /// Offset of the register within the address, in bits
+    pub bit_offset: u8,
+    /// Size of each access to the register
+    pub access_size: AccessSize,
+    /// Address of the register within its address space
+    pub address: u64,
+}
+
+impl GenericAddress {
+    /// Size of the encoded structure, in bytes
+    pub const SIZE: usize = 12;
+
+    /// Decode a Generic Address Structure
+    pub const fn from_bytes(bytes: [u8; Self::SIZE]) -> GenericAddress {
+        let [space, bit_width, bit_offset, access_size, a0, a1, a2, a3, a4, a5, a6, a7] = bytes;
+        Self {
+            address_space: AddressSpace(space),
+            bit_width,
+            bit_offset,
+            access_size: AccessSize(access_size),
+            address: u64::from_le_bytes([a0, a1, a2, a3, a4, a5, a6, a7]),
+        }
+    }
+
+    /// Encode this Generic Address Structure
+    pub const fn to_bytes(self) -> [u8; Self::SIZE] {
+        let [a0, a1, a2, a3, a4, a5, a6, a7] = self.address.to_le_bytes();
+        [
+            self.address_space.0,
+            self.bit_width,
+            self.bit_offset,
+            self.access_size.0,
+            a0,
+            a1,
+            a2,
+            a3,
+            a4,
+            a5,
+            a6,
+            a7,
+        ]
+    }
+
+    /// Create a Generic Address Structure for a block of `len` bytes of system I/O space
+    ///
+    /// This describes the legacy register blocks found in older tables, which only give a
+    /// port number and length.
+    pub const fn system_io(port: u64, len: u8) -> GenericAddress {
+        Self {
+            address_space: AddressSpace::SYSTEM_IO,
+            bit_width: len.saturating_mul(8),
+            bit_offset: 0,
+            access_size: AccessSize::UNDEFINED,
+            address: port,
+        }
+    }
+
+    /// Returns `true` if this structure does not describe a register
+    ///
+    /// Optional registers are indicated by an address of zero.
+    #[inline]
+    pub const fn is_null(&self) -> bool {
+        self.address == 0
+    }
+
+    /// Returns the accesses needed to cover the bit offset and bit width of the register, as
+    /// the offset in bytes and size of each
+    ///
+    /// If the access size is undefined, the register is split into the largest naturally
+    /// aligned accesses which fit within it, such as a word and a byte for 3 bytes.
+    fn accesses(&self) -> Result<impl Iterator<Item = (u64, AccessSize)>, Error> {
+        let bits = self.bit_offset as u32 + self.bit_width as u32;
+        if self.bit_width == 0 || bits > u64::BITS {
+            return Err(Error::Register(*self));
+        }
+        let unit = match self.access_size {
+            AccessSize::UNDEFINED => None,
+            size => Some(size.bytes().ok_or(Error::Register(*self))? as u64),
+        };
+        let (address, len) = (self.address, bits.div_ceil(8) as u64);
+        let mut offset = 0;
+        Ok(core::iter::from_fn(move || {
+            if offset >= len {
+                return None;
+            }
+            let bytes = unit.unwrap_or_else(|| {
+                let addr = address.wrapping_add(offset);
+                [8, 4, 2, 1]
+                    .into_iter()
+                    .find(|&n| n <= len - offset && addr % n == 0)
+                    .unwrap()
+            });
+            let size = AccessSize(bytes.trailing_zeros() as u8 + 1);
+            let access = (offset, size);
+            offset += bytes;
+            Some(access)
+        }))
+    }
+
+    fn read_unit(&self, io: &impl RegisterIo, addr: u64, size: AccessSize) -> Result<u64, Error> {
+        match self.address_space {
+            AddressSpace::SYSTEM_MEMORY => io.read_memory(addr, size),
+            AddressSpace::SYSTEM_IO => io.read_io(addr, size),
+            AddressSpace::PCI_CONFIG => {
+                io.read_pci_config(PciAddress::from_gas_address(addr), size)
+            }
+            space => Err(Error::AddressSpace(space)),
+        }
+    }
+
+    fn write_unit(
+        &self,
+        io: &impl RegisterIo,
+        addr: u64,
+        size: AccessSize,
+        value: u64,
+    ) -> Result<(), Error> {
+        match self.address_space {
+            AddressSpace::SYSTEM_MEMORY => io.write_memory(addr, size, value),
+            AddressSpace::SYSTEM_IO => io.write_io(addr, size, value),
+            AddressSpace::PCI_CONFIG => {
+                io.write_pci_config(PciAddress::from_gas_address(addr), size, value)
+            }
+            space => Err(Error::AddressSpace(space)),
+        }
+    }
+
+    /// Read the register
+    ///
+    /// The register is read with as many accesses of the access size as are needed to cover
+    /// the bit offset and bit width, or with the largest accesses which fit if the access size
+    /// is undefined. The returned value is shifted down by the bit offset, and bits beyond the
+    /// bit width are cleared.
+    pub fn read(&self, io: &impl RegisterIo) -> Result<u64, Error> {
+        let mut value = 0;
+        for (offset, size) in self.accesses()? {
+            let unit = self.read_unit(io, self.address + offset, size)?;
+            let unit_bits = size.bytes().unwrap() as u32 * 8;
+            value |= (unit & width_mask(unit_bits)) << (offset * 8);
+        }
+        Ok((value >> self.bit_offset) & width_mask(self.bit_width as u32))
+    }
+
+    /// Write the register
+    ///
+    /// `value` is truncated to the bit width and shifted up by the bit offset, then written
+    /// with the same accesses as [`GenericAddress::read()`]. Bits outside of the register
+    /// within the accessed units are written as zero.
+    pub fn write(&self, io: &impl RegisterIo, value: u64) -> Result<(), Error> {
+        let value = (value & width_mask(self.bit_width as u32)) << self.bit_offset;
+        for (offset, size) in self.accesses()? {
+            let unit_bits = size.bytes().unwrap() as u32 * 8;
+            let unit = (value >> (offset * 8)) & width_mask(unit_bits);
+            self.write_unit(io, self.address + offset, size, unit)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns a mask of the low `width` bits
+fn width_mask(width: u32) -> u64 {
+    u64::MAX.checked_shr(u64::BITS - width).unwrap_or(0)
+}
+
+/// A pair of registers accessed as a single logical register
+///
+/// The PM1 register blocks are split into `a` and `b` blocks, either of which may contain
+/// any of the bits of a register. Reads combine the values of both registers and writes
+/// write the same value to both.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct RegisterPair {
+    pub a: GenericAddress,
+    pub b: Option<GenericAddress>,
+}
+
+impl RegisterPair {
+    /// Read both registers and combine their values
+    pub fn read(&self, io: &impl RegisterIo) -> Result<u64, Error> {
+        let a = self.a.read(io)?;
+        let b = match self.b {
+            Some(b) => b.read(io)?,
+            None => 0,
+        };
+        Ok(a | b)
+    }
+
+    /// Write `value` to both registers
+    pub fn write(&self, io: &impl RegisterIo, value: u64) -> Result<(), Error> {
+        self.a.write(io, value)?;
+        if let Some(b) = self.b {
+            b.write(io, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Location of a register in PCI configuration space
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct PciAddress {
+    pub segment: u16,
+    pub bus: u8,
+    pub device: u8,
+    pub function: u8,
+    pub offset: u16,
+}
+
+impl PciAddress {
+    /// Decode the address of a [`GenericAddress`] in PCI configuration space
+    ///
+    /// These addresses are always in segment 0, bus 0.
+    pub const fn from_gas_address(address: u64) -> PciAddress {
+        Self {
+            segment: 0,
+            bus: 0,
+            device: (address >> 32) as u8,
+            function: (address >> 16) as u8,
+            offset: address as u16,
+        }
+    }
+}
+
+/// Access to the address spaces a [`GenericAddress`] may refer to
+///
+/// Every access is of an [`AccessSize`] between [`AccessSize::BYTE`] and
+/// [`AccessSize::QWORD`], and values are passed in the low bits of a `u64`. The default
+/// implementations of all methods fail with [`Error::AddressSpace`], so platforms only need
+/// to implement the address spaces they have.
+pub trait RegisterIo {
+    /// Read from the system I/O port `port`
+    fn read_io(&self, port: u64, size: AccessSize) -> Result<u64, Error> {
+        let _ = (port, size);
+        Err(Error::AddressSpace(AddressSpace::SYSTEM_IO))
+    }
+
+    /// Write to the system I/O port `port`
+    fn write_io(&self, port: u64, size: AccessSize, value: u64) -> Result<(), Error> {
+        let _ = (port, size, value);
+        Err(Error::AddressSpace(AddressSpace::SYSTEM_IO))
+    }
+
+    /// Read from physical memory at `phys`
+    fn read_memory(&self, phys: u64, size: AccessSize) -> Result<u64, Error> {
+        let _ = (phys, size);
+        Err(Error::AddressSpace(AddressSpace::SYSTEM_MEMORY))
+    }
+
+    /// Write to physical memory at `phys`
+    fn write_memory(&self, phys: u64, size: AccessSize, value: u64) -> Result<(), Error> {
+        let _ = (phys, size, value);
+        Err(Error::AddressSpace(AddressSpace::SYSTEM_MEMORY))
+    }
+
+    /// Read from PCI configuration space
+    fn read_pci_config(&self, address: PciAddress, size: AccessSize) -> Result<u64, Error> {
+        let _ = (address, size);
+        Err(Error::AddressSpace(AddressSpace::PCI_CONFIG))
+    }
+
+    /// Write to PCI configuration space
+    fn write_pci_config(
+        &self,
+        address: PciAddress,
+        size: AccessSize,
+        value: u64,
+    ) -> Result<(), Error> {
+        let _ = (address, size, value);
+        Err(Error::AddressSpace(AddressSpace::PCI_CONFIG))
+    }
+}
+
+/// Address Space ID of a [`GenericAddress`]
+#[repr(transparent)]
+#[derive(Clone, Copy, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct AddressSpace(pub u8);
+
+impl AddressSpace {
+    pub const SYSTEM_MEMORY: Self = Self(0x00);
+    pub const SYSTEM_IO: Self = Self(0x01);
+    pub const PCI_CONFIG: Self = Self(0x02);
+    pub const EMBEDDED_CONTROLLER: Self = Self(0x03);
+    pub const SMBUS: Self = Self(0x04);
+    pub const SYSTEM_CMOS: Self = Self(0x05);
+    pub const PCI_BAR_TARGET: Self = Self(0x06);
+    pub const IPMI: Self = Self(0x07);
+    pub const GENERAL_PURPOSE_IO: Self = Self(0x08);
+    pub const GENERIC_SERIAL_BUS: Self = Self(0x09);
+    /// Platform Communications Channel
+    pub const PCC: Self = Self(0x0a);
+    /// Platform Runtime Mechanism
+    pub const PRM: Self = Self(0x0b);
+    /// Functional Fixed Hardware
+    pub const FFH: Self = Self(0x7f);
+}
+
+impl fmt::Debug for AddressSpace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            Self::SYSTEM_MEMORY => "SYSTEM_MEMORY",
+            Self::SYSTEM_IO => "SYSTEM_IO",
+            Self::PCI_CONFIG => "PCI_CONFIG",
+            Self::EMBEDDED_CONTROLLER => "EMBEDDED_CONTROLLER",
+            Self::SMBUS => "SMBUS",
+            Self::SYSTEM_CMOS => "SYSTEM_CMOS",
+            Self::PCI_BAR_TARGET => "PCI_BAR_TARGET",
+            Self::IPMI => "IPMI",
+            Self::GENERAL_PURPOSE_IO => "GENERAL_PURPOSE_IO",
+            Self::GENERIC_SERIAL_BUS => "GENERIC_SERIAL_BUS",
+            Self::PCC => "PCC",
+            Self::PRM => "PRM",
+            Self::FFH => "FFH",
+            _ => return write!(f, "AddressSpace({:#x})", self.0),
+        };
+        write!(f, "AddressSpace::{name}")
+    }
+}
+
+/// Access Size of a [`GenericAddress`]
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct AccessSize(pub u8);
+
+impl AccessSize {
+    /// Legacy value, the access size is determined by the bit width and offset.
+    pub const UNDEFINED: Self = Self(0);
+    pub const BYTE: Self = Self(1);
+    pub const WORD: Self = Self(2);
+    pub const DWORD: Self = Self(3);
+    pub const QWORD: Self = Self(4);
+
+    /// Returns the access size in bytes, or `None` if it is undefined or reserved
+    #[inline]
+    pub const fn bytes(self) -> Option<usize> {
+        match self.0 {
+            1..=4 => Some(1 << (self.0 - 1)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    extern crate std;
+
+    use super::*;
+    use core::cell::RefCell;
+    use std::{collections::BTreeMap, vec::Vec};
+
+    /// A register access made through [`RecordingIo`]
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub(crate) enum Access {
+        Read(AddressSpace, u64, AccessSize),
+        Write(AddressSpace, u64, AccessSize, u64),
+    }
+
+    /// Byte-addressed system I/O and memory spaces which record every access
+    ///
+    /// Unwritten bytes read as zero.
+    #[derive(Default)]
+    pub(crate) struct RecordingIo {
+        pub bytes: RefCell<BTreeMap<(AddressSpace, u64), u8>>,
+        pub accesses: RefCell<Vec<Access>>,
+    }
+
+    impl RecordingIo {
+        pub fn set(&self, space: AddressSpace, addr: u64, bytes: &[u8]) {
+            let mut map = self.bytes.borrow_mut();
+            for (addr, &byte) in (addr..).zip(bytes) {
+                map.insert((space, addr), byte);
+            }
+        }
+
+        pub fn get(&self, space: AddressSpace, addr: u64, len: usize) -> Vec<u8> {
+            let map = self.bytes.borrow();
+            (addr..addr + len as u64)
+                .map(|addr| map.get(&(space, addr)).copied().unwrap_or(0))
+                .collect()
+        }
+
+        pub fn take_accesses(&self) -> Vec<Access> {
+            self.accesses.take()
+        }
+
+        fn read(&self, space: AddressSpace, addr: u64, size: AccessSize) -> Result<u64, Error> {
+            self.accesses
+                .borrow_mut()
+                .push(Access::Read(space, addr, size));
+            let len = size.bytes().ok_or(Error::AddressSpace(space))?;
+            let mut value = [0; 8];
+            value[..len].copy_from_slice(&self.get(space, addr, len));
+            Ok(u64::from_le_bytes(value))
+        }
+
+        fn write(
+            &self,
+            space: AddressSpace,
+            addr: u64,
+            size: AccessSize,
+            value: u64,
+        ) -> Result<(), Error> {
+            self.accesses
+                .borrow_mut()
+                .push(Access::Write(space, addr, size, value));
+            let len = size.bytes().ok_or(Error::AddressSpace(space))?;
+            self.set(space, addr, &value.to_le_bytes()[..len]);
+            Ok(())
+        }
+    }
+
+    impl RegisterIo for RecordingIo {
+        fn read_io(&self, port: u64, size: AccessSize) -> Result<u64, Error> {
+            self.read(AddressSpace::SYSTEM_IO, port, size)
+        }
+
+        fn write_io(&self, port: u64, size: AccessSize, value: u64) -> Result<(), Error> {
+            self.write(AddressSpace::SYSTEM_IO, port, size, value)
+        }
+
+        fn read_memory(&self, phys: u64, size: AccessSize) -> Result<u64, Error> {
+            self.read(AddressSpace::SYSTEM_MEMORY, phys, size)
+        }
+
+        fn write_memory(&self, phys: u64, size: AccessSize, value: u64) -> Result<(), Error> {
+            self.write(AddressSpace::SYSTEM_MEMORY, phys, size, value)
+        }
+    }
+
+    const MEMORY: AddressSpace = AddressSpace::SYSTEM_MEMORY;
+
+    #[test]
+    fn split_accesses() {
+        let io = RecordingIo::default();
+        io.set(MEMORY, 0x1000, &[0x12, 0x34, 0x56, 0x78]);
+        let gas = GenericAddress {
+            address_space: MEMORY,
+            bit_width: 16,
+            bit_offset: 8,
+            access_size: AccessSize::BYTE,
+            address: 0x1000,
+        };
+
+        assert_eq!(gas.read(&io), Ok(0x5634));
+        assert_eq!(
+            io.take_accesses(),
+            [0x1000, 0x1001, 0x1002].map(|addr| Access::Read(MEMORY, addr, AccessSize::BYTE))
+        );
+
+        gas.write(&io, 0xabcd).unwrap();
+        assert_eq!(
+            io.take_accesses(),
+            [(0x1000, 0x00), (0x1001, 0xcd), (0x1002, 0xab)].map(|(addr, value)| Access::Write(
+                MEMORY,
+                addr,
+                AccessSize::BYTE,
+                value
+            ))
+        );
+    }
+
+    #[test]
+    fn undefined_access_size() {
+        let io = RecordingIo::default();
+        let gas = GenericAddress::system_io(0x400, 3);
+        gas.write(&io, 0x12_3456).unwrap();
+        assert_eq!(io.take_accesses(), [
+            Access::Write(AddressSpace::SYSTEM_IO, 0x400, AccessSize::WORD, 0x3456),
+            Access::Write(AddressSpace::SYSTEM_IO, 0x402, AccessSize::BYTE, 0x12),
+        ]);
+        assert_eq!(gas.read(&io), Ok(0x12_3456));
+        io.take_accesses();
+
+        // Accesses are naturally aligned.
+        let unaligned = GenericAddress::system_io(0x401, 7);
+        unaligned.write(&io, 0x0e_0d0c_0b0a_0908).unwrap();
+        assert_eq!(io.take_accesses(), [
+            Access::Write(AddressSpace::SYSTEM_IO, 0x401, AccessSize::BYTE, 0x08),
+            Access::Write(AddressSpace::SYSTEM_IO, 0x402, AccessSize::WORD, 0x0a09),
+            Access::Write(
+                AddressSpace::SYSTEM_IO,
+                0x404,
+                AccessSize::DWORD,
+                0x0e0d_0c0b
+            ),
+        ]);
+        assert_eq!(unaligned.read(&io), Ok(0x0e_0d0c_0b0a_0908));
+        io.take_accesses();
+
+        let gas = GenericAddress {
+            address_space: AddressSpace::EMBEDDED_CONTROLLER,
+            ..gas
+        };
+        assert_eq!(
+            gas.read(&io),
+            Err(Error::AddressSpace(AddressSpace::EMBEDDED_CONTROLLER))
+        );
+        assert_eq!(io.take_accesses(), []);
+    }
+
+    #[test]
+    fn register_pair() {
+        let io = RecordingIo::default();
+        let pair = RegisterPair {
+            a: GenericAddress::system_io(0x400, 2),
+            b: Some(GenericAddress::system_io(0x500, 2)),
+        };
+        io.set(AddressSpace::SYSTEM_IO, 0x400, &[0x01, 0x00]);
+        io.set(AddressSpace::SYSTEM_IO, 0x500, &[0x00, 0x01]);
+        assert_eq!(pair.read(&io), Ok(0x0101));
+
+        pair.write(&io, 0x0120).unwrap();
+        assert_eq!(io.get(AddressSpace::SYSTEM_IO, 0x400, 2), [0x20, 0x01]);
+        assert_eq!(io.get(AddressSpace::SYSTEM_IO, 0x500, 2), [0x20, 0x01]);
+    }
+}
@@ -0,0 +1,26 @@
+//! ACPI Machine Language
+//!
+//! Definition blocks, such as the [`Dsdt`](crate::sdt::dsdt::Dsdt), contain AML byte code
+//! describing the ACPI namespace. This module parses AML into a tree of [`Term`]s, which is
+//! loaded into a [`Namespace`], and evaluates control methods with an [`Interpreter`].
+//! Resource templates returned by methods such as `_CRS` are decoded by [`resource`].
+
+pub mod interpreter;
+pub mod name;
+pub mod namespace;
+pub mod parser;
+pub mod pci;
+pub mod region;
+pub mod resource;
+pub mod term;
+pub mod value;
+
+pub use interpreter::{AmlError, Host, Interpreter};
+pub use name::{NameSeg, NameString};
+pub use namespace::{Namespace, NodeId};
+pub use parser::{parse, ParseError, Parser};
+pub use pci::{PciInterrupt, PciRouting};
+pub use region::{RegionAccess, RegionHandler};
+pub use resource::{Resource, ResourceError};
+pub use term::{Expr, SuperName, Term};
+pub use value::{Reference, Value};
@@ -0,0 +1,2334 @@
+use super::{
+    name::{NameSeg, NameString},
+    namespace::{
+        AccessAttrib, BufferField, Declaration, FieldKind, FieldUnit, LoadError, Namespace, Node,
+        NodeId, Object,
+    },
+    parser::ParseError,
+    region::{RegionAccess, RegionHandler},
+    resource::ResourceError,
+    term::{
+        BinaryOp, Connection, CreateFieldKind, Expr, LogicalOp, MatchOp, SuperName, Target, Term,
+        UnaryOp, UpdateRule,
+    },
+    value::{
+        field_value, read_bits, write_bits, ConversionError, ObjectType, Reference, Value,
+        MAX_OBJECT_SIZE,
+    },
+};
+use crate::{
+    address::{AddressSpace, PciAddress},
+    sdt::{
+        dsdt::{DefinitionBlock, IntegerWidth},
+        Bridge, RootTable,
+    },
+};
+use alloc::{
+    boxed::Box,
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{cmp::Ordering, fmt};
+
+/// Value of the `Revision` operator
+pub const REVISION: u64 = 1;
+
+/// Deepest nesting of method invocations before evaluation fails with [`AmlError::TooDeep`]
+pub const MAX_CALL_DEPTH: usize = 32;
+
+/// Default limit on the time spent in a single `While` loop, in milliseconds
+pub const DEFAULT_LOOP_TIMEOUT: u64 = 30_000;
+
+/// Interfaces reported as supported by the default [`Host::osi()`]
+pub const OSI_INTERFACES: &[&str] = &[
+    "Windows 2000",
+    "Windows 2001",
+    "Windows 2001 SP1",
+    "Windows 2001.1",
+    "Windows 2001 SP2",
+    "Windows 2001.1 SP1",
+    "Windows 2006",
+    "Windows 2006.1",
+    "Windows 2006 SP1",
+    "Windows 2006 SP2",
+    "Windows 2009",
+    "Windows 2012",
+    "Windows 2013",
+    "Windows 2015",
+    "Windows 2016",
+    "Windows 2017",
+    "Windows 2017.2",
+    "Windows 2018",
+    "Windows 2018.2",
+    "Windows 2019",
+    "Windows 2020",
+    "Windows 2021",
+    "Windows 2022",
+    "Module Device",
+    "Processor Device",
+    "3.0 Thermal Model",
+    "Extended Address Space Descriptor",
+    "Processor Aggregator Device",
+];
+
+/// AML Host Interface
+///
+/// Operations which the interpreter cannot perform on its own, such as waiting and
+/// synchronizing with other threads. Mutexes and events are identified by their node in
+/// the namespace, `\_GL` being the Global Lock.
+///
+/// The default implementations suit a single-threaded host: mutexes are always acquired,
+/// and events are never signaled.
+pub trait Host {
+    /// Returns the value of a monotonic timer, in units of 100 nanoseconds
+    fn timer(&mut self) -> u64;
+
+    /// Sleep for at least `ms` milliseconds
+    fn sleep(&mut self, ms: u64) {
+        self.stall(ms.saturating_mul(1000));
+    }
+
+    /// Busy-wait for at least `us` microseconds
+    fn stall(&mut self, us: u64) {
+        let end = self.timer().saturating_add(us.saturating_mul(10));
+        while self.timer() < end {
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Acquire a mutex, waiting at most `timeout` milliseconds
+    ///
+    /// A timeout of `0xffff` waits forever. Returns `false` if the timeout expired.
+    fn acquire(&mut self, mutex: NodeId, timeout: u16) -> bool {
+        let _ = (mutex, timeout);
+        true
+    }
+
+    fn release(&mut self, mutex: NodeId) {
+        let _ = mutex;
+    }
+
+    fn signal(&mut self, event: NodeId) {
+        let _ = event;
+    }
+
+    /// Wait for an event to be signaled, waiting at most `timeout` milliseconds
+    ///
+    /// A timeout of `0xffff` waits forever. Returns `false` if the timeout expired.
+    fn wait(&mut self, event: NodeId, timeout: u16) -> bool {
+        let _ = (event, timeout);
+        false
+    }
+
+    fn reset(&mut self, event: NodeId) {
+        let _ = event;
+    }
+
+    /// Called by `Notify`, to deliver the notification `value` to the handlers of `node`
+    fn notify(&mut self, node: NodeId, value: u64) {
+        log::debug!("acpi: unhandled Notify({node:?}, {value:#x})");
+    }
+
+    /// Called by `Fatal`, before evaluation fails with [`AmlError::Fatal`]
+    fn fatal(&mut self, kind: u8, code: u32, arg: u64) {
+        log::error!("acpi: Fatal({kind:#x}, {code:#x}, {arg:#x})");
+    }
+
+    fn breakpoint(&mut self) {}
+
+    /// Called for each value stored to the `Debug` object
+    fn debug(&mut self, value: &Value) {
+        log::info!("acpi: Debug = {value:?}");
+    }
+
+    /// Returns `true` if the interface passed to `\_OSI` is supported
+    fn osi(&mut self, interface: &str) -> bool {
+        OSI_INTERFACES.contains(&interface)
+    }
+}
+
+/// Error returned when evaluating AML
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AmlError {
+    /// A definition block could not be loaded
+    Load(LoadError),
+    /// A path could not be parsed
+    InvalidName,
+    /// A name does not exist in the namespace
+    NotFound(NameString),
+    /// An operand could not be converted to the required type
+    Conversion(ConversionError),
+    DivideByZero,
+    /// An index is past the end of a buffer, string or package
+    IndexOutOfBounds,
+    /// An object is larger than [`MAX_OBJECT_SIZE`]
+    TooLarge,
+    /// A reference does not refer to an object of the required type
+    InvalidReference,
+    /// A value was stored to an object which cannot hold one, such as a method
+    InvalidTarget,
+    /// Methods are nested more than [`MAX_CALL_DEPTH`] times
+    TooDeep,
+    /// A `While` loop ran for longer than the loop timeout
+    LoopTimeout,
+    /// The AML executed `Fatal`
+    Fatal {
+        kind: u8,
+        code: u32,
+        arg: u64,
+    },
+    /// No handler is installed for the address space of an operation region
+    NoRegionHandler(AddressSpace),
+    /// A region handler failed to access an operation region
+    Region(crate::Error),
+    /// An object returned by the firmware, such as a `_PRT` entry, has the wrong form
+    InvalidObject(&'static str),
+    /// A resource template could not be decoded
+    Resource(ResourceError),
+    /// The operation is not supported by the interpreter
+    Unsupported(&'static str),
+}
+
+impl From<LoadError> for AmlError {
+    fn from(error: LoadError) -> Self {
+        Self::Load(error)
+    }
+}
+
+impl From<ParseError> for AmlError {
+    fn from(error: ParseError) -> Self {
+        Self::Load(LoadError::Parse(error))
+    }
+}
+
+impl From<crate::Error> for AmlError {
+    fn from(error: crate::Error) -> Self {
+        Self::Load(LoadError::Table(error))
+    }
+}
+
+impl From<ResourceError> for AmlError {
+    fn from(error: ResourceError) -> Self {
+        Self::Resource(error)
+    }
+}
+
+impl From<ConversionError> for AmlError {
+    fn from(error: ConversionError) -> Self {
+        Self::Conversion(error)
+    }
+}
+
+impl fmt::Display for AmlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Load(error) => write!(f, "{error}"),
+            Self::InvalidName => f.write_str("invalid name"),
+            Self::NotFound(name) => write!(f, "{name} does not exist"),
+            Self::Conversion(error) => write!(f, "{error}"),
+            Self::DivideByZero => f.write_str("divide by zero"),
+            Self::IndexOutOfBounds => f.write_str("index out of bounds"),
+            Self::TooLarge => f.write_str("object is too large"),
+            Self::InvalidReference => f.write_str("invalid reference"),
+            Self::InvalidTarget => f.write_str("invalid target"),
+            Self::TooDeep => f.write_str("methods are nested too deeply"),
+            Self::LoopTimeout => f.write_str("loop timed out"),
+            Self::Fatal { kind, code, arg } => {
+                write!(
+                    f,
+                    "fatal error {kind:#x}, code {code:#x}, argument {arg:#x}"
+                )
+            }
+            Self::NoRegionHandler(space) => write!(f, "no handler for {space:?}"),
+            Self::Region(error) => write!(f, "{error}"),
+            Self::InvalidObject(what) => write!(f, "invalid {what}"),
+            Self::Resource(error) => write!(f, "{error}"),
+            Self::Unsupported(what) => write!(f, "{what} is not supported"),
+        }
+    }
+}
+
+type Result<T, E = AmlError> = core::result::Result<T, E>;
+
+/// Outcome of executing a term
+enum Flow {
+    Normal,
+    Break,
+    Continue,
+    Return(Value),
+}
+
+/// State of a method invocation, or of a definition block being loaded
+struct Frame {
+    /// Number of the invocation, which identifies the frame in references to its arguments
+    /// and locals
+    serial: usize,
+    scope: NodeId,
+    args: [Value; 7],
+    locals: [Value; 8],
+    /// Objects created by the method, which are removed when it returns
+    created: Vec<NodeId>,
+    /// `Name` objects which could not be initialized yet, only used while loading
+    pending: Option<Vec<(NodeId, Expr)>>,
+}
+
+impl Frame {
+    fn new(scope: NodeId) -> Self {
+        Self {
+            serial: 0,
+            scope,
+            args: Default::default(),
+            locals: Default::default(),
+            created: Vec::new(),
+            pending: None,
+        }
+    }
+}
+
+/// AML Interpreter
+///
+/// Loads definition blocks into a [`Namespace`], executing the code they contain, and
+/// evaluates control methods and other objects.
+///
+/// ```ignore
+/// let mut interpreter = Interpreter::new(host);
+/// interpreter.load_tables(&tables)?;
+/// let sta = interpreter.evaluate("\\_SB.PCI0._STA", vec![])?;
+/// ```
+pub struct Interpreter<H: Host> {
+    namespace: Namespace,
+    host: H,
+    /// Loop timeout, in units of 100 nanoseconds
+    loop_timeout: u64,
+    frames: Vec<Frame>,
+    /// Serial number of the next frame
+    next_serial: usize,
+    handlers: BTreeMap<AddressSpace, Box<dyn RegionHandler>>,
+    /// Buffer returned by the last write to a serial bus field, which is the result of the
+    /// `Store`
+    transfer_result: Option<Value>,
+}
+
+impl<H: Host> Interpreter<H> {
+    /// Create an interpreter with an empty namespace
+    pub fn new(host: H) -> Interpreter<H> {
+        Self {
+            namespace: Namespace::new(),
+            host,
+            loop_timeout: DEFAULT_LOOP_TIMEOUT * 10_000,
+            frames: Vec::new(),
+            next_serial: 0,
+            handlers: BTreeMap::new(),
+            transfer_result: None,
+        }
+    }
+
+    #[inline]
+    pub fn namespace(&self) -> &Namespace {
+        &self.namespace
+    }
+
+    #[inline]
+    pub fn namespace_mut(&mut self) -> &mut Namespace {
+        &mut self.namespace
+    }
+
+    #[inline]
+    pub fn host(&self) -> &H {
+        &self.host
+    }
+
+    #[inline]
+    pub fn host_mut(&mut self) -> &mut H {
+        &mut self.host
+    }
+
+    /// Set the longest time a single `While` loop may run for, in milliseconds
+    ///
+    /// Loops which run for longer fail with [`AmlError::LoopTimeout`]. The default is
+    /// [`DEFAULT_LOOP_TIMEOUT`].
+    #[inline]
+    pub fn set_loop_timeout(&mut self, ms: u64) {
+        self.loop_timeout = ms.saturating_mul(10_000);
+    }
+
+    /// Install the handler for operation regions in `space`, returning the previous one
+    ///
+    /// Once the handler is installed, the `_REG` method of each device containing a region
+    /// in `space` is called to tell it the region is available, except for the
+    /// `SystemMemory` and `SystemIO` spaces which are always available. Handlers should be
+    /// installed after loading the definition blocks.
+    pub fn install_region_handler(
+        &mut self,
+        space: AddressSpace,
+        handler: Box<dyn RegionHandler>,
+    ) -> Option<Box<dyn RegionHandler>> {
+        let previous = self.handlers.insert(space, handler);
+        self.run_reg_methods(space, true);
+        previous
+    }
+
+    /// Remove the handler for operation regions in `space`
+    ///
+    /// The `_REG` methods are called to tell devices the regions are no longer available.
+    pub fn remove_region_handler(&mut self, space: AddressSpace) -> Option<Box<dyn RegionHandler>> {
+        let handler = self.handlers.remove(&space)?;
+        self.run_reg_methods(space, false);
+        Some(handler)
+    }
+
+    fn run_reg_methods(&mut self, space: AddressSpace, connect: bool) {
+        if let AddressSpace::SYSTEM_MEMORY | AddressSpace::SYSTEM_IO = space {
+            return;
+        }
+        let mut devices = Vec::new();
+        for id in self.namespace.walk() {
+            let node = self.namespace.node(id);
+            if let Object::OpRegion { space: s, .. } = node.object() {
+                if *s == space && !devices.contains(&node.parent()) {
+                    devices.push(node.parent());
+                }
+            }
+        }
+        let args = [
+            Value::Integer(space.0 as u64),
+            Value::Integer(connect as u64),
+        ];
+        for device in devices.into_iter().flatten() {
+            if let Err(error) = self.evaluate_child(device, "_REG", args.to_vec()) {
+                let path = self.namespace.path(device);
+                log::warn!("acpi: {path}._REG failed: {error}");
+            }
+        }
+    }
+
+    /// Load a definition block, executing the code outside of its methods
+    pub fn load(&mut self, aml: &[u8]) -> Result<()> {
+        let terms = self.namespace.parser_mut().parse(aml)?;
+        let root = self.namespace.root();
+        let mut frame = Frame::new(root);
+        frame.pending = Some(Vec::new());
+        self.push_frame(frame);
+        let result = self.exec_terms(&terms);
+        let frame = self.frames.pop().unwrap();
+        self.namespace.init_names(frame.pending.unwrap_or_default());
+        result.map(|_| ())
+    }
+
+    /// Load the DSDT followed by every SSDT and PSDT
+    ///
+    /// The integer width is set according to the revision of the DSDT. Like
+    /// [`Namespace::load_tables()`], only a failure to load the DSDT is returned as an
+    /// error.
+    pub fn load_tables<B: Bridge>(&mut self, tables: &RootTable<B>) -> Result<()> {
+        for table in tables.try_definition_blocks() {
+            let table = match table {
+                Ok(table) => table,
+                Err(error) => {
+                    log::warn!("acpi: skipping definition block: {error}");
+                    continue;
+                }
+            };
+            if let DefinitionBlock::Dsdt(dsdt) = &table {
+                self.namespace.set_integer_width(dsdt.integer_width());
+                self.load(dsdt.aml())?;
+            } else if let Err(error) = self.load(table.aml()) {
+                let signature = table.header().signature;
+                log::warn!("acpi: failed to load {signature}: {error}");
+            }
+        }
+        Ok(())
+    }
+
+    /// Evaluate the object at the absolute path `path`, such as `\_SB.PCI0._STA`
+    ///
+    /// Methods are invoked with `args`, other objects return their value.
+    pub fn evaluate(&mut self, path: &str, args: Vec<Value>) -> Result<Value> {
+        let name = NameString::from_asl(path).ok_or(AmlError::InvalidName)?;
+        let id = self
+            .namespace
+            .lookup(self.namespace.root(), &name)
+            .ok_or(AmlError::NotFound(name))?;
+        self.evaluate_node(id, args)
+    }
+
+    /// Evaluate an object, see [`Interpreter::evaluate()`]
+    pub fn evaluate_node(&mut self, id: NodeId, args: Vec<Value>) -> Result<Value> {
+        match self.object(id)? {
+            Object::Method { .. } => self.invoke(id, args),
+            _ => self.read_object(id),
+        }
+    }
+
+    /// Evaluate the child `name` of a device, such as `_STA`, returning `None` if it does
+    /// not exist
+    pub fn evaluate_child(
+        &mut self,
+        device: NodeId,
+        name: &str,
+        args: Vec<Value>,
+    ) -> Result<Option<Value>> {
+        let name = NameSeg::from_asl(name).ok_or(AmlError::InvalidName)?;
+        match self.namespace.node(device).child(name) {
+            Some(id) => self.evaluate_node(id, args).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn push_frame(&mut self, mut frame: Frame) {
+        frame.serial = self.next_serial;
+        self.next_serial += 1;
+        self.frames.push(frame);
+    }
+
+    /// Returns the frame numbered `serial`, failing if it has been popped
+    fn frame_by_serial(&mut self, serial: usize) -> Result<&mut Frame> {
+        // Serial numbers increase from the bottom of the stack to the top.
+        match self
+            .frames
+            .binary_search_by_key(&serial, |frame| frame.serial)
+        {
+            Ok(index) => Ok(&mut self.frames[index]),
+            Err(_) => Err(AmlError::InvalidReference),
+        }
+    }
+
+    fn frame(&self) -> &Frame {
+        self.frames.last().expect("no frame")
+    }
+
+    fn frame_mut(&mut self) -> &mut Frame {
+        self.frames.last_mut().expect("no frame")
+    }
+
+    fn width(&self) -> IntegerWidth {
+        self.namespace.integer_width()
+    }
+
+    fn is_osi(&self, id: NodeId) -> bool {
+        let node = self.namespace.node(id);
+        node.name() == NameSeg(*b"_OSI") && node.parent() == Some(self.namespace.root())
+    }
+
+    fn invoke(&mut self, id: NodeId, args: Vec<Value>) -> Result<Value> {
+        let Object::Method { flags, body } = self.namespace.node(id).object() else {
+            return self.read_object(id);
+        };
+        let (flags, body) = (*flags, body.clone());
+        if self.is_osi(id) {
+            let interface = args.first().and_then(Value::as_str).unwrap_or_default();
+            let supported = self.host.osi(interface);
+            return Ok(Value::from_bool(supported, self.width()));
+        }
+        if self.frames.len() >= MAX_CALL_DEPTH {
+            return Err(AmlError::TooDeep);
+        }
+        if flags.is_serialized() {
+            self.host.acquire(id, 0xffff);
+        }
+        let mut frame = Frame::new(id);
+        for (slot, arg) in frame.args.iter_mut().zip(args) {
+            *slot = arg;
+        }
+        self.push_frame(frame);
+        let result = self.exec_terms(&body);
+        let result = match result {
+            Ok(Flow::Return(value)) => self.detach(value),
+            Ok(_) => Ok(Value::Uninitialized),
+            Err(error) => Err(error),
+        };
+        let frame = self.frames.pop().unwrap();
+        for id in frame.created.into_iter().rev() {
+            self.namespace.remove(id);
+        }
+        if flags.is_serialized() {
+            self.host.release(id);
+        }
+        result
+    }
+
+    /// Replace references to the arguments, locals and `Name` objects of the current frame,
+    /// which is about to be popped, by their values
+    ///
+    /// References to other objects created by the method are left as they are, and become
+    /// invalid once the objects are removed.
+    fn detach(&mut self, value: Value) -> Result<Value> {
+        let current = self.frame();
+        let refers_to_frame = |r: &Reference| {
+            let mut r = r;
+            loop {
+                match r {
+                    Reference::Local { frame, .. } | Reference::Arg { frame, .. } => {
+                        break *frame == current.serial
+                    }
+                    Reference::Named(id) => {
+                        break current.created.contains(id)
+                            && matches!(
+                                self.namespace.get(*id).map(Node::object),
+                                Some(Object::Name(_))
+                            )
+                    }
+                    Reference::Index { base, .. } => r = base,
+                    _ => break false,
+                }
+            }
+        };
+        match value {
+            Value::Reference(r) if refers_to_frame(&r) => {
+                let value = self.read_reference(&r)?;
+                Ok(Value::Reference(Reference::Temporary(Box::new(value))))
+            }
+            value => Ok(value),
+        }
+    }
+
+    fn exec_terms(&mut self, terms: &[Term]) -> Result<Flow> {
+        for term in terms {
+            match self.exec_term(term)? {
+                Flow::Normal => {}
+                flow => return Ok(flow),
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn exec_term(&mut self, term: &Term) -> Result<Flow> {
+        match term {
+            Term::Break => return Ok(Flow::Break),
+            Term::BreakPoint => self.host.breakpoint(),
+            Term::Continue => return Ok(Flow::Continue),
+            Term::Fatal { kind, code, arg } => {
+                let arg = self.eval_integer(arg)?;
+                self.host.fatal(*kind, *code, arg);
+                return Err(AmlError::Fatal {
+                    kind: *kind,
+                    code: *code,
+                    arg,
+                });
+            }
+            Term::If {
+                predicate,
+                then,
+                otherwise,
+            } => {
+                // Follow `ElseIf` chains in a loop rather than recursing into each `Else`
+                let (mut predicate, mut then, mut otherwise) = (predicate, then, otherwise);
+                loop {
+                    if self.eval_integer(predicate)? != 0 {
+                        return self.exec_terms(then);
+                    }
+                    match otherwise.as_deref() {
+                        Some(
+                            [Term::If {
+                                predicate: next,
+                                then: next_then,
+                                otherwise: next_otherwise,
+                            }],
+                        ) => (predicate, then, otherwise) = (next, next_then, next_otherwise),
+                        Some(otherwise) => return self.exec_terms(otherwise),
+                        None => break,
+                    }
+                }
+            }
+            Term::Noop => {}
+            Term::Notify { object, value } => {
+                let id = self.super_name_node(object)?;
+                let value = self.eval_integer(value)?;
+                self.host.notify(id, value);
+            }
+            Term::Release(mutex) => {
+                let id = self.sync_object(mutex, ObjectType::MUTEX)?;
+                self.host.release(id);
+            }
+            Term::Reset(event) => {
+                let id = self.sync_object(event, ObjectType::EVENT)?;
+                self.host.reset(id);
+            }
+            Term::Return(value) => {
+                let value = self.eval(value)?;
+                return Ok(Flow::Return(value));
+            }
+            Term::Signal(event) => {
+                let id = self.sync_object(event, ObjectType::EVENT)?;
+                self.host.signal(id);
+            }
+            Term::Sleep(ms) => {
+                let ms = self.eval_integer(ms)?;
+                self.host.sleep(ms);
+            }
+            Term::Stall(us) => {
+                let us = self.eval_integer(us)?;
+                self.host.stall(us);
+            }
+            Term::Unload(_) => return Err(AmlError::Unsupported("Unload")),
+            Term::While { predicate, body } => {
+                let start = self.host.timer();
+                while self.eval_integer(predicate)? != 0 {
+                    match self.exec_terms(body)? {
+                        Flow::Break => break,
+                        Flow::Return(value) => return Ok(Flow::Return(value)),
+                        Flow::Normal | Flow::Continue => {}
+                    }
+                    if self.host.timer().wrapping_sub(start) > self.loop_timeout {
+                        return Err(AmlError::LoopTimeout);
+                    }
+                }
+            }
+            Term::Expr(expr) => {
+                self.eval(expr)?;
+            }
+            Term::CreateField {
+                kind,
+                buffer,
+                index,
+                name,
+            } => self.create_buffer_field(kind, buffer, index, name)?,
+            term => return self.declare(term),
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn declare(&mut self, term: &Term) -> Result<Flow> {
+        let scope = self.frame().scope;
+        let term = match term {
+            // Operation regions created by methods may depend on arguments and locals, so
+            // they are evaluated immediately.
+            Term::OpRegion {
+                name,
+                space,
+                offset,
+                length,
+            } if self.frame().pending.is_none() => Term::OpRegion {
+                name: name.clone(),
+                space: *space,
+                offset: Expr::Integer(self.eval_integer(offset)?),
+                length: Expr::Integer(self.eval_integer(length)?),
+            },
+            term => term.clone(),
+        };
+        let mut created = Vec::new();
+        let declaration = self.namespace.declare(scope, term, &mut created);
+        self.frame_mut().created.extend(created);
+        match declaration? {
+            Declaration::Done => {}
+            Declaration::Name(id, expr) => match self.frame_mut().pending.take() {
+                Some(mut pending) => {
+                    self.namespace.init_name(id, expr, &mut pending);
+                    self.frame_mut().pending = Some(pending);
+                }
+                None => {
+                    let value = self.eval(&expr)?;
+                    if let Some(object) = self.namespace.object_mut(id) {
+                        *object = Object::Name(value);
+                    }
+                }
+            },
+            Declaration::Scope(id, terms) => {
+                self.frame_mut().scope = id;
+                let result = self.exec_terms(&terms);
+                self.frame_mut().scope = scope;
+                return result;
+            }
+            Declaration::Code(term) => {
+                log::warn!("acpi: unexpected {term:?}");
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn create_buffer_field(
+        &mut self,
+        kind: &CreateFieldKind,
+        buffer: &Expr,
+        index: &Expr,
+        name: &NameString,
+    ) -> Result<()> {
+        let buffer = self.expr_reference(buffer)?;
+        let index = self.eval_integer(index)?;
+        let (bit_offset, bit_length) = match kind {
+            CreateFieldKind::Bit => (index, 1),
+            CreateFieldKind::Byte => (index.saturating_mul(8), 8),
+            CreateFieldKind::Word => (index.saturating_mul(8), 16),
+            CreateFieldKind::DWord => (index.saturating_mul(8), 32),
+            CreateFieldKind::QWord => (index.saturating_mul(8), 64),
+            CreateFieldKind::Bits(bits) => (index, self.eval_integer(bits)?),
+        };
+        let size = match self.read_reference(&buffer)? {
+            Value::Buffer(bytes) => bytes.len() as u64,
+            value => {
+                return Err(AmlError::Conversion(ConversionError {
+                    from: value.object_type(),
+                    to: ObjectType::BUFFER,
+                }))
+            }
+        };
+        if bit_length == 0 || bit_offset.saturating_add(bit_length) > size * 8 {
+            return Err(AmlError::IndexOutOfBounds);
+        }
+        let field = BufferField {
+            buffer,
+            bit_offset,
+            bit_length,
+        };
+        let scope = self.frame().scope;
+        let mut created = Vec::new();
+        self.namespace
+            .create(scope, name, Object::BufferField(field), &mut created)?;
+        self.frame_mut().created.extend(created);
+        Ok(())
+    }
+
+    /// Returns the node of a mutex or event
+    fn sync_object(&mut self, name: &SuperName, object_type: ObjectType) -> Result<NodeId> {
+        let id = self.super_name_node(name)?;
+        match self.node_type(id)? == object_type {
+            true => Ok(id),
+            false => Err(AmlError::InvalidReference),
+        }
+    }
+
+    fn lookup(&self, name: &NameString) -> Result<NodeId> {
+        self.namespace
+            .lookup(self.frame().scope, name)
+            .ok_or_else(|| AmlError::NotFound(name.clone()))
+    }
+
+    fn eval_integer(&mut self, expr: &Expr) -> Result<u64> {
+        let value = self.eval(expr)?;
+        let value = self.deref_index(value)?;
+        Ok(value.to_integer(self.width())?)
+    }
+
+    /// Replace a reference to an element of a package, buffer or string by its value
+    fn deref_index(&mut self, value: Value) -> Result<Value> {
+        match value {
+            Value::Reference(r @ Reference::Index { .. }) => self.read_reference(&r),
+            value => Ok(value),
+        }
+    }
+
+    fn eval(&mut self, expr: &Expr) -> Result<Value> {
+        let width = self.width();
+        let value = match expr {
+            Expr::Integer(value) => Value::Integer(width.truncate(*value)),
+            Expr::Ones => Value::Integer(width.max()),
+            Expr::Revision => Value::Integer(REVISION),
+            Expr::String(s) => Value::String(s.clone()),
+            Expr::Buffer { size, data } => {
+                let size = self.eval_size(size)?;
+                let mut data = data.clone();
+                if data.len() < size {
+                    data.resize(size, 0);
+                }
+                Value::Buffer(data)
+            }
+            Expr::Package { count, elements } => {
+                let count = self.eval_size(count)?;
+                let mut package = Vec::with_capacity(elements.len());
+                for element in elements {
+                    let value = match element {
+                        Expr::Name(name) => match self.lookup(name) {
+                            Ok(id) => Value::Reference(Reference::Named(id)),
+                            Err(_) => {
+                                log::warn!("acpi: {name} in package does not exist");
+                                Value::Uninitialized
+                            }
+                        },
+                        element => self.eval(element)?,
+                    };
+                    package.push(value);
+                }
+                if package.len() < count {
+                    package.resize(count, Value::Uninitialized);
+                }
+                Value::Package(package)
+            }
+            Expr::Name(name) => {
+                let id = self.lookup(name)?;
+                self.read_object(id)?
+            }
+            Expr::Arg(_) | Expr::Local(_) | Expr::Debug => {
+                let name = match *expr {
+                    Expr::Arg(index) => SuperName::Arg(index),
+                    Expr::Local(index) => SuperName::Local(index),
+                    _ => SuperName::Debug,
+                };
+                self.read_super_name(&name)?
+            }
+            Expr::Invoke { name, args } => {
+                let id = self.lookup(name)?;
+                let mut values = Vec::with_capacity(args.len());
+                for arg in args {
+                    values.push(self.eval(arg)?);
+                }
+                self.invoke(id, values)?
+            }
+            Expr::Acquire { mutex, timeout } => {
+                let id = self.sync_object(mutex, ObjectType::MUTEX)?;
+                let acquired = self.host.acquire(id, *timeout);
+                // `Acquire` returns `True` if the timeout expired.
+                Value::from_bool(!acquired, width)
+            }
+            Expr::Binary {
+                op,
+                lhs,
+                rhs,
+                target,
+            } => {
+                let value = self.binary(*op, lhs, rhs)?;
+                self.store_target(target, &value)?;
+                value
+            }
+            Expr::Unary {
+                op,
+                operand,
+                target,
+            } => {
+                let value = self.unary(*op, operand)?;
+                self.store_target(target, &value)?;
+                value
+            }
+            Expr::Logical { op, lhs, rhs } => {
+                let result = match op {
+                    LogicalOp::LAnd | LogicalOp::LOr => {
+                        let lhs = self.eval_integer(lhs)? != 0;
+                        let rhs = self.eval_integer(rhs)? != 0;
+                        match op {
+                            LogicalOp::LAnd => lhs && rhs,
+                            _ => lhs || rhs,
+                        }
+                    }
+                    _ => {
+                        let lhs = self.eval(lhs)?;
+                        let lhs = self.deref_index(lhs)?;
+                        let rhs = self.eval(rhs)?;
+                        let rhs = self.deref_index(rhs)?;
+                        let ordering = lhs.compare(&rhs, width)?;
+                        match op {
+                            LogicalOp::LEqual => ordering == Ordering::Equal,
+                            LogicalOp::LGreater => ordering == Ordering::Greater,
+                            _ => ordering == Ordering::Less,
+                        }
+                    }
+                };
+                Value::from_bool(result, width)
+            }
+            Expr::LNot(operand) => Value::from_bool(self.eval_integer(operand)? == 0, width),
+            Expr::CondRefOf { source, target } => {
+                let reference = match source {
+                    SuperName::Name(name) => {
+                        match self.namespace.lookup(self.frame().scope, name) {
+                            Some(id) => Reference::Named(id),
+                            None => return Ok(Value::from_bool(false, width)),
+                        }
+                    }
+                    source => self.super_name_reference(source)?,
+                };
+                self.store_target(target, &Value::Reference(reference))?;
+                Value::from_bool(true, width)
+            }
+            Expr::CopyObject { source, target } => {
+                let value = self.eval(source)?;
+                self.copy_object(target, value.clone())?;
+                value
+            }
+            Expr::Decrement(name) | Expr::Increment(name) => {
+                let value = self.read_super_name(name)?;
+                let value = self.deref_index(value)?.to_integer(width)?;
+                let value = match expr {
+                    Expr::Decrement(_) => value.wrapping_sub(1),
+                    _ => value.wrapping_add(1),
+                };
+                let value = Value::Integer(width.truncate(value));
+                self.store(name, value.clone())?;
+                value
+            }
+            Expr::DerefOf(source) => match self.eval(source)? {
+                Value::Reference(r) => self.read_reference(&r)?,
+                // `DerefOf` of a string refers to the object with that name.
+                Value::String(path) => {
+                    let name = NameString::from_asl(&path).ok_or(AmlError::InvalidName)?;
+                    let id = self.lookup(&name)?;
+                    self.read_object(id)?
+                }
+                _ => return Err(AmlError::InvalidReference),
+            },
+            Expr::Divide {
+                dividend,
+                divisor,
+                remainder,
+                quotient,
+            } => {
+                let dividend = self.eval_integer(dividend)?;
+                let divisor = self.eval_integer(divisor)?;
+                if divisor == 0 {
+                    return Err(AmlError::DivideByZero);
+                }
+                self.store_target(remainder, &Value::Integer(dividend % divisor))?;
+                let value = Value::Integer(dividend / divisor);
+                self.store_target(quotient, &value)?;
+                value
+            }
+            Expr::Index {
+                source,
+                index,
+                target,
+            } => {
+                let base = self.expr_reference(source)?;
+                let index = self.eval_integer(index)?;
+                let len = match self.read_deref(&base)? {
+                    Value::String(s) => s.len(),
+                    Value::Buffer(bytes) => bytes.len(),
+                    Value::Package(elements) => elements.len(),
+                    _ => return Err(AmlError::InvalidReference),
+                };
+                if index >= len as u64 {
+                    return Err(AmlError::IndexOutOfBounds);
+                }
+                let value = Value::Reference(Reference::Index {
+                    base: Box::new(base),
+                    index: index as usize,
+                });
+                self.store_target(target, &value)?;
+                value
+            }
+            Expr::Load { .. } => return Err(AmlError::Unsupported("Load")),
+            Expr::LoadTable { .. } => return Err(AmlError::Unsupported("LoadTable")),
+            Expr::Match {
+                package,
+                op1,
+                operand1,
+                op2,
+                operand2,
+                start,
+            } => {
+                let package = self.eval(package)?;
+                let package = self.deref_index(package)?;
+                let Value::Package(elements) = package else {
+                    return Err(AmlError::InvalidReference);
+                };
+                let operand1 = self.eval(operand1)?;
+                let operand2 = self.eval(operand2)?;
+                let start = self.eval_integer(start)?;
+                let matches = |element: &Value, op: MatchOp, operand: &Value| {
+                    element.matches(op, operand, width)
+                };
+                let index = elements
+                    .iter()
+                    .enumerate()
+                    .skip(start.min(usize::MAX as u64) as usize)
+                    .find(|(_, element)| {
+                        matches(element, *op1, &operand1) && matches(element, *op2, &operand2)
+                    })
+                    .map_or(width.max(), |(index, _)| index as u64);
+                Value::Integer(index)
+            }
+            Expr::Mid {
+                source,
+                index,
+                length,
+                target,
+            } => {
+                let source = self.eval(source)?;
+                let index = self.eval_integer(index)?;
+                let length = self.eval_integer(length)?;
+                let slice = |len: usize| {
+                    let start = index.min(len as u64) as usize;
+                    let end = index.saturating_add(length).min(len as u64) as usize;
+                    start..end
+                };
+                let value = match self.deref_index(source)? {
+                    Value::String(s) => {
+                        let range = slice(s.len());
+                        Value::String(String::from_utf8_lossy(&s.as_bytes()[range]).into_owned())
+                    }
+                    value => {
+                        let bytes = value.to_buffer(width)?;
+                        Value::Buffer(bytes[slice(bytes.len())].to_vec())
+                    }
+                };
+                self.store_target(target, &value)?;
+                value
+            }
+            Expr::ObjectType(name) => {
+                let object_type = match name {
+                    SuperName::Name(name) => {
+                        let id = self.lookup(name)?;
+                        self.node_type(id)?
+                    }
+                    SuperName::Debug => ObjectType::DEBUG,
+                    name => {
+                        let value = self.read_super_name(name)?;
+                        self.value_type(&value)?
+                    }
+                };
+                Value::Integer(object_type.0 as u64)
+            }
+            Expr::RefOf(name) => Value::Reference(self.super_name_reference(name)?),
+            Expr::SizeOf(name) => {
+                let value = self.read_super_name(name)?;
+                let len = match self.deref(value)? {
+                    Value::String(s) => s.len(),
+                    Value::Buffer(bytes) => bytes.len(),
+                    Value::Package(elements) => elements.len(),
+                    value => {
+                        return Err(AmlError::Conversion(ConversionError {
+                            from: value.object_type(),
+                            to: ObjectType::BUFFER,
+                        }))
+                    }
+                };
+                Value::Integer(len as u64)
+            }
+            Expr::Store { source, target } => {
+                let value = self.eval(source)?;
+                self.store(target, value.clone())?;
+                self.transfer_result.take().unwrap_or(value)
+            }
+            Expr::Timer => Value::Integer(self.host.timer()),
+            Expr::ToString {
+                source,
+                length,
+                target,
+            } => {
+                let source = self.eval(source)?;
+                let bytes = self.deref_index(source)?.to_buffer(width)?;
+                let length = self.eval_integer(length)?;
+                let bytes = bytes
+                    .iter()
+                    .take(length.min(usize::MAX as u64) as usize)
+                    .take_while(|&&byte| byte != 0)
+                    .copied()
+                    .collect::<Vec<_>>();
+                let value = Value::String(String::from_utf8_lossy(&bytes).into_owned());
+                self.store_target(target, &value)?;
+                value
+            }
+            Expr::Wait { event, timeout } => {
+                let id = self.sync_object(event, ObjectType::EVENT)?;
+                let timeout = self.eval_integer(timeout)?.min(0xffff) as u16;
+                let signaled = self.host.wait(id, timeout);
+                // `Wait` returns `True` if the timeout expired.
+                Value::from_bool(!signaled, width)
+            }
+        };
+        Ok(value)
+    }
+
+    fn eval_size(&mut self, expr: &Expr) -> Result<usize> {
+        let size = self.eval_integer(expr)?;
+        match size <= MAX_OBJECT_SIZE as u64 {
+            true => Ok(size as usize),
+            false => Err(AmlError::TooLarge),
+        }
+    }
+
+    fn binary(&mut self, op: BinaryOp, lhs: &Expr, rhs: &Expr) -> Result<Value> {
+        let width = self.width();
+        if let BinaryOp::Concat | BinaryOp::ConcatRes = op {
+            let lhs = self.eval(lhs)?;
+            let lhs = self.deref_index(lhs)?;
+            let rhs = self.eval(rhs)?;
+            let rhs = self.deref_index(rhs)?;
+            return Ok(match (op, lhs) {
+                (BinaryOp::ConcatRes, lhs) => {
+                    let lhs = lhs.to_buffer(width)?;
+                    let rhs = rhs.to_buffer(width)?;
+                    let mut bytes = strip_end_tag(&lhs).to_vec();
+                    bytes.extend_from_slice(strip_end_tag(&rhs));
+                    bytes.extend_from_slice(&[0x79, 0x00]);
+                    Value::Buffer(bytes)
+                }
+                (_, Value::String(mut lhs)) => {
+                    lhs.push_str(&rhs.to_string(width)?);
+                    Value::String(lhs)
+                }
+                (_, lhs @ Value::Integer(_)) => {
+                    let mut bytes = lhs.to_buffer(width)?;
+                    let rhs = Value::Integer(rhs.to_integer(width)?);
+                    bytes.extend_from_slice(&rhs.to_buffer(width)?);
+                    Value::Buffer(bytes)
+                }
+                (_, lhs) => {
+                    let mut bytes = lhs.to_buffer(width)?;
+                    bytes.extend_from_slice(&rhs.to_buffer(width)?);
+                    Value::Buffer(bytes)
+                }
+            });
+        }
+        let lhs = self.eval_integer(lhs)?;
+        let rhs = self.eval_integer(rhs)?;
+        let shift = |value: u64, f: fn(u64, u32) -> Option<u64>| match rhs < width.bits() as u64 {
+            true => f(value, rhs as u32).unwrap_or(0),
+            false => 0,
+        };
+        let value = match op {
+            BinaryOp::Add => lhs.wrapping_add(rhs),
+            BinaryOp::And => lhs & rhs,
+            BinaryOp::Mod => lhs.checked_rem(rhs).ok_or(AmlError::DivideByZero)?,
+            BinaryOp::Multiply => lhs.wrapping_mul(rhs),
+            BinaryOp::NAnd => !(lhs & rhs),
+            BinaryOp::NOr => !(lhs | rhs),
+            BinaryOp::Or => lhs | rhs,
+            BinaryOp::ShiftLeft => shift(lhs, u64::checked_shl),
+            BinaryOp::ShiftRight => shift(lhs, u64::checked_shr),
+            BinaryOp::Subtract => lhs.wrapping_sub(rhs),
+            BinaryOp::XOr => lhs ^ rhs,
+            BinaryOp::Concat | BinaryOp::ConcatRes => unreachable!(),
+        };
+        Ok(Value::Integer(width.truncate(value)))
+    }
+
+    fn unary(&mut self, op: UnaryOp, operand: &Expr) -> Result<Value> {
+        let width = self.width();
+        let value = self.eval(operand)?;
+        let value = self.deref_index(value)?;
+        let value = match op {
+            UnaryOp::FindSetLeftBit => {
+                let value = value.to_integer(width)?;
+                Value::Integer((u64::BITS - value.leading_zeros()) as u64)
+            }
+            UnaryOp::FindSetRightBit => {
+                let value = value.to_integer(width)?;
+                Value::Integer(match value {
+                    0 => 0,
+                    value => value.trailing_zeros() as u64 + 1,
+                })
+            }
+            UnaryOp::FromBcd => {
+                let mut value = value.to_integer(width)?;
+                let (mut result, mut scale) = (0u64, 1u64);
+                while value != 0 {
+                    result = result.wrapping_add((value & 0xf).wrapping_mul(scale));
+                    scale = scale.wrapping_mul(10);
+                    value >>= 4;
+                }
+                Value::Integer(width.truncate(result))
+            }
+            UnaryOp::Not => Value::Integer(width.truncate(!value.to_integer(width)?)),
+            UnaryOp::ToBcd => {
+                let mut value = value.to_integer(width)?;
+                let (mut result, mut shift) = (0u64, 0);
+                while value != 0 && shift < u64::BITS {
+                    result |= (value % 10) << shift;
+                    shift += 4;
+                    value /= 10;
+                }
+                Value::Integer(width.truncate(result))
+            }
+            UnaryOp::ToBuffer => Value::Buffer(value.to_buffer(width)?),
+            UnaryOp::ToDecimalString => Value::String(match value {
+                Value::Integer(value) => value.to_string(),
+                Value::Buffer(bytes) => join(&bytes, |byte| format!("{byte}")),
+                value => value.to_string(width)?,
+            }),
+            UnaryOp::ToHexString => Value::String(match value {
+                Value::Buffer(bytes) => join(&bytes, |byte| format!("0x{byte:02X}")),
+                value => value.to_string(width)?,
+            }),
+            UnaryOp::ToInteger => Value::Integer(match value {
+                Value::String(s) => parse_integer(&s, width),
+                value => value.to_integer(width)?,
+            }),
+        };
+        Ok(value)
+    }
+
+    /// Returns the object attached to a node, which may have been removed if the handle was
+    /// held by a reference
+    fn object(&self, id: NodeId) -> Result<&Object> {
+        self.namespace
+            .get(id)
+            .map(Node::object)
+            .ok_or(AmlError::InvalidReference)
+    }
+
+    /// Returns the value of a named object
+    ///
+    /// Methods are invoked without arguments, and objects which have no value, such as
+    /// devices, are returned as references.
+    fn read_object(&mut self, id: NodeId) -> Result<Value> {
+        match self.object(id)? {
+            Object::Name(value) => Ok(value.clone()),
+            Object::Method { .. } => self.invoke(id, Vec::new()),
+            Object::BufferField(field) => {
+                let field = field.clone();
+                let Value::Buffer(bytes) = self.read_deref(&field.buffer)? else {
+                    return Err(AmlError::InvalidReference);
+                };
+                let bytes = read_bits(&bytes, field.bit_offset, field.bit_length);
+                Ok(field_value(bytes, field.bit_length, self.width()))
+            }
+            Object::FieldUnit(field) => {
+                let field = field.clone();
+                self.with_lock(&field, |this| this.read_field(id, &field))
+            }
+            _ => Ok(Value::Reference(Reference::Named(id))),
+        }
+    }
+
+    /// Store `value` to a named object, converting it to the type of the current value
+    fn write_object(&mut self, id: NodeId, value: Value) -> Result<()> {
+        let width = self.width();
+        match self.namespace.object_mut(id) {
+            None => Err(AmlError::InvalidReference),
+            Some(Object::Name(current)) => {
+                *current = current.convert_to_type_of(value, width)?;
+                Ok(())
+            }
+            Some(Object::BufferField(field)) => {
+                let field = field.clone();
+                let Value::Buffer(mut bytes) = self.read_deref(&field.buffer)? else {
+                    return Err(AmlError::InvalidReference);
+                };
+                let value = match value {
+                    Value::Integer(_) | Value::Buffer(_) => value.to_buffer(width)?,
+                    value => Value::Integer(value.to_integer(width)?).to_buffer(width)?,
+                };
+                write_bits(&mut bytes, field.bit_offset, field.bit_length, &value);
+                self.write_reference(&field.buffer, Value::Buffer(bytes))
+            }
+            Some(Object::FieldUnit(field)) => {
+                let field = field.clone();
+                self.with_lock(&field, |this| this.write_field(id, &field, value))
+            }
+            _ => Err(AmlError::InvalidTarget),
+        }
+    }
+
+    /// Returns the address space of the operation region containing a field unit
+    fn field_space(&self, id: NodeId) -> AddressSpace {
+        let mut id = id;
+        // Index fields are accessed through their data field, which may itself be an index
+        // field.
+        for _ in 0..MAX_CALL_DEPTH {
+            match self.namespace.node(id).object() {
+                Object::FieldUnit(field) => match field.kind {
+                    FieldKind::Region(region) | FieldKind::Bank { region, .. } => id = region,
+                    FieldKind::Index { data, .. } => id = data,
+                },
+                Object::OpRegion { space, .. } => return *space,
+                _ => break,
+            }
+        }
+        AddressSpace(0xff)
+    }
+
+    /// Run `f` while holding the Global Lock, if the lock rule of the field requires it
+    fn with_lock<T>(
+        &mut self,
+        field: &FieldUnit,
+        f: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        let lock = match field.flags.lock_rule() {
+            true => self.namespace.lookup_path("\\_GL"),
+            false => None,
+        };
+        if let Some(lock) = lock {
+            self.host.acquire(lock, 0xffff);
+        }
+        let result = f(self);
+        if let Some(lock) = lock {
+            self.host.release(lock);
+        }
+        result
+    }
+
+    /// Evaluate an expression in the scope of a node, outside of any method
+    fn eval_in(&mut self, scope: NodeId, expr: &Expr) -> Result<Value> {
+        if self.frames.len() >= MAX_CALL_DEPTH {
+            return Err(AmlError::TooDeep);
+        }
+        self.push_frame(Frame::new(scope));
+        let result = self.eval(expr);
+        self.frames.pop();
+        result
+    }
+
+    /// Returns the address space, address and length of an operation region
+    fn region(&mut self, id: NodeId) -> Result<(AddressSpace, u64, u64)> {
+        let node = self.namespace.node(id);
+        let scope = node.parent().unwrap_or(self.namespace.root());
+        let Object::OpRegion {
+            space,
+            offset,
+            length,
+        } = node.object()
+        else {
+            return Err(AmlError::InvalidReference);
+        };
+        let (space, offset, length) = (*space, offset.clone(), length.clone());
+        let width = self.width();
+        let offset = self.eval_in(scope, &offset)?.to_integer(width)?;
+        let length = self.eval_in(scope, &length)?.to_integer(width)?;
+        // The offset and length are only evaluated once.
+        if let Some(Object::OpRegion {
+            offset: o,
+            length: l,
+            ..
+        }) = self.namespace.object_mut(id)
+        {
+            (*o, *l) = (Expr::Integer(offset), Expr::Integer(length));
+        }
+        Ok((space, offset, length))
+    }
+
+    /// Returns the address of the PCI function containing an operation region, from the
+    /// `_ADR` of its device and the `_SEG` and `_BBN` of its host bridge
+    fn pci_address(&mut self, region: NodeId) -> Result<PciAddress> {
+        let width = self.width();
+        let integer = |this: &mut Self, id, name| -> Result<Option<u64>> {
+            match this.evaluate_child(id, name, Vec::new())? {
+                Some(value) => Ok(Some(value.to_integer(width)?)),
+                None => Ok(None),
+            }
+        };
+        let device = self
+            .namespace
+            .node(region)
+            .parent()
+            .unwrap_or(self.namespace.root());
+        let adr = integer(self, device, "_ADR")?.unwrap_or(0);
+        let (mut segment, mut bus) = (None, None);
+        let mut id = Some(device);
+        while let Some(node) = id {
+            if segment.is_none() {
+                segment = integer(self, node, "_SEG")?;
+            }
+            if bus.is_none() {
+                bus = integer(self, node, "_BBN")?;
+            }
+            id = self.namespace.node(node).parent();
+        }
+        Ok(PciAddress {
+            segment: segment.unwrap_or(0) as u16,
+            bus: bus.unwrap_or(0) as u8,
+            device: (adr >> 16) as u8,
+            function: adr as u8,
+            offset: 0,
+        })
+    }
+
+    /// Returns the resource template of a field's connection
+    fn connection(&mut self, id: NodeId, field: &FieldUnit) -> Result<Option<Vec<u8>>> {
+        let scope = self
+            .namespace
+            .node(id)
+            .parent()
+            .unwrap_or(self.namespace.root());
+        let value = match &field.connection {
+            None => return Ok(None),
+            Some(Connection::Name(name)) => self.eval_in(scope, &Expr::Name(name.clone()))?,
+            Some(Connection::Buffer(expr)) => self.eval_in(scope, expr)?,
+        };
+        Ok(Some(value.to_buffer(self.width())?))
+    }
+
+    /// Returns the width of each access to a field, in bits
+    ///
+    /// `AnyAcc` and `BufferAcc` fields use the smallest access which covers the field,
+    /// falling back to byte accesses for fields which cross a quadword boundary.
+    fn access_width(field: &FieldUnit) -> u64 {
+        match field.access_type.bytes() {
+            Some(bytes) => bytes as u64 * 8,
+            None => {
+                let end = field.bit_offset + (field.bit_length as u64).max(1) - 1;
+                [8, 16, 32, 64]
+                    .into_iter()
+                    .find(|width| field.bit_offset / width == end / width)
+                    .unwrap_or(8)
+            }
+        }
+    }
+
+    /// Perform a single access to the region containing a field
+    ///
+    /// `offset` is the offset of the access in bytes, from the start of the region or from
+    /// the start of the data field of an index field. Writes pass `Some(value)`.
+    fn access_unit(
+        &mut self,
+        id: NodeId,
+        field: &FieldUnit,
+        offset: u64,
+        bits: u64,
+        write: Option<u64>,
+    ) -> Result<u64> {
+        let scope = self
+            .namespace
+            .node(id)
+            .parent()
+            .unwrap_or(self.namespace.root());
+        let region = match &field.kind {
+            FieldKind::Region(region) => *region,
+            FieldKind::Bank {
+                region,
+                bank,
+                bank_value,
+            } => {
+                let bank_value = self.eval_in(scope, bank_value)?;
+                self.write_object(*bank, bank_value)?;
+                *region
+            }
+            FieldKind::Index { index, data } => {
+                self.write_object(*index, Value::Integer(offset))?;
+                return match write {
+                    Some(value) => self.write_object(*data, Value::Integer(value)).map(|_| 0),
+                    None => Ok(self.read_object(*data)?.to_integer(self.width())?),
+                };
+            }
+        };
+        let (space, base, length) = self.region(region)?;
+        if offset.saturating_add(bits / 8) > length {
+            return Err(AmlError::IndexOutOfBounds);
+        }
+        let pci = match space {
+            AddressSpace::PCI_CONFIG => Some(self.pci_address(region)?),
+            _ => None,
+        };
+        let connection = self.connection(id, field)?;
+        let access = RegionAccess {
+            region,
+            space,
+            base,
+            length,
+            offset,
+            bit_width: bits as u32,
+            pci,
+            attrib: field.access_attrib,
+            connection: connection.as_deref(),
+        };
+        let handler = self
+            .handlers
+            .get_mut(&space)
+            .ok_or(AmlError::NoRegionHandler(space))?;
+        match write {
+            Some(value) => handler.write(&access, value).map(|_| 0),
+            None => handler.read(&access),
+        }
+    }
+
+    fn read_field(&mut self, id: NodeId, field: &FieldUnit) -> Result<Value> {
+        let width = self.width();
+        match self.field_space(id) {
+            AddressSpace::GENERAL_PURPOSE_IO => return self.access_pins(id, field, None),
+            space if is_transfer_space(space) => {
+                return self
+                    .transfer(id, field, space, Vec::new())
+                    .map(Value::Buffer);
+            }
+            _ => {}
+        }
+        let (start, end) = field_bounds(field)?;
+        let unit = Self::access_width(field);
+        let mut bytes = vec![0; field.bit_length.div_ceil(8) as usize];
+        let mut pos = start / unit * unit;
+        while pos < end {
+            let value = self.access_unit(id, field, pos / 8, unit, None)?;
+            let (lo, hi) = (pos.max(start), (pos + unit).min(end));
+            let bits = read_bits(&value.to_le_bytes(), lo - pos, hi - lo);
+            write_bits(&mut bytes, lo - start, hi - lo, &bits);
+            pos += unit;
+        }
+        Ok(field_value(bytes, field.bit_length as u64, width))
+    }
+
+    fn write_field(&mut self, id: NodeId, field: &FieldUnit, value: Value) -> Result<()> {
+        let width = self.width();
+        let bytes = match value {
+            Value::Buffer(bytes) => bytes,
+            Value::String(s) => s.into_bytes(),
+            value => value.to_integer(width)?.to_le_bytes().to_vec(),
+        };
+        match self.field_space(id) {
+            AddressSpace::GENERAL_PURPOSE_IO => {
+                let value = Value::Buffer(bytes).to_integer(IntegerWidth::Bits64)?;
+                return self.access_pins(id, field, Some(value)).map(|_| ());
+            }
+            space if is_transfer_space(space) => {
+                let result = self.transfer(id, field, space, bytes)?;
+                self.transfer_result = Some(Value::Buffer(result));
+                return Ok(());
+            }
+            _ => {}
+        }
+        let (start, end) = field_bounds(field)?;
+        let unit = Self::access_width(field);
+        let mut pos = start / unit * unit;
+        while pos < end {
+            let (lo, hi) = (pos.max(start), (pos + unit).min(end));
+            // Bits of the access unit outside of the field are filled according to the
+            // update rule.
+            let mut value = if hi - lo == unit {
+                0
+            } else {
+                match field.flags.update_rule() {
+                    UpdateRule::PRESERVE => self.access_unit(id, field, pos / 8, unit, None)?,
+                    UpdateRule::WRITE_AS_ONES => u64::MAX,
+                    _ => 0,
+                }
+            }
+            .to_le_bytes();
+            let bits = read_bits(&bytes, lo - start, hi - lo);
+            write_bits(&mut value, lo - pos, hi - lo, &bits);
+            let value = u64::from_le_bytes(value) & (u64::MAX >> (64 - unit));
+            self.access_unit(id, field, pos / 8, unit, Some(value))?;
+            pos += unit;
+        }
+        Ok(())
+    }
+
+    /// Access the pins of a `GeneralPurposeIo` field with a single access
+    fn access_pins(&mut self, id: NodeId, field: &FieldUnit, write: Option<u64>) -> Result<Value> {
+        let FieldKind::Region(region) = field.kind else {
+            return Err(AmlError::Unsupported(
+                "GeneralPurposeIo index or bank field",
+            ));
+        };
+        let (space, base, length) = self.region(region)?;
+        let connection = self.connection(id, field)?;
+        let access = RegionAccess {
+            region,
+            space,
+            base,
+            length,
+            offset: field.bit_offset,
+            bit_width: field.bit_length,
+            pci: None,
+            attrib: field.access_attrib,
+            connection: connection.as_deref(),
+        };
+        let handler = self
+            .handlers
+            .get_mut(&space)
+            .ok_or(AmlError::NoRegionHandler(space))?;
+        match write {
+            Some(value) => handler.write(&access, value).map(|_| Value::Integer(0)),
+            None => handler.read(&access).map(Value::Integer),
+        }
+    }
+
+    /// Exchange a buffer with a serial bus region, for reads `data` is empty
+    fn transfer(
+        &mut self,
+        id: NodeId,
+        field: &FieldUnit,
+        space: AddressSpace,
+        data: Vec<u8>,
+    ) -> Result<Vec<u8>> {
+        let FieldKind::Region(region) = field.kind else {
+            return Err(AmlError::Unsupported("serial bus index or bank field"));
+        };
+        let (_, base, length) = self.region(region)?;
+        let connection = self.connection(id, field)?;
+        let access = RegionAccess {
+            region,
+            space,
+            base,
+            length,
+            offset: field.bit_offset / 8,
+            bit_width: field.bit_length,
+            pci: None,
+            attrib: field.access_attrib,
+            connection: connection.as_deref(),
+        };
+        let write = !data.is_empty();
+        let mut buffer = data;
+        buffer.resize(transfer_length(space, field), 0);
+        let handler = self
+            .handlers
+            .get_mut(&space)
+            .ok_or(AmlError::NoRegionHandler(space))?;
+        handler.transfer(&access, write, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    fn node_type(&self, id: NodeId) -> Result<ObjectType> {
+        Ok(match self.object(id)? {
+            Object::Scope => ObjectType::UNINITIALIZED,
+            Object::Name(value) => value.object_type(),
+            Object::Alias(_) => ObjectType::UNINITIALIZED,
+            Object::BufferField(_) => ObjectType::BUFFER_FIELD,
+            Object::DataRegion { .. } | Object::OpRegion { .. } => ObjectType::OPERATION_REGION,
+            Object::Device => ObjectType::DEVICE,
+            Object::Event => ObjectType::EVENT,
+            Object::FieldUnit(_) => ObjectType::FIELD_UNIT,
+            Object::Method { .. } => ObjectType::METHOD,
+            Object::Mutex { .. } => ObjectType::MUTEX,
+            Object::PowerResource { .. } => ObjectType::POWER_RESOURCE,
+            Object::Processor { .. } => ObjectType::PROCESSOR,
+            Object::ThermalZone => ObjectType::THERMAL_ZONE,
+        })
+    }
+
+    /// Returns the type of a value, or of the object it refers to
+    fn value_type(&mut self, value: &Value) -> Result<ObjectType> {
+        match value {
+            Value::Reference(Reference::Named(id)) => self.node_type(*id),
+            Value::Reference(Reference::Debug) => Ok(ObjectType::DEBUG),
+            Value::Reference(r) => Ok(self.read_reference(r)?.object_type()),
+            value => Ok(value.object_type()),
+        }
+    }
+
+    fn read_reference(&mut self, r: &Reference) -> Result<Value> {
+        match r {
+            Reference::Named(id) => self.read_object(*id),
+            Reference::Local { frame, index } => {
+                Ok(self.frame_by_serial(*frame)?.locals[*index as usize].clone())
+            }
+            Reference::Arg { frame, index } => {
+                Ok(self.frame_by_serial(*frame)?.args[*index as usize].clone())
+            }
+            Reference::Index { base, index } => {
+                let value = match self.read_deref(base)? {
+                    Value::String(s) => s.as_bytes().get(*index).map(|&b| Value::Integer(b as u64)),
+                    Value::Buffer(bytes) => bytes.get(*index).map(|&b| Value::Integer(b as u64)),
+                    Value::Package(mut elements) if *index < elements.len() => {
+                        Some(elements.swap_remove(*index))
+                    }
+                    Value::Package(_) => None,
+                    _ => return Err(AmlError::InvalidReference),
+                };
+                value.ok_or(AmlError::IndexOutOfBounds)
+            }
+            Reference::Temporary(value) => Ok((**value).clone()),
+            Reference::Debug => Err(AmlError::InvalidReference),
+        }
+    }
+
+    /// Read a reference, following any references it holds to the value they refer to
+    fn read_deref(&mut self, r: &Reference) -> Result<Value> {
+        let value = self.read_reference(r)?;
+        self.deref(value)
+    }
+
+    /// Follow references to the value they refer to, stopping at objects without a value
+    fn deref(&mut self, mut value: Value) -> Result<Value> {
+        for _ in 0..MAX_CALL_DEPTH {
+            match value {
+                Value::Reference(ref r @ Reference::Named(id)) => {
+                    let new = self.read_reference(r)?;
+                    if new == Value::Reference(Reference::Named(id)) {
+                        return Ok(new);
+                    }
+                    value = new;
+                }
+                Value::Reference(Reference::Debug) => return Ok(value),
+                Value::Reference(r) => value = self.read_reference(&r)?,
+                value => return Ok(value),
+            }
+        }
+        Err(AmlError::InvalidReference)
+    }
+
+    fn write_reference(&mut self, r: &Reference, value: Value) -> Result<()> {
+        match r {
+            Reference::Named(id) => self.write_object(*id, value),
+            Reference::Local { frame, index } => {
+                self.frame_by_serial(*frame)?.locals[*index as usize] = value;
+                Ok(())
+            }
+            Reference::Arg { frame, index } => {
+                self.frame_by_serial(*frame)?.args[*index as usize] = value;
+                Ok(())
+            }
+            Reference::Index { base, index } => self.write_index(base, *index, value, 0),
+            Reference::Temporary(_) => Ok(()),
+            Reference::Debug => {
+                self.host.debug(&value);
+                Ok(())
+            }
+        }
+    }
+
+    fn write_index(
+        &mut self,
+        base: &Reference,
+        index: usize,
+        value: Value,
+        depth: usize,
+    ) -> Result<()> {
+        let width = self.width();
+        let mut current = self.read_reference(base)?;
+        match &mut current {
+            Value::Reference(r) if depth < MAX_CALL_DEPTH => {
+                let r = r.clone();
+                return self.write_index(&r, index, value, depth + 1);
+            }
+            Value::Package(elements) => {
+                *elements.get_mut(index).ok_or(AmlError::IndexOutOfBounds)? = value;
+            }
+            Value::Buffer(bytes) => {
+                *bytes.get_mut(index).ok_or(AmlError::IndexOutOfBounds)? =
+                    value.to_integer(width)? as u8;
+            }
+            Value::String(s) => {
+                let mut bytes = core::mem::take(s).into_bytes();
+                let byte = bytes.get_mut(index).ok_or(AmlError::IndexOutOfBounds)?;
+                *byte = value.to_integer(width)? as u8;
+                *s = String::from_utf8_lossy(&bytes).into_owned();
+            }
+            _ => return Err(AmlError::InvalidReference),
+        }
+        self.write_reference(base, current)
+    }
+
+    /// Returns a reference to the location named by an expression
+    ///
+    /// Expressions which do not name a location, such as a package literal, are returned
+    /// as temporaries.
+    fn expr_reference(&mut self, expr: &Expr) -> Result<Reference> {
+        match expr {
+            Expr::Name(name) => Ok(Reference::Named(self.lookup(name)?)),
+            Expr::Arg(index) => self.super_name_reference(&SuperName::Arg(*index)),
+            Expr::Local(index) => self.super_name_reference(&SuperName::Local(*index)),
+            Expr::Debug => Ok(Reference::Debug),
+            expr => match self.eval(expr)? {
+                Value::Reference(r) => Ok(r),
+                value => Ok(Reference::Temporary(Box::new(value))),
+            },
+        }
+    }
+
+    fn super_name_reference(&mut self, name: &SuperName) -> Result<Reference> {
+        let frame = self.frame().serial;
+        match name {
+            SuperName::Name(name) => Ok(Reference::Named(self.lookup(name)?)),
+            SuperName::Arg(index) => match &self.frame().args[*index as usize] {
+                // Arguments holding a reference act as that reference.
+                Value::Reference(r) => Ok(r.clone()),
+                _ => Ok(Reference::Arg {
+                    frame,
+                    index: *index,
+                }),
+            },
+            SuperName::Local(index) => Ok(Reference::Local {
+                frame,
+                index: *index,
+            }),
+            SuperName::Debug => Ok(Reference::Debug),
+            SuperName::Reference(expr) => self.target_reference(expr),
+        }
+    }
+
+    /// Returns the reference yielded by an expression used as a target
+    fn target_reference(&mut self, expr: &Expr) -> Result<Reference> {
+        let value = match expr {
+            Expr::DerefOf(source) => self.eval(source)?,
+            expr => self.eval(expr)?,
+        };
+        match value {
+            Value::Reference(r) => Ok(r),
+            _ => Err(AmlError::InvalidReference),
+        }
+    }
+
+    fn read_super_name(&mut self, name: &SuperName) -> Result<Value> {
+        match name {
+            SuperName::Name(name) => {
+                let id = self.lookup(name)?;
+                self.read_object(id)
+            }
+            SuperName::Arg(index) => match self.frame().args[*index as usize].clone() {
+                Value::Reference(r) => self.read_reference(&r),
+                value => Ok(value),
+            },
+            SuperName::Local(index) => Ok(self.frame().locals[*index as usize].clone()),
+            SuperName::Debug => Ok(Value::Reference(Reference::Debug)),
+            SuperName::Reference(expr) => {
+                let r = self.target_reference(expr)?;
+                self.read_reference(&r)
+            }
+        }
+    }
+
+    /// Returns the node named by a super name, or referred to by the value it holds
+    fn super_name_node(&mut self, name: &SuperName) -> Result<NodeId> {
+        match name {
+            SuperName::Name(name) => self.lookup(name),
+            name => match self.read_super_name(name)? {
+                Value::Reference(Reference::Named(id)) => Ok(id),
+                _ => Err(AmlError::InvalidReference),
+            },
+        }
+    }
+
+    fn store_target(&mut self, target: &Target, value: &Value) -> Result<()> {
+        match target {
+            Some(target) => self.store(target, value.clone()),
+            None => Ok(()),
+        }
+    }
+
+    /// Store `value` to `target`, as done by `Store` and the targets of operators
+    ///
+    /// Named objects convert the value to their type, locals are overwritten, and
+    /// arguments holding a reference store to the object they refer to.
+    fn store(&mut self, target: &SuperName, value: Value) -> Result<()> {
+        let r = self.super_name_reference(target)?;
+        self.write_reference(&r, value)
+    }
+
+    /// Store `value` to `target` without converting it, as done by `CopyObject`
+    fn copy_object(&mut self, target: &SuperName, value: Value) -> Result<()> {
+        match self.super_name_reference(target)? {
+            Reference::Named(id) => match self.namespace.object_mut(id) {
+                None => Err(AmlError::InvalidReference),
+                Some(object @ (Object::Name(_) | Object::BufferField(_))) => {
+                    *object = Object::Name(value);
+                    Ok(())
+                }
+                _ => Err(AmlError::InvalidTarget),
+            },
+            r => self.write_reference(&r, value),
+        }
+    }
+}
+
+/// Returns `true` if fields in `space` are accessed with [`RegionHandler::transfer()`]
+fn is_transfer_space(space: AddressSpace) -> bool {
+    matches!(
+        space,
+        AddressSpace::SMBUS
+            | AddressSpace::GENERIC_SERIAL_BUS
+            | AddressSpace::IPMI
+            | AddressSpace::PRM
+            | AddressSpace::FFH
+    )
+}
+
+/// Returns the size of the buffer exchanged with a serial bus region
+fn transfer_length(space: AddressSpace, field: &FieldUnit) -> usize {
+    match space {
+        // Status, length and 32 bytes of data
+        AddressSpace::SMBUS => 34,
+        AddressSpace::IPMI => 66,
+        AddressSpace::PRM => 26,
+        AddressSpace::FFH => 256,
+        // Status, length and data whose size depends on the protocol
+        _ => {
+            2 + match field.access_attrib {
+                Some(AccessAttrib {
+                    length: Some(length),
+                    ..
+                }) => length as usize,
+                Some(AccessAttrib {
+                    kind: 1..=3,
+                    attrib,
+                    ..
+                }) => attrib as usize,
+                Some(AccessAttrib { attrib: 0x02, .. }) => 0,
+                Some(AccessAttrib {
+                    attrib: 0x04 | 0x06,
+                    ..
+                }) => 1,
+                Some(AccessAttrib {
+                    attrib: 0x08 | 0x0c,
+                    ..
+                }) => 2,
+                _ => 255,
+            }
+        }
+    }
+}
+
+/// Returns the first bit of a field and the bit after its end
+fn field_bounds(field: &FieldUnit) -> Result<(u64, u64)> {
+    if field.bit_length as u64 > MAX_OBJECT_SIZE as u64 * 8 {
+        return Err(AmlError::TooLarge);
+    }
+    let end = field
+        .bit_offset
+        .checked_add(field.bit_length as u64)
+        .ok_or(AmlError::IndexOutOfBounds)?;
+    Ok((field.bit_offset, end))
+}
+
+/// Remove the end tag from a resource template
+fn strip_end_tag(bytes: &[u8]) -> &[u8] {
+    match bytes {
+        [rest @ .., 0x79, _] => rest,
+        bytes => bytes,
+    }
+}
+
+fn join(bytes: &[u8], f: impl Fn(u8) -> String) -> String {
+    let mut s = String::new();
+    for (i, &byte) in bytes.iter().enumerate() {
+        if i != 0 {
+            s.push(',');
+        }
+        s.push_str(&f(byte));
+    }
+    s
+}
+
+/// Parse a string as `ToInteger` does, as hexadecimal with a `0x` prefix or decimal
+/// otherwise
+fn parse_integer(s: &str, width: IntegerWidth) -> u64 {
+    let s = s.trim_start();
+    let (digits, radix) = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(digits) => (digits, 16),
+        None => (s, 10),
+    };
+    let value = digits
+        .chars()
+        .map_while(|c| c.to_digit(radix))
+        .fold(0u64, |acc, digit| {
+            acc.wrapping_mul(radix as u64).wrapping_add(digit as u64)
+        });
+    width.truncate(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aml::parser::tests::pkg;
+    use alloc::rc::Rc;
+    use core::cell::RefCell;
+
+    struct TestHost;
+
+    impl Host for TestHost {
+        fn timer(&mut self) -> u64 {
+            0
+        }
+    }
+
+    /// Returns a serialized method without arguments
+    fn method(name: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let length = 6 + body.len();
+        assert!(length < 0x40);
+        [&[0x14, length as u8], &name[..], &[0x00], body].concat()
+    }
+
+    fn interpreter(aml: &[&[u8]]) -> Interpreter<TestHost> {
+        let mut interpreter = Interpreter::new(TestHost);
+        interpreter.load(&aml.concat()).unwrap();
+        interpreter
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        Read(u64, u32),
+        Write(u64, u32, u64),
+        Acquire(NodeId),
+        Release(NodeId),
+    }
+
+    type Log = Rc<RefCell<Vec<Event>>>;
+
+    /// Host recording the mutexes acquired and released
+    struct RecordingHost(Log);
+
+    impl Host for RecordingHost {
+        fn timer(&mut self) -> u64 {
+            0
+        }
+
+        fn acquire(&mut self, mutex: NodeId, _timeout: u16) -> bool {
+            self.0.borrow_mut().push(Event::Acquire(mutex));
+            true
+        }
+
+        fn release(&mut self, mutex: NodeId) {
+            self.0.borrow_mut().push(Event::Release(mutex));
+        }
+    }
+
+    /// `SystemIO` handler backed by memory, recording each access
+    struct RecordingHandler {
+        bytes: BTreeMap<u64, u8>,
+        log: Log,
+    }
+
+    impl RegionHandler for RecordingHandler {
+        fn read(&mut self, access: &RegionAccess<'_>) -> Result<u64> {
+            let address = access.address()?;
+            let value = (0..access.bit_width as u64 / 8).rev().fold(0, |value, i| {
+                value << 8 | *self.bytes.get(&(address + i)).unwrap_or(&0) as u64
+            });
+            let event = Event::Read(address, access.bit_width);
+            self.log.borrow_mut().push(event);
+            Ok(value)
+        }
+
+        fn write(&mut self, access: &RegionAccess<'_>, value: u64) -> Result<()> {
+            let address = access.address()?;
+            for i in 0..access.bit_width as u64 / 8 {
+                self.bytes.insert(address + i, (value >> (i * 8)) as u8);
+            }
+            let event = Event::Write(address, access.bit_width, value);
+            self.log.borrow_mut().push(event);
+            Ok(())
+        }
+    }
+
+    /// Load `aml` into an interpreter whose `SystemIO` space holds `bytes` at 0x100
+    fn recording(aml: &[&[u8]], bytes: &[u8]) -> (Interpreter<RecordingHost>, Log) {
+        let log = Log::default();
+        let mut interpreter = Interpreter::new(RecordingHost(log.clone()));
+        let handler = RecordingHandler {
+            bytes: (0x100..).zip(bytes.iter().copied()).collect(),
+            log: log.clone(),
+        };
+        interpreter.install_region_handler(AddressSpace::SYSTEM_IO, Box::new(handler));
+        interpreter.load(&aml.concat()).unwrap();
+        (interpreter, log)
+    }
+
+    /// `OperationRegion (REG0, SystemIO, 0x100, 0x10)`
+    const REG0: &[u8] = b"\x5b\x80REG0\x01\x0b\x00\x01\x0a\x10";
+
+    /// Returns `Field (REG0, ...)` with the given flags and field list
+    fn field(flags: u8, elements: &[u8]) -> Vec<u8> {
+        let body = [&b"REG0"[..], &[flags], elements].concat();
+        [&b"\x5b\x81"[..], &pkg(&body)].concat()
+    }
+
+    const INTEGER_TO_BUFFER: AmlError = AmlError::Conversion(ConversionError {
+        from: ObjectType::INTEGER,
+        to: ObjectType::BUFFER,
+    });
+
+    #[test]
+    fn else_if_chains() {
+        const ARMS: u8 = 24;
+        // If (Arg0 == 0) { Return (0) } ElseIf (Arg0 == 1) { Return (1) } ...
+        // Else { Return (0xff) }, with each comparison against `lhs`
+        let chain = |lhs: &[u8]| {
+            let mut aml = vec![0xa4, 0x0a, 0xff];
+            for arm in (0..ARMS).rev() {
+                let body = [&[0x93][..], lhs, &[0x0a, arm, 0xa4, 0x0a, arm]].concat();
+                aml = [&[0xa0][..], &pkg(&body), &[0xa1], &pkg(&aml)].concat();
+            }
+            aml
+        };
+        // Switch (ToInteger (Arg0)) { Case (0) { Return (0) } ... Default { Return (0xff) } },
+        // as compiled by iasl
+        let switch = [
+            &b"\x08_T_0\x00\x70\x99\x68\x00_T_0"[..],
+            &chain(b"_T_0"),
+            &[0xa5],
+        ]
+        .concat();
+        let switch = [&[0xa2][..], &pkg(&[&[0x01][..], &switch].concat())].concat();
+
+        // Scope (\_SB) { Device (DEV0) { Method (_DSM, 1) { ... } Method (SWCH, 1) { ... } } }
+        let dsm = [
+            &[0x14][..],
+            &pkg(&[&b"_DSM\x01"[..], &chain(&[0x68])].concat()),
+        ]
+        .concat();
+        let swch = [&[0x14][..], &pkg(&[&b"SWCH\x01"[..], &switch].concat())].concat();
+        let device = [
+            &b"\x5b\x82"[..],
+            &pkg(&[&b"DEV0"[..], &dsm, &swch].concat()),
+        ]
+        .concat();
+        let scope = [&[0x10][..], &pkg(&[&b"\\_SB_"[..], &device].concat())].concat();
+
+        let mut interpreter = interpreter(&[&scope]);
+        for method in ["\\_SB.DEV0._DSM", "\\_SB.DEV0.SWCH"] {
+            for arg in [0, 1, ARMS - 1, ARMS] {
+                let expected = if arg < ARMS { arg.into() } else { 0xff };
+                assert_eq!(
+                    interpreter.evaluate(method, vec![Value::Integer(arg.into())]),
+                    Ok(Value::Integer(expected))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn update_rules() {
+        // Field (REG0, ByteAcc, NoLock, Preserve) { , 2, PRE0, 4 }
+        // Field (REG0, ByteAcc, NoLock, WriteAsOnes) { Offset (1), , 2, ONE0, 4 }
+        // Field (REG0, ByteAcc, NoLock, WriteAsZeros) { Offset (2), , 2, ZER0, 4 }
+        let pre0 = field(0x01, b"\x00\x02PRE0\x04");
+        let one0 = field(0x21, b"\x00\x0aONE0\x04");
+        let zer0 = field(0x41, b"\x00\x12ZER0\x04");
+        // Method (TST1) { PRE0 = 0x0a; ONE0 = 0x0a; ZER0 = 0x0a }
+        let tst1 = method(b"TST1", b"\x70\x0a\x0aPRE0\x70\x0a\x0aONE0\x70\x0a\x0aZER0");
+        let (mut interpreter, log) = recording(&[REG0, &pre0, &one0, &zer0, &tst1], &[0x81; 3]);
+        assert_eq!(
+            interpreter.evaluate("\\TST1", vec![]),
+            Ok(Value::Uninitialized)
+        );
+        assert_eq!(*log.borrow(), [
+            Event::Read(0x100, 8),
+            Event::Write(0x100, 8, 0xa9),
+            Event::Write(0x101, 8, 0xeb),
+            Event::Write(0x102, 8, 0x28),
+        ]);
+    }
+
+    #[test]
+    fn field_spanning_units() {
+        // Field (REG0, ByteAcc, NoLock, Preserve) { Offset (4), , 4, SPAN, 8 }
+        let span = field(0x01, b"\x00\x24SPAN\x08");
+        // Method (TST1) { Return (SPAN) }
+        // Method (TST2) { SPAN = 0x12 }
+        let tst1 = method(b"TST1", b"\xa4SPAN");
+        let tst2 = method(b"TST2", b"\x70\x0a\x12SPAN");
+        let bytes = [0, 0, 0, 0, 0xab, 0xcd];
+        let (mut interpreter, log) = recording(&[REG0, &span, &tst1, &tst2], &bytes);
+        assert_eq!(
+            interpreter.evaluate("\\TST1", vec![]),
+            Ok(Value::Integer(0xda))
+        );
+        assert_eq!(log.take(), [Event::Read(0x104, 8), Event::Read(0x105, 8)]);
+        assert_eq!(
+            interpreter.evaluate("\\TST2", vec![]),
+            Ok(Value::Uninitialized)
+        );
+        assert_eq!(log.take(), [
+            Event::Read(0x104, 8),
+            Event::Write(0x104, 8, 0x2b),
+            Event::Read(0x105, 8),
+            Event::Write(0x105, 8, 0xc1),
+        ]);
+    }
+
+    #[test]
+    fn index_field() {
+        // Field (REG0, ByteAcc, NoLock, Preserve) { IDX0, 8, DAT0, 8 }
+        // IndexField (IDX0, DAT0, ByteAcc, NoLock, Preserve) { Offset (2), IFLD, 8 }
+        let fields = field(0x01, b"IDX0\x08DAT0\x08");
+        let index = [&b"\x5b\x86"[..], &pkg(b"IDX0DAT0\x01\x00\x10IFLD\x08")].concat();
+        // Method (TST1) { IFLD = 0x55; Return (IFLD) }
+        let tst1 = method(b"TST1", b"\x70\x0a\x55IFLD\xa4IFLD");
+        let (mut interpreter, log) = recording(&[REG0, &fields, &index, &tst1], &[]);
+        assert_eq!(
+            interpreter.evaluate("\\TST1", vec![]),
+            Ok(Value::Integer(0x55))
+        );
+        assert_eq!(*log.borrow(), [
+            Event::Write(0x100, 8, 2),
+            Event::Write(0x101, 8, 0x55),
+            Event::Write(0x100, 8, 2),
+            Event::Read(0x101, 8),
+        ]);
+    }
+
+    #[test]
+    fn bank_field() {
+        // Field (REG0, ByteAcc, NoLock, Preserve) { BNK0, 8 }
+        // BankField (REG0, BNK0, 1, ByteAcc, NoLock, Preserve) { Offset (2), BFL1, 8 }
+        // BankField (REG0, BNK0, 2, ByteAcc, NoLock, Preserve) { Offset (2), BFL2, 8 }
+        let bnk0 = field(0x01, b"BNK0\x08");
+        let bfl1 = [&b"\x5b\x87"[..], &pkg(b"REG0BNK0\x01\x01\x00\x10BFL1\x08")].concat();
+        let bfl2 = [
+            &b"\x5b\x87"[..],
+            &pkg(b"REG0BNK0\x0a\x02\x01\x00\x10BFL2\x08"),
+        ]
+        .concat();
+        // Method (TST1) { BFL1 = 0x11; BFL2 = 0x22; Return (BFL1) }
+        let tst1 = method(b"TST1", b"\x70\x0a\x11BFL1\x70\x0a\x22BFL2\xa4BFL1");
+        let (mut interpreter, log) = recording(&[REG0, &bnk0, &bfl1, &bfl2, &tst1], &[]);
+        assert_eq!(
+            interpreter.evaluate("\\TST1", vec![]),
+            Ok(Value::Integer(0x22))
+        );
+        assert_eq!(*log.borrow(), [
+            Event::Write(0x100, 8, 1),
+            Event::Write(0x102, 8, 0x11),
+            Event::Write(0x100, 8, 2),
+            Event::Write(0x102, 8, 0x22),
+            Event::Write(0x100, 8, 1),
+            Event::Read(0x102, 8),
+        ]);
+    }
+
+    #[test]
+    fn global_lock() {
+        // Field (REG0, ByteAcc, Lock, Preserve) { LCK0, 8 }
+        let lck0 = field(0x11, b"LCK0\x08");
+        // Method (TST1) { LCK0 = 5; Return (LCK0) }
+        let tst1 = method(b"TST1", b"\x70\x0a\x05LCK0\xa4LCK0");
+        let (mut interpreter, log) = recording(&[REG0, &lck0, &tst1], &[]);
+        assert_eq!(
+            interpreter.evaluate("\\TST1", vec![]),
+            Ok(Value::Integer(5))
+        );
+        let gl = interpreter.namespace().lookup_path("\\_GL").unwrap();
+        assert_eq!(*log.borrow(), [
+            Event::Acquire(gl),
+            Event::Write(0x100, 8, 5),
+            Event::Release(gl),
+            Event::Acquire(gl),
+            Event::Read(0x100, 8),
+            Event::Release(gl),
+        ]);
+    }
+
+    #[test]
+    fn size_of_integer() {
+        // Method (TST1) { Local0 = 5; Return (SizeOf (Local0)) }
+        let tst1 = method(b"TST1", &[0x70, 0x0a, 0x05, 0x60, 0xa4, 0x87, 0x60]);
+        let mut interpreter = interpreter(&[&tst1]);
+        assert_eq!(
+            interpreter.evaluate("\\TST1", vec![]),
+            Err(INTEGER_TO_BUFFER)
+        );
+    }
+
+    #[test]
+    fn create_field_on_integer() {
+        // Method (TST1) { Local0 = 5; CreateDWordField (Local0, 0, FOO) }
+        let body = [&[0x70, 0x0a, 0x05, 0x60, 0x8a, 0x60, 0x00][..], b"FOO_"].concat();
+        let tst1 = method(b"TST1", &body);
+        let mut interpreter = interpreter(&[&tst1]);
+        assert_eq!(
+            interpreter.evaluate("\\TST1", vec![]),
+            Err(INTEGER_TO_BUFFER)
+        );
+    }
+
+    #[test]
+    fn reference_to_method_object() {
+        // Method (TST1) { Name (XXX, 5); Return (RefOf (XXX)) }
+        // Method (TST2) { Return (DerefOf (TST1 ())) }
+        let tst1 = method(b"TST1", b"\x08XXX_\x0a\x05\xa4\x71XXX_");
+        let tst2 = method(b"TST2", b"\xa4\x83TST1");
+        // Method (TST3) { Device (DEV0) {} Return (RefOf (DEV0)) }
+        // Method (TST4) { Local0 = TST3 (); TST1 (); Return (ObjectType (Local0)) }
+        let tst3 = method(b"TST3", b"\x5b\x82\x05DEV0\xa4\x71DEV0");
+        let tst4 = method(b"TST4", b"\x70TST3\x60TST1\xa4\x8e\x60");
+        let mut interpreter = interpreter(&[&tst1, &tst2, &tst3, &tst4]);
+        assert_eq!(
+            interpreter.evaluate("\\TST2", vec![]),
+            Ok(Value::Integer(5))
+        );
+        assert_eq!(
+            interpreter.evaluate("\\TST4", vec![]),
+            Err(AmlError::InvalidReference)
+        );
+    }
+
+    #[test]
+    fn reference_to_popped_frame() {
+        // Method (TST1) { Local0 = 7; Return (RefOf (Local0)) }
+        // Method (TST2) { Local0 = 9; Return (DerefOf (TST1 ())) }
+        let tst1 = method(b"TST1", &[0x70, 0x0a, 0x07, 0x60, 0xa4, 0x71, 0x60]);
+        let tst2 = method(b"TST2", b"\x70\x0a\x09\x60\xa4\x83TST1");
+        // Name (GLOB, 0)
+        // Method (TST3) { Local0 = 7; CopyObject (RefOf (Local0), GLOB) }
+        // Method (TST4) { TST3 (); Local0 = 1; Return (DerefOf (GLOB)) }
+        let glob = b"\x08GLOB\x00";
+        let tst3 = method(b"TST3", b"\x70\x0a\x07\x60\x9d\x71\x60GLOB");
+        let tst4 = method(b"TST4", b"TST3\x70\x01\x60\xa4\x83GLOB");
+        let mut interpreter = interpreter(&[&tst1, &tst2, glob, &tst3, &tst4]);
+        assert_eq!(
+            interpreter.evaluate("\\TST2", vec![]),
+            Ok(Value::Integer(7))
+        );
+        assert_eq!(
+            interpreter.evaluate("\\TST4", vec![]),
+            Err(AmlError::InvalidReference)
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Name Segment
+///
+/// A four-character name, where the first character is an uppercase letter or `_` and the
+/// remaining characters are uppercase letters, digits, or `_`. Shorter names are padded with
+/// trailing underscores.
+#[repr(transparent)]
+#[derive(Clone, Copy, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct NameSeg(pub [u8; 4]);
+
+impl NameSeg {
+    /// `_SB_`, the root of the device tree
+    pub const SB: NameSeg = NameSeg(*b"_SB_");
+    /// `_GPE`, the scope of general-purpose event handlers
+    pub const GPE: NameSeg = NameSeg(*b"_GPE");
+    /// `_PR_`, the legacy scope of processor objects
+    pub const PR: NameSeg = NameSeg(*b"_PR_");
+    /// `_TZ_`, the legacy scope of thermal zones
+    pub const TZ: NameSeg = NameSeg(*b"_TZ_");
+    /// `_SI_`, the system indicator scope
+    pub const SI: NameSeg = NameSeg(*b"_SI_");
+
+    /// Create a `NameSeg` from four bytes, returning `None` if they are not a valid name
+    pub const fn new(bytes: [u8; 4]) -> Option<NameSeg> {
+        if !is_lead_name_char(bytes[0])
+            || !is_name_char(bytes[1])
+            || !is_name_char(bytes[2])
+            || !is_name_char(bytes[3])
+        {
+            return None;
+        }
+        Some(NameSeg(bytes))
+    }
+
+    /// Create a `NameSeg` from a string of up to four characters, padding it with `_`
+    pub fn from_asl(s: &str) -> Option<NameSeg> {
+        let mut bytes = [b'_'; 4];
+        if s.is_empty() || s.len() > 4 {
+            return None;
+        }
+        bytes[..s.len()].copy_from_slice(s.as_bytes());
+        Self::new(bytes)
+    }
+
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8; 4] {
+        &self.0
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        // A valid `NameSeg` only contains ASCII, but the field is public.
+        core::str::from_utf8(&self.0).unwrap_or("????")
+    }
+}
+
+impl fmt::Debug for NameSeg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
+
+impl fmt::Display for NameSeg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+pub(crate) const fn is_lead_name_char(byte: u8) -> bool {
+    matches!(byte, b'A'..=b'Z' | b'_')
+}
+
+pub(crate) const fn is_name_char(byte: u8) -> bool {
+    matches!(byte, b'A'..=b'Z' | b'0'..=b'9' | b'_')
+}
+
+/// Prefix of a [`NameString`]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum NamePrefix {
+    /// The name is relative to the current scope, and single-segment names are subject to
+    /// the namespace search rules
+    None,
+    /// The name is absolute, starting with `\`
+    Root,
+    /// The name is relative to the `n`th parent of the current scope, starting with `n`
+    /// copies of `^`
+    Parent(usize),
+}
+
+/// Name String
+///
+/// A path into the ACPI namespace, made up of a [`NamePrefix`] and zero or more segments.
+#[derive(Clone, Eq, Hash, PartialEq)]
+pub struct NameString {
+    pub prefix: NamePrefix,
+    pub segments: Vec<NameSeg>,
+}
+
+impl NameString {
+    /// Create an absolute name from a list of segments
+    pub fn absolute(segments: &[NameSeg]) -> NameString {
+        Self {
+            prefix: NamePrefix::Root,
+            segments: segments.to_vec(),
+        }
+    }
+
+    /// Returns `true` if this is the null name, which has no prefix and no segments
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        self.prefix == NamePrefix::None && self.segments.is_empty()
+    }
+
+    /// Returns `true` if the namespace search rules apply to this name
+    ///
+    /// This is only the case for names without a prefix made up of a single segment.
+    #[inline]
+    pub fn is_search_name(&self) -> bool {
+        self.prefix == NamePrefix::None && self.segments.len() == 1
+    }
+
+    /// Returns the last segment of the name, or `None` for the root or the null name
+    #[inline]
+    pub fn last(&self) -> Option<NameSeg> {
+        self.segments.last().copied()
+    }
+
+    /// Resolve this name relative to `scope`, without applying the search rules
+    ///
+    /// Returns `None` if the name refers to a parent of the root.
+    pub fn resolve(&self, scope: &[NameSeg]) -> Option<Vec<NameSeg>> {
+        let base = match self.prefix {
+            NamePrefix::None => scope,
+            NamePrefix::Root => &[],
+            NamePrefix::Parent(n) => scope.get(..scope.len().checked_sub(n)?)?,
+        };
+        let mut path = Vec::with_capacity(base.len() + self.segments.len());
+        path.extend_from_slice(base);
+        path.extend_from_slice(&self.segments);
+        Some(path)
+    }
+
+    /// Parse a name in ASL syntax, such as `\_SB.PCI0` or `^^FOO`
+    pub fn from_asl(s: &str) -> Option<NameString> {
+        let (prefix, rest) = if let Some(rest) = s.strip_prefix('\\') {
+            (NamePrefix::Root, rest)
+        } else {
+            let rest = s.trim_start_matches('^');
+            match s.len() - rest.len() {
+                0 => (NamePrefix::None, rest),
+                n => (NamePrefix::Parent(n), rest),
+            }
+        };
+        let segments = if rest.is_empty() {
+            Vec::new()
+        } else {
+            rest.split('.')
+                .map(NameSeg::from_asl)
+                .collect::<Option<Vec<_>>>()?
+        };
+        Some(Self { prefix, segments })
+    }
+}
+
+impl fmt::Debug for NameString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{self}\"")
+    }
+}
+
+impl fmt::Display for NameString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.prefix {
+            NamePrefix::None => {}
+            NamePrefix::Root => f.write_str("\\")?,
+            NamePrefix::Parent(n) => {
+                for _ in 0..n {
+                    f.write_str("^")?;
+                }
+            }
+        }
+        for (i, segment) in self.segments.iter().enumerate() {
+            if i != 0 {
+                f.write_str(".")?;
+            }
+            write!(f, "{segment}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,1112 @@
+use super::{
+    name::{NamePrefix, NameSeg, NameString},
+    parser::{ParseError, Parser},
+    term::{AccessType, Connection, Expr, FieldElement, FieldFlags, MethodFlags, Term},
+    value::{Reference, Value, MAX_OBJECT_SIZE},
+};
+use crate::{
+    address::AddressSpace,
+    sdt::{
+        dsdt::{DefinitionBlock, IntegerWidth},
+        Bridge, RootTable,
+    },
+};
+use alloc::{boxed::Box, collections::BTreeMap, sync::Arc, vec, vec::Vec};
+use core::fmt;
+
+/// Handle to a node in a [`Namespace`]
+///
+/// Handles carry the generation of the slot they were allocated from, so a handle to a node
+/// which has been removed never refers to a node created later in the same slot.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct NodeId {
+    index: usize,
+    generation: u32,
+}
+
+/// Node in a [`Namespace`]
+#[derive(Clone, Debug)]
+pub struct Node {
+    name: NameSeg,
+    parent: Option<NodeId>,
+    children: BTreeMap<NameSeg, NodeId>,
+    object: Object,
+}
+
+impl Node {
+    /// Returns the name of this node, the root is named `\___`
+    #[inline]
+    pub fn name(&self) -> NameSeg {
+        self.name
+    }
+
+    /// Returns the parent of this node, or `None` for the root
+    #[inline]
+    pub fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    #[inline]
+    pub fn object(&self) -> &Object {
+        &self.object
+    }
+
+    /// Returns the child of this node named `name`
+    #[inline]
+    pub fn child(&self, name: NameSeg) -> Option<NodeId> {
+        self.children.get(&name).copied()
+    }
+
+    /// Returns an iterator over the children of this node, ordered by name
+    pub fn children(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.children.values().copied()
+    }
+}
+
+/// Object attached to a node in a [`Namespace`]
+#[derive(Clone, Debug)]
+pub enum Object {
+    /// A scope with no other object, such as the root or `\_SB`
+    Scope,
+    /// A named data object, declared with `Name`
+    Name(Value),
+    /// Another name for the object at the target node
+    Alias(NodeId),
+    BufferField(BufferField),
+    DataRegion {
+        signature: Expr,
+        oem_id: Expr,
+        oem_table_id: Expr,
+    },
+    Device,
+    Event,
+    FieldUnit(FieldUnit),
+    Method {
+        flags: MethodFlags,
+        body: Arc<[Term]>,
+    },
+    Mutex {
+        sync_level: u8,
+    },
+    OpRegion {
+        space: AddressSpace,
+        offset: Expr,
+        length: Expr,
+    },
+    PowerResource {
+        system_level: u8,
+        resource_order: u16,
+    },
+    Processor {
+        id: u8,
+        pblk_addr: u32,
+        pblk_len: u8,
+    },
+    ThermalZone,
+}
+
+/// Buffer Field
+///
+/// A field within a buffer, created by `CreateField` or one of its fixed-size variants.
+#[derive(Clone, Debug)]
+pub struct BufferField {
+    /// The buffer containing the field
+    pub buffer: Reference,
+    pub bit_offset: u64,
+    pub bit_length: u64,
+}
+
+/// Field Unit
+///
+/// A named element of a `Field`, `IndexField`, or `BankField`.
+#[derive(Clone, Debug)]
+pub struct FieldUnit {
+    pub kind: FieldKind,
+    pub flags: FieldFlags,
+    /// Access type in effect for this field unit, which may have been changed from the
+    /// access type in `flags` by an `AccessAs` element
+    pub access_type: AccessType,
+    /// Access attribute set by the last `AccessAs` element, if any
+    pub access_attrib: Option<AccessAttrib>,
+    /// Connection set by the last `Connection` element, if any
+    pub connection: Option<Connection>,
+    pub bit_offset: u64,
+    pub bit_length: u32,
+}
+
+#[derive(Clone, Debug)]
+pub enum FieldKind {
+    /// A field within an operation region
+    Region(NodeId),
+    /// A field accessed by writing an offset to the `index` field unit and then accessing
+    /// the `data` field unit
+    Index { index: NodeId, data: NodeId },
+    /// A field within an operation region which is only accessible after writing
+    /// `bank_value` to the `bank` field unit
+    Bank {
+        region: NodeId,
+        bank: NodeId,
+        bank_value: Expr,
+    },
+}
+
+/// Access attribute of a field unit, used by `SMBus` and `GenericSerialBus` regions
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct AccessAttrib {
+    /// Attribute kind, see [`FieldElement::Access`]
+    pub kind: u8,
+    pub attrib: u8,
+    /// Access length of an extended access attribute
+    pub length: Option<u8>,
+}
+
+/// Error returned when loading a definition block
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LoadError {
+    /// The definition block could not be parsed
+    Parse(ParseError),
+    /// A name referenced by the definition block does not exist
+    NotFound(NameString),
+    /// A definition block could not be mapped
+    Table(crate::Error),
+}
+
+impl From<ParseError> for LoadError {
+    fn from(error: ParseError) -> Self {
+        Self::Parse(error)
+    }
+}
+
+impl From<crate::Error> for LoadError {
+    fn from(error: crate::Error) -> Self {
+        Self::Table(error)
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(error) => write!(f, "{error}"),
+            Self::NotFound(name) => write!(f, "{name} does not exist"),
+            Self::Table(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+/// ACPI Namespace
+///
+/// A tree of named objects, built by loading definition blocks. The root and the predefined
+/// scopes `\_GPE`, `\_PR`, `\_SB`, `\_SI` and `\_TZ` always exist, as do the predefined
+/// objects `\_GL`, `\_OS`, `\_OSI` and `\_REV`.
+#[derive(Clone, Debug)]
+pub struct Namespace {
+    nodes: Vec<Slot>,
+    /// Indices of the slots whose node has been removed
+    free: Vec<usize>,
+    parser: Parser,
+    integer_width: IntegerWidth,
+}
+
+#[derive(Clone, Debug)]
+struct Slot {
+    /// Incremented each time the node in this slot is removed
+    generation: u32,
+    node: Option<Node>,
+}
+
+/// Result of declaring a named object with [`Namespace::declare()`]
+pub(crate) enum Declaration {
+    /// The object was created, or already existed
+    Done,
+    /// A `Name` object was created with an uninitialized value
+    Name(NodeId, Expr),
+    /// The terms must be loaded within the scope of the node
+    Scope(NodeId, Vec<Term>),
+    /// The term does not declare a named object
+    Code(Box<Term>),
+}
+
+/// Error returned by [`Namespace::data_value()`]
+pub(crate) enum DataError {
+    /// The data object refers to a name which does not exist
+    NotFound,
+    /// The expression is not a data object
+    NotData,
+}
+
+/// Value of the `\_OS` object
+pub const OS_NAME: &str = "Microsoft Windows NT";
+
+/// Value of the `\_REV` object
+pub const OS_REVISION: u64 = 2;
+
+impl Default for Namespace {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Namespace {
+    const ROOT: NodeId = NodeId {
+        index: 0,
+        generation: 0,
+    };
+
+    /// Create a namespace containing only the predefined scopes and objects
+    pub fn new() -> Namespace {
+        let mut namespace = Self {
+            nodes: vec![Slot {
+                generation: 0,
+                node: Some(Node {
+                    name: NameSeg(*b"\\___"),
+                    parent: None,
+                    children: BTreeMap::new(),
+                    object: Object::Scope,
+                }),
+            }],
+            free: Vec::new(),
+            parser: Parser::new(),
+            integer_width: IntegerWidth::Bits64,
+        };
+        for name in [
+            NameSeg::GPE,
+            NameSeg::PR,
+            NameSeg::SB,
+            NameSeg::SI,
+            NameSeg::TZ,
+        ] {
+            namespace.insert(Self::ROOT, name, Object::Scope);
+        }
+        let objects = [
+            (*b"_GL_", Object::Mutex { sync_level: 0 }),
+            (*b"_OS_", Object::Name(Value::from(OS_NAME))),
+            (*b"_REV", Object::Name(Value::Integer(OS_REVISION))),
+            // `\_OSI` is implemented by the interpreter.
+            (*b"_OSI", Object::Method {
+                flags: MethodFlags(1),
+                body: Arc::new([]),
+            }),
+        ];
+        for (name, object) in objects {
+            namespace.insert(Self::ROOT, NameSeg(name), object);
+        }
+        namespace.parser.declare_method(&[NameSeg(*b"_OSI")], 1);
+        namespace
+    }
+
+    /// Returns the root node
+    #[inline]
+    pub fn root(&self) -> NodeId {
+        Self::ROOT
+    }
+
+    /// Returns the node with the handle `id`
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `id` was not returned by this namespace, or if the node has
+    /// been removed, see [`Namespace::get()`] for a non-panicking version.
+    #[inline]
+    pub fn node(&self, id: NodeId) -> &Node {
+        match self.get(id) {
+            Some(node) => node,
+            None => panic!("acpi: invalid NodeId"),
+        }
+    }
+
+    /// Returns the node with the handle `id`, or `None` if it has been removed
+    #[inline]
+    pub fn get(&self, id: NodeId) -> Option<&Node> {
+        match self.nodes.get(id.index)? {
+            Slot {
+                generation,
+                node: Some(node),
+            } if *generation == id.generation => Some(node),
+            _ => None,
+        }
+    }
+
+    /// Returns the node with the handle `id`, see [`Namespace::node()`]
+    #[inline]
+    pub(crate) fn node_mut(&mut self, id: NodeId) -> &mut Node {
+        match self.get_mut(id) {
+            Some(node) => node,
+            None => panic!("acpi: invalid NodeId"),
+        }
+    }
+
+    #[inline]
+    fn get_mut(&mut self, id: NodeId) -> Option<&mut Node> {
+        match self.nodes.get_mut(id.index)? {
+            Slot {
+                generation,
+                node: Some(node),
+            } if *generation == id.generation => Some(node),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the object attached to a node, or `None` if it has
+    /// been removed
+    #[inline]
+    pub(crate) fn object_mut(&mut self, id: NodeId) -> Option<&mut Object> {
+        self.get_mut(id).map(|node| &mut node.object)
+    }
+
+    /// Returns the width of integers, as decided by the revision of the DSDT
+    #[inline]
+    pub fn integer_width(&self) -> IntegerWidth {
+        self.integer_width
+    }
+
+    /// Set the width of integers
+    ///
+    /// This is done by [`Namespace::load_tables()`], and only needs to be called when
+    /// loading the DSDT by other means.
+    #[inline]
+    pub fn set_integer_width(&mut self, integer_width: IntegerWidth) {
+        self.integer_width = integer_width;
+    }
+
+    /// Returns the parser used to load definition blocks, which knows every declared method
+    #[inline]
+    pub fn parser(&self) -> &Parser {
+        &self.parser
+    }
+
+    #[inline]
+    pub(crate) fn parser_mut(&mut self) -> &mut Parser {
+        &mut self.parser
+    }
+
+    /// Returns the absolute path of a node
+    pub fn path(&self, id: NodeId) -> NameString {
+        let mut segments = Vec::new();
+        let mut node = self.node(id);
+        while let Some(parent) = node.parent {
+            segments.push(node.name);
+            node = self.node(parent);
+        }
+        segments.reverse();
+        NameString {
+            prefix: NamePrefix::Root,
+            segments,
+        }
+    }
+
+    /// Returns an iterator over every node in the namespace, in depth-first order
+    pub fn walk(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.walk_from(Self::ROOT)
+    }
+
+    /// Returns an iterator over `id` and all of its descendants, in depth-first order
+    pub fn walk_from(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        let mut stack = vec![id];
+        core::iter::from_fn(move || {
+            let id = stack.pop()?;
+            stack.extend(self.node(id).children.values().rev());
+            Some(id)
+        })
+    }
+
+    /// Look up `name` relative to `scope`
+    ///
+    /// Single-segment names without a prefix are searched for in `scope` and then in each of
+    /// its parents up to the root. Other names must match exactly. Aliases are followed to
+    /// the object they refer to.
+    pub fn lookup(&self, scope: NodeId, name: &NameString) -> Option<NodeId> {
+        let id = if name.is_search_name() {
+            let mut scope = Some(scope);
+            loop {
+                let node = self.node(scope?);
+                if let Some(id) = node.child(name.segments[0]) {
+                    break id;
+                }
+                scope = node.parent;
+            }
+        } else {
+            let mut id = self.base(scope, name.prefix)?;
+            for &segment in &name.segments {
+                id = self.node(self.resolve_alias(id)).child(segment)?;
+            }
+            id
+        };
+        Some(self.resolve_alias(id))
+    }
+
+    /// Look up an absolute path in ASL syntax, such as `\_SB.PCI0`
+    pub fn lookup_path(&self, path: &str) -> Option<NodeId> {
+        self.lookup(Self::ROOT, &NameString::from_asl(path)?)
+    }
+
+    /// Returns an iterator over all devices whose `_HID` is `hid`
+    ///
+    /// `hid` is either a compressed EISA ID such as `PNP0A08`, or an ACPI ID such as
+    /// `ACPI0007`. Devices whose `_HID` is a method are not returned, as it cannot be
+    /// evaluated while walking the namespace.
+    pub fn find_devices_by_hid<'a>(&'a self, hid: &'a str) -> impl Iterator<Item = NodeId> + 'a {
+        self.walk().filter(move |&id| {
+            let node = self.node(id);
+            matches!(node.object, Object::Device | Object::Processor { .. })
+                && node.child(NameSeg(*b"_HID")).is_some_and(|hid_id| {
+                    match &self.node(hid_id).object {
+                        Object::Name(Value::Integer(id)) => {
+                            EisaId(*id as u32).to_bytes() == hid.as_bytes()
+                        }
+                        Object::Name(Value::String(id)) => id == hid,
+                        _ => false,
+                    }
+                })
+        })
+    }
+
+    /// Load a definition block into the namespace
+    ///
+    /// Named objects are created, but code outside of methods is not executed, so objects
+    /// declared within an `If` at the top level of the block and buffer fields are not
+    /// loaded. Use [`Interpreter::load()`](super::Interpreter::load) to execute it.
+    ///
+    /// Declarations which refer to objects that do not exist, such as a `Scope` whose target
+    /// is declared by a later table, are skipped with a warning, like ACPICA does.
+    pub fn load(&mut self, aml: &[u8]) -> Result<(), LoadError> {
+        let terms = self.parser.parse(aml)?;
+        let mut names = Vec::new();
+        self.load_terms(Self::ROOT, terms, &mut names);
+        self.init_names(names);
+        Ok(())
+    }
+
+    /// Load the DSDT followed by every SSDT and PSDT
+    ///
+    /// The integer width is set according to the revision of the DSDT. Only a DSDT which
+    /// fails to load is returned as an error, other tables which cannot be mapped or loaded
+    /// are skipped with a warning, like ACPICA does.
+    pub fn load_tables<B: Bridge>(&mut self, tables: &RootTable<B>) -> Result<(), LoadError> {
+        for table in tables.try_definition_blocks() {
+            let table = match table {
+                Ok(table) => table,
+                Err(error) => {
+                    log::warn!("acpi: skipping definition block: {error}");
+                    continue;
+                }
+            };
+            if let DefinitionBlock::Dsdt(dsdt) = &table {
+                self.integer_width = dsdt.integer_width();
+                self.load(dsdt.aml())?;
+            } else if let Err(error) = self.load(table.aml()) {
+                let signature = table.header().signature;
+                log::warn!("acpi: failed to load {signature}: {error}");
+            }
+        }
+        Ok(())
+    }
+
+    fn load_terms(&mut self, scope: NodeId, terms: Vec<Term>, names: &mut Vec<(NodeId, Expr)>) {
+        for term in terms {
+            let declaration = match self.declare(scope, term, &mut Vec::new()) {
+                Ok(declaration) => declaration,
+                Err(error) => {
+                    log::warn!(
+                        "acpi: skipping declaration in {}: {error}",
+                        self.path(scope)
+                    );
+                    continue;
+                }
+            };
+            match declaration {
+                Declaration::Done => {}
+                Declaration::Name(id, value) => self.init_name(id, value, names),
+                Declaration::Scope(id, terms) => self.load_terms(id, terms, names),
+                Declaration::Code(term) => {
+                    log::debug!(
+                        "acpi: ignoring code in {} while loading: {term:?}",
+                        self.path(scope)
+                    );
+                }
+            }
+        }
+    }
+
+    /// Initialize the value of a `Name` object
+    ///
+    /// Packages which refer to objects that do not exist yet are added to `pending`, to be
+    /// initialized by [`Namespace::init_names()`] once the definition block is loaded.
+    pub(crate) fn init_name(&mut self, id: NodeId, expr: Expr, pending: &mut Vec<(NodeId, Expr)>) {
+        let scope = self.node(id).parent.unwrap_or(Self::ROOT);
+        match self.data_value(scope, &expr, false) {
+            Ok(value) => self.node_mut(id).object = Object::Name(value),
+            Err(DataError::NotFound) => pending.push((id, expr)),
+            Err(DataError::NotData) => {
+                log::warn!("acpi: {} is not a data object", self.path(id));
+            }
+        }
+    }
+
+    /// Initialize the values of `Name` objects once the whole definition block is loaded
+    ///
+    /// Names within packages which do not exist are replaced by uninitialized values.
+    pub(crate) fn init_names(&mut self, names: Vec<(NodeId, Expr)>) {
+        for (id, expr) in names {
+            let scope = self.node(id).parent.unwrap_or(Self::ROOT);
+            let value = match self.data_value(scope, &expr, true) {
+                Ok(value) => value,
+                Err(_) => {
+                    log::warn!("acpi: {} is not a data object", self.path(id));
+                    Value::Uninitialized
+                }
+            };
+            self.node_mut(id).object = Object::Name(value);
+        }
+    }
+
+    /// Evaluate a data object, as found in a `Name` declaration
+    ///
+    /// Names within packages become references to the objects they name. If `lenient` is
+    /// `true`, names which do not exist are replaced by uninitialized values rather than
+    /// failing.
+    pub(crate) fn data_value(
+        &self,
+        scope: NodeId,
+        expr: &Expr,
+        lenient: bool,
+    ) -> Result<Value, DataError> {
+        let integer = |expr: &Expr| match self.data_value(scope, expr, lenient)? {
+            Value::Integer(value) => Ok(value),
+            _ => Err(DataError::NotData),
+        };
+        let value = match expr {
+            Expr::Integer(value) => Value::Integer(self.integer_width.truncate(*value)),
+            Expr::Ones => Value::Integer(self.integer_width.max()),
+            Expr::Revision => Value::Integer(super::interpreter::REVISION),
+            Expr::String(s) => Value::String(s.clone()),
+            Expr::Buffer { size, data } => {
+                let size = integer(size)?;
+                if size > MAX_OBJECT_SIZE as u64 {
+                    return Err(DataError::NotData);
+                }
+                let mut data = data.clone();
+                if data.len() < size as usize {
+                    data.resize(size as usize, 0);
+                }
+                Value::Buffer(data)
+            }
+            Expr::Package { count, elements } => {
+                let count = integer(count)?;
+                if count > MAX_OBJECT_SIZE as u64 {
+                    return Err(DataError::NotData);
+                }
+                let mut package = Vec::with_capacity(elements.len());
+                for element in elements {
+                    let value = match element {
+                        Expr::Name(name) => match self.lookup(scope, name) {
+                            Some(id) => Value::Reference(Reference::Named(id)),
+                            None if lenient => {
+                                log::warn!("acpi: {name} in package does not exist");
+                                Value::Uninitialized
+                            }
+                            None => return Err(DataError::NotFound),
+                        },
+                        element => self.data_value(scope, element, lenient)?,
+                    };
+                    package.push(value);
+                }
+                if package.len() < count as usize {
+                    package.resize(count as usize, Value::Uninitialized);
+                }
+                Value::Package(package)
+            }
+            _ => return Err(DataError::NotData),
+        };
+        Ok(value)
+    }
+
+    /// Declare the named object described by `term` in `scope`
+    ///
+    /// Newly created nodes are added to `created`. Objects which already exist are skipped
+    /// with a warning, except that the objects declared within a device, processor, power
+    /// resource or thermal zone are still added to the existing node.
+    pub(crate) fn declare(
+        &mut self,
+        scope: NodeId,
+        term: Term,
+        created: &mut Vec<NodeId>,
+    ) -> Result<Declaration, LoadError> {
+        match term {
+            Term::Alias { source, alias } => {
+                let target = self.find(scope, &source)?;
+                self.create(scope, &alias, Object::Alias(target), created)?;
+            }
+            Term::Name { name, value } => {
+                let object = Object::Name(Value::Uninitialized);
+                if let Some(id) = self.create(scope, &name, object, created)? {
+                    return Ok(Declaration::Name(id, value));
+                }
+            }
+            Term::Scope { name, terms } => {
+                let id = self.find(scope, &name)?;
+                return Ok(Declaration::Scope(id, terms));
+            }
+            Term::BankField {
+                region,
+                bank,
+                bank_value,
+                flags,
+                elements,
+            } => {
+                let kind = FieldKind::Bank {
+                    region: self.find(scope, &region)?,
+                    bank: self.find(scope, &bank)?,
+                    bank_value,
+                };
+                self.create_field(scope, kind, flags, elements, created)?;
+            }
+            Term::DataRegion {
+                name,
+                signature,
+                oem_id,
+                oem_table_id,
+            } => {
+                let object = Object::DataRegion {
+                    signature,
+                    oem_id,
+                    oem_table_id,
+                };
+                self.create(scope, &name, object, created)?;
+            }
+            Term::Device { name, terms } => {
+                return self.create_scope(scope, &name, Object::Device, terms, created);
+            }
+            Term::Event { name } => {
+                self.create(scope, &name, Object::Event, created)?;
+            }
+            // Externals only tell the parser about methods declared in other tables.
+            Term::External { .. } => {}
+            Term::Field {
+                region,
+                flags,
+                elements,
+            } => {
+                let kind = FieldKind::Region(self.find(scope, &region)?);
+                self.create_field(scope, kind, flags, elements, created)?;
+            }
+            Term::IndexField {
+                index,
+                data,
+                flags,
+                elements,
+            } => {
+                let kind = FieldKind::Index {
+                    index: self.find(scope, &index)?,
+                    data: self.find(scope, &data)?,
+                };
+                self.create_field(scope, kind, flags, elements, created)?;
+            }
+            Term::Method { name, flags, body } => {
+                let object = Object::Method {
+                    flags,
+                    body: body.into(),
+                };
+                self.create(scope, &name, object, created)?;
+            }
+            Term::Mutex { name, sync_level } => {
+                self.create(scope, &name, Object::Mutex { sync_level }, created)?;
+            }
+            Term::OpRegion {
+                name,
+                space,
+                offset,
+                length,
+            } => {
+                let object = Object::OpRegion {
+                    space,
+                    offset,
+                    length,
+                };
+                self.create(scope, &name, object, created)?;
+            }
+            Term::PowerResource {
+                name,
+                system_level,
+                resource_order,
+                terms,
+            } => {
+                let object = Object::PowerResource {
+                    system_level,
+                    resource_order,
+                };
+                return self.create_scope(scope, &name, object, terms, created);
+            }
+            Term::Processor {
+                name,
+                id,
+                pblk_addr,
+                pblk_len,
+                terms,
+            } => {
+                let object = Object::Processor {
+                    id,
+                    pblk_addr,
+                    pblk_len,
+                };
+                return self.create_scope(scope, &name, object, terms, created);
+            }
+            Term::ThermalZone { name, terms } => {
+                return self.create_scope(scope, &name, Object::ThermalZone, terms, created);
+            }
+            term => return Ok(Declaration::Code(Box::new(term))),
+        }
+        Ok(Declaration::Done)
+    }
+
+    /// Remove a node and all of its descendants
+    ///
+    /// Handles to the removed nodes become invalid.
+    pub(crate) fn remove(&mut self, id: NodeId) {
+        if self.get(id).is_none() {
+            return;
+        }
+        let slot = &mut self.nodes[id.index];
+        slot.generation = slot.generation.wrapping_add(1);
+        let Some(node) = slot.node.take() else {
+            return;
+        };
+        if let Some(parent) = node.parent.and_then(|parent| self.get_mut(parent)) {
+            parent.children.remove(&node.name);
+        }
+        for child in node.children.into_values() {
+            self.remove(child);
+        }
+        self.free.push(id.index);
+    }
+
+    fn base(&self, scope: NodeId, prefix: NamePrefix) -> Option<NodeId> {
+        match prefix {
+            NamePrefix::None => Some(scope),
+            NamePrefix::Root => Some(Self::ROOT),
+            NamePrefix::Parent(n) => (0..n).try_fold(scope, |id, _| self.node(id).parent),
+        }
+    }
+
+    fn resolve_alias(&self, mut id: NodeId) -> NodeId {
+        // Aliases always refer to an existing node which is not an alias, but the number of
+        // steps is bounded anyway.
+        for _ in 0..self.nodes.len() {
+            match self.node(id).object {
+                Object::Alias(target) => id = target,
+                _ => break,
+            }
+        }
+        id
+    }
+
+    fn find(&self, scope: NodeId, name: &NameString) -> Result<NodeId, LoadError> {
+        self.lookup(scope, name)
+            .ok_or_else(|| LoadError::NotFound(name.clone()))
+    }
+
+    fn insert(&mut self, parent: NodeId, name: NameSeg, object: Object) -> NodeId {
+        let node = Node {
+            name,
+            parent: Some(parent),
+            children: BTreeMap::new(),
+            object,
+        };
+        let id = match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.nodes[index];
+                slot.node = Some(node);
+                NodeId {
+                    index,
+                    generation: slot.generation,
+                }
+            }
+            None => {
+                self.nodes.push(Slot {
+                    generation: 0,
+                    node: Some(node),
+                });
+                NodeId {
+                    index: self.nodes.len() - 1,
+                    generation: 0,
+                }
+            }
+        };
+        self.node_mut(parent).children.insert(name, id);
+        id
+    }
+
+    /// Create a node for the object `name`, returning `None` if it already exists
+    pub(crate) fn create(
+        &mut self,
+        scope: NodeId,
+        name: &NameString,
+        object: Object,
+        created: &mut Vec<NodeId>,
+    ) -> Result<Option<NodeId>, LoadError> {
+        let not_found = || LoadError::NotFound(name.clone());
+        let (&last, parents) = name.segments.split_last().ok_or_else(not_found)?;
+        let mut parent = self.base(scope, name.prefix).ok_or_else(not_found)?;
+        for &segment in parents {
+            parent = self.resolve_alias(self.node(parent).child(segment).ok_or_else(not_found)?);
+        }
+        if let Some(existing) = self.node(parent).child(last) {
+            log::warn!("acpi: {} already exists", self.path(existing));
+            return Ok(None);
+        }
+        let id = self.insert(parent, last, object);
+        created.push(id);
+        Ok(Some(id))
+    }
+
+    fn create_scope(
+        &mut self,
+        scope: NodeId,
+        name: &NameString,
+        object: Object,
+        terms: Vec<Term>,
+        created: &mut Vec<NodeId>,
+    ) -> Result<Declaration, LoadError> {
+        let id = match self.create(scope, name, object, created)? {
+            Some(id) => id,
+            // Reopen the existing object, so its contents are not lost
+            None => self.find(scope, name)?,
+        };
+        Ok(Declaration::Scope(id, terms))
+    }
+
+    fn create_field(
+        &mut self,
+        scope: NodeId,
+        kind: FieldKind,
+        flags: FieldFlags,
+        elements: Vec<FieldElement>,
+        created: &mut Vec<NodeId>,
+    ) -> Result<(), LoadError> {
+        let mut access_type = flags.access_type();
+        let mut access_attrib = None;
+        let mut connection = None;
+        let mut bit_offset = 0;
+        for element in elements {
+            match element {
+                FieldElement::Named { name, bits } => {
+                    let field = FieldUnit {
+                        kind: kind.clone(),
+                        flags,
+                        access_type,
+                        access_attrib,
+                        connection: connection.clone(),
+                        bit_offset,
+                        bit_length: bits,
+                    };
+                    let name = NameString {
+                        prefix: NamePrefix::None,
+                        segments: vec![name],
+                    };
+                    self.create(scope, &name, Object::FieldUnit(field), created)?;
+                    bit_offset += bits as u64;
+                }
+                FieldElement::Reserved { bits } => bit_offset += bits as u64,
+                FieldElement::Access {
+                    access_type: new_access_type,
+                    attrib_kind,
+                    attrib,
+                } => {
+                    access_type = new_access_type;
+                    access_attrib = Some(AccessAttrib {
+                        kind: attrib_kind,
+                        attrib,
+                        length: None,
+                    });
+                }
+                FieldElement::ExtendedAccess {
+                    access_type: new_access_type,
+                    attrib,
+                    length,
+                } => {
+                    access_type = new_access_type;
+                    access_attrib = Some(AccessAttrib {
+                        kind: 0,
+                        attrib,
+                        length: Some(length),
+                    });
+                }
+                FieldElement::Connection(new_connection) => connection = Some(new_connection),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compressed EISA ID
+///
+/// Three uppercase letters and four hexadecimal digits, such as `PNP0A08`, packed into an
+/// integer as produced by the ASL `EisaId()` macro.
+#[repr(transparent)]
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
+pub struct EisaId(pub u32);
+
+impl EisaId {
+    /// Returns the seven ASCII characters of the ID
+    pub fn to_bytes(self) -> [u8; 7] {
+        const HEX: &[u8; 16] = b"0123456789ABCDEF";
+        let id = self.0.swap_bytes();
+        let letter = |shift: u32| b'@' + ((id >> shift) & 0x1f) as u8;
+        let digit = |shift: u32| HEX[((id >> shift) & 0xf) as usize];
+        [
+            letter(26),
+            letter(21),
+            letter(16),
+            digit(12),
+            digit(8),
+            digit(4),
+            digit(0),
+        ]
+    }
+}
+
+impl fmt::Debug for EisaId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EisaId(\"{self}\")")
+    }
+}
+
+impl fmt::Display for EisaId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.to_bytes() {
+            write!(f, "{}", byte as char)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aml::parser::tests::pkg;
+
+    fn scope(name: &[u8], body: &[u8]) -> Vec<u8> {
+        [&[0x10][..], &pkg(&[name, body].concat())].concat()
+    }
+
+    fn device(name: &[u8], body: &[u8]) -> Vec<u8> {
+        [&[0x5b, 0x82][..], &pkg(&[name, body].concat())].concat()
+    }
+
+    fn lookup(namespace: &Namespace, scope: &str, name: &str) -> Option<NodeId> {
+        let scope = namespace.lookup_path(scope).unwrap();
+        namespace.lookup(scope, &NameString::from_asl(name).unwrap())
+    }
+
+    #[test]
+    fn duplicate_device() {
+        // Device (\DEV0) { Name (A, 1) }
+        // Device (\DEV0) { Name (B, 2) }
+        let mut namespace = Namespace::new();
+        namespace
+            .load(b"\x5b\x82\x0d\\DEV0\x08A___\x0a\x01\x5b\x82\x0d\\DEV0\x08B___\x0a\x02")
+            .unwrap();
+        assert!(namespace.lookup_path("\\DEV0.A").is_some());
+        assert!(namespace.lookup_path("\\DEV0.B").is_some());
+    }
+
+    #[test]
+    fn missing_scope() {
+        // Name (\PKG0, Package () { \NAM0 })
+        // Scope (\_SB.PCI0) { Name (A, 1) }
+        // Name (\NAM0, 2)
+        let pkg0 = b"\x08\\PKG0\x12\x07\x01\\NAM0";
+        let pci0 = scope(b"\\/\x02_SB_PCI0", b"\x08A___\x0a\x01");
+        let nam0 = b"\x08\\NAM0\x0a\x02";
+        let mut namespace = Namespace::new();
+        namespace.load(&[&pkg0[..], &pci0, nam0].concat()).unwrap();
+        assert!(namespace.lookup_path("\\_SB.PCI0").is_none());
+        assert!(namespace.lookup_path("\\NAM0").is_some());
+        let pkg0 = namespace.lookup_path("\\PKG0").unwrap();
+        let Object::Name(Value::Package(package)) = namespace.node(pkg0).object() else {
+            panic!("PKG0 is not a package");
+        };
+        assert!(matches!(package[..], [Value::Reference(_)]));
+    }
+
+    #[test]
+    fn lookup_rules() {
+        // Scope (\_SB) {
+        //     Name (A, 1)
+        //     Device (DEV0) {
+        //         Name (B, 2)
+        //         Device (DEV1) { Scope (^^) { Name (C, 3) } }
+        //     }
+        //     Alias (DEV0, ALS0)
+        // }
+        let dev1 = device(b"DEV1", &scope(b"^^\x00", b"\x08C___\x0a\x03"));
+        let dev0 = device(b"DEV0", &[&b"\x08B___\x0a\x02"[..], &dev1].concat());
+        let body = [&b"\x08A___\x0a\x01"[..], &dev0, b"\x06DEV0ALS0"].concat();
+        let mut namespace = Namespace::new();
+        namespace.load(&scope(b"\\_SB_", &body)).unwrap();
+        let path = |path| namespace.lookup_path(path).unwrap();
+
+        // `^` refers to the parent of the current scope.
+        assert_eq!(
+            path("\\_SB.C"),
+            lookup(&namespace, "\\_SB.DEV0.DEV1", "^^C").unwrap()
+        );
+        assert_eq!(
+            lookup(&namespace, "\\_SB.DEV0", "^A"),
+            Some(path("\\_SB.A"))
+        );
+        assert_eq!(lookup(&namespace, "\\_SB.DEV0", "^B"), None);
+        assert_eq!(lookup(&namespace, "\\", "^A"), None);
+
+        // Single segments are searched for in each parent scope, other names are not.
+        assert_eq!(
+            lookup(&namespace, "\\_SB.DEV0.DEV1", "B"),
+            Some(path("\\_SB.DEV0.B"))
+        );
+        assert_eq!(
+            lookup(&namespace, "\\_SB.DEV0.DEV1", "A"),
+            Some(path("\\_SB.A"))
+        );
+        assert_eq!(
+            lookup(&namespace, "\\_SB.DEV0.DEV1", "_GL"),
+            Some(path("\\_GL"))
+        );
+        assert_eq!(lookup(&namespace, "\\_SB.DEV0", "DEV0.B"), None);
+        assert_eq!(
+            lookup(&namespace, "\\_SB", "DEV0.B"),
+            Some(path("\\_SB.DEV0.B"))
+        );
+        assert_eq!(lookup(&namespace, "\\_SB.DEV0", "A.B"), None);
+
+        // Aliases resolve to their target, including within paths.
+        assert_eq!(path("\\_SB.ALS0"), path("\\_SB.DEV0"));
+        assert_eq!(path("\\_SB.ALS0.B"), path("\\_SB.DEV0.B"));
+        assert_eq!(
+            lookup(&namespace, "\\_SB.DEV0", "ALS0"),
+            Some(path("\\_SB.DEV0"))
+        );
+    }
+
+    #[test]
+    fn devices_by_hid() {
+        // Scope (\_SB) {
+        //     Device (PCI0) { Name (_HID, EisaId ("PNP0A08")) }
+        //     Device (CPU0) { Name (_HID, "ACPI0007") }
+        //     Device (CPU1) { Name (_HID, "ACPI0007") }
+        //     Device (DEV0) { Name (_CID, "ACPI0007") }
+        // }
+        let body = [
+            device(b"PCI0", b"\x08_HID\x0c\x41\xd0\x0a\x08"),
+            device(b"CPU0", b"\x08_HID\x0dACPI0007\x00"),
+            device(b"CPU1", b"\x08_HID\x0dACPI0007\x00"),
+            device(b"DEV0", b"\x08_CID\x0dACPI0007\x00"),
+        ]
+        .concat();
+        let mut namespace = Namespace::new();
+        namespace.load(&scope(b"\\_SB_", &body)).unwrap();
+        let path = |path| namespace.lookup_path(path).unwrap();
+        let find = |hid| namespace.find_devices_by_hid(hid).collect::<Vec<_>>();
+        assert_eq!(find("PNP0A08"), [path("\\_SB.PCI0")]);
+        assert_eq!(find("ACPI0007"), [path("\\_SB.CPU0"), path("\\_SB.CPU1")]);
+        assert_eq!(find("PNP0A03"), []);
+    }
+}
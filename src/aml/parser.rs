@@ -0,0 +1,1252 @@
+use super::{
+    name::{is_lead_name_char, NamePrefix, NameSeg, NameString},
+    term::{
+        AccessType, BinaryOp, Connection, CreateFieldKind, Expr, FieldElement, FieldFlags,
+        LogicalOp, MatchOp, MethodFlags, SuperName, Target, Term, UnaryOp,
+    },
+};
+use crate::address::AddressSpace;
+use alloc::{boxed::Box, collections::BTreeMap, string::String, vec, vec::Vec};
+use core::{fmt, mem, ptr};
+
+/// Maximum nesting depth of term lists and expressions
+///
+/// This bounds the size of the term tree handed to the interpreter. The stack used by the
+/// parser itself is bounded by [`Parser::set_stack_limit()`].
+pub const MAX_DEPTH: usize = 64;
+
+/// Maximum number of `If` terms chained through their `Else`, as by `ElseIf`
+///
+/// Chains up to this length, far more than the cases of any `Switch` in practice, are parsed
+/// without nesting. Longer chains are parsed as nested term lists. Dropping a chain still recurses through each
+/// link, using about 80 bytes of stack per link in optimized builds and 250 in unoptimized ones.
+pub const MAX_ELSE_IF: usize = 256;
+
+/// Default amount of stack the parser may use, in bytes
+///
+/// Optimized builds use about 400 bytes for each level of nested expressions, and 1.1 KiB
+/// for each nested term list, such as the body of an `If`. Unoptimized builds use about
+/// three times as much, and are given a larger default.
+pub const DEFAULT_STACK_LIMIT: usize = match cfg!(debug_assertions) {
+    true => 48 * 1024,
+    false => 16 * 1024,
+};
+
+/// `ObjectType` of a control method in an `External` declaration
+const EXTERNAL_METHOD: u8 = 8;
+
+/// Error returned when parsing malformed AML
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ParseError {
+    /// Offset of the malformed data from the start of the AML byte stream
+    ///
+    /// For a definition block, add the size of the table [`Header`](crate::sdt::Header) to
+    /// get the offset within the table.
+    pub offset: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ParseErrorKind {
+    /// The byte stream, or the enclosing package, ended in the middle of an object
+    UnexpectedEnd,
+    /// An unknown or misplaced opcode, where extended opcodes are prefixed with `0x5b`
+    InvalidOpcode(u16),
+    /// A name segment contained invalid characters
+    InvalidNameSeg,
+    /// A name referred to a parent of the root scope
+    InvalidScope,
+    /// A package length was malformed or extended past its enclosing package
+    InvalidPkgLength,
+    /// A string contained non-ASCII characters or was not terminated
+    InvalidString,
+    /// A `SuperName` was expected, but something else was found
+    InvalidSuperName,
+    /// Objects were nested more deeply than [`MAX_DEPTH`], or deeply enough to exceed the
+    /// stack limit of the parser
+    TooDeep,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ParseErrorKind::UnexpectedEnd => f.write_str("unexpected end of data")?,
+            ParseErrorKind::InvalidOpcode(op) => write!(f, "invalid opcode {op:#x}")?,
+            ParseErrorKind::InvalidNameSeg => f.write_str("invalid name segment")?,
+            ParseErrorKind::InvalidScope => f.write_str("name refers to a parent of the root")?,
+            ParseErrorKind::InvalidPkgLength => f.write_str("invalid package length")?,
+            ParseErrorKind::InvalidString => f.write_str("invalid string")?,
+            ParseErrorKind::InvalidSuperName => f.write_str("invalid SuperName")?,
+            ParseErrorKind::TooDeep => f.write_str("objects are nested too deeply")?,
+        }
+        write!(f, " at offset {:#x}", self.offset)
+    }
+}
+
+/// AML Parser
+///
+/// The AML grammar cannot be parsed without knowing how many arguments each control method
+/// takes, so the parser remembers every method declared by the definition blocks it has
+/// parsed. Definition blocks should be parsed by the same `Parser` in the order they are
+/// loaded, so that SSDTs may call methods declared by the DSDT.
+///
+/// Each definition block is parsed twice: first to find the methods it declares, and then
+/// to parse method bodies which may call methods declared later in the block.
+#[derive(Clone, Debug)]
+pub struct Parser {
+    methods: BTreeMap<Vec<NameSeg>, u8>,
+    stack_limit: usize,
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Self {
+            methods: BTreeMap::new(),
+            stack_limit: DEFAULT_STACK_LIMIT,
+        }
+    }
+}
+
+impl Parser {
+    pub fn new() -> Parser {
+        Self::default()
+    }
+
+    /// Set the amount of stack the parser may use, in bytes
+    ///
+    /// Input nested deeply enough to use more fails with [`ParseErrorKind::TooDeep`]. The
+    /// limit is checked at each level of nesting, so it may be exceeded by the stack used
+    /// by a single level. The default is [`DEFAULT_STACK_LIMIT`].
+    #[inline]
+    pub fn set_stack_limit(&mut self, bytes: usize) {
+        self.stack_limit = bytes;
+    }
+
+    /// Declare a control method taking `arg_count` arguments at the absolute path `path`
+    pub fn declare_method(&mut self, path: &[NameSeg], arg_count: u8) {
+        self.methods.insert(path.to_vec(), arg_count & 0x7);
+    }
+
+    /// Returns the number of arguments taken by the method at the absolute path `path`, if
+    /// it has been declared
+    pub fn method_arg_count(&self, path: &[NameSeg]) -> Option<u8> {
+        self.methods.get(path).copied()
+    }
+
+    /// Parse a term list, such as the AML byte code of a definition block
+    pub fn parse(&mut self, aml: &[u8]) -> Result<Vec<Term>, ParseError> {
+        // Errors from the first pass are ignored, as they may be caused by calls to methods
+        // which are declared later in the block. The second pass will report real errors.
+        let _ = State::new(self, aml, false).parse();
+        State::new(self, aml, true).parse()
+    }
+}
+
+/// Parse a term list, see [`Parser::parse()`]
+pub fn parse(aml: &[u8]) -> Result<Vec<Term>, ParseError> {
+    Parser::new().parse(aml)
+}
+
+type Result<T, E = ParseError> = core::result::Result<T, E>;
+
+struct State<'p, 'a> {
+    methods: &'p mut BTreeMap<Vec<NameSeg>, u8>,
+    aml: &'a [u8],
+    pos: usize,
+    /// End of the enclosing package, past which nothing may be read
+    end: usize,
+    scope: Vec<NameSeg>,
+    depth: usize,
+    /// Address of the stack when parsing started
+    stack_base: usize,
+    stack_limit: usize,
+    parse_method_bodies: bool,
+}
+
+impl<'p, 'a> State<'p, 'a> {
+    fn new(parser: &'p mut Parser, aml: &'a [u8], parse_method_bodies: bool) -> Self {
+        Self {
+            stack_limit: parser.stack_limit,
+            methods: &mut parser.methods,
+            aml,
+            pos: 0,
+            end: aml.len(),
+            scope: Vec::new(),
+            depth: 0,
+            stack_base: 0,
+            parse_method_bodies,
+        }
+    }
+
+    fn parse(mut self) -> Result<Vec<Term>> {
+        self.stack_base = stack_address();
+        let end = self.end;
+        self.term_list(end)
+    }
+
+    fn error(&self, offset: usize, kind: ParseErrorKind) -> ParseError {
+        ParseError { offset, kind }
+    }
+
+    // Primitives
+
+    fn peek(&self) -> Option<u8> {
+        if self.pos < self.end {
+            self.aml.get(self.pos).copied()
+        } else {
+            None
+        }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.end)
+            .and_then(|end| self.aml.get(self.pos..end))
+            .ok_or(self.error(self.pos, ParseErrorKind::UnexpectedEnd))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let bytes = self.take(N)?;
+        let mut array = [0; N];
+        array.copy_from_slice(bytes);
+        Ok(array)
+    }
+
+    fn byte(&mut self) -> Result<u8> {
+        Ok(self.take_array::<1>()?[0])
+    }
+
+    fn word(&mut self) -> Result<u16> {
+        self.take_array().map(u16::from_le_bytes)
+    }
+
+    fn dword(&mut self) -> Result<u32> {
+        self.take_array().map(u32::from_le_bytes)
+    }
+
+    fn qword(&mut self) -> Result<u64> {
+        self.take_array().map(u64::from_le_bytes)
+    }
+
+    /// Run `f` with the depth incremented, failing if it becomes too deep or too much of the
+    /// stack is used
+    fn nested<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        // The stack grows downwards on every supported architecture.
+        let stack_used = self.stack_base.saturating_sub(stack_address());
+        if self.depth >= MAX_DEPTH || stack_used > self.stack_limit {
+            return Err(self.error(self.pos, ParseErrorKind::TooDeep));
+        }
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// Run `f` with reads limited to the package ending at `end`
+    fn bounded<T>(&mut self, end: usize, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let outer = mem::replace(&mut self.end, end);
+        let result = f(self);
+        self.end = outer;
+        result
+    }
+
+    /// Run `f` within the scope named `name`
+    fn scoped<T>(
+        &mut self,
+        name: &NameString,
+        f: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        let scope = self.resolve(name)?;
+        let outer = mem::replace(&mut self.scope, scope);
+        let result = f(self);
+        self.scope = outer;
+        result
+    }
+
+    fn resolve(&self, name: &NameString) -> Result<Vec<NameSeg>> {
+        name.resolve(&self.scope)
+            .ok_or(self.error(self.pos, ParseErrorKind::InvalidScope))
+    }
+
+    /// Decode a package length, returning its value
+    fn pkg_length_raw(&mut self) -> Result<u32> {
+        let start = self.pos;
+        let lead = self.byte()?;
+        let follow = lead >> 6;
+        if follow == 0 {
+            return Ok((lead & 0x3f) as u32);
+        }
+        if lead & 0x30 != 0 {
+            return Err(self.error(start, ParseErrorKind::InvalidPkgLength));
+        }
+        let mut len = (lead & 0xf) as u32;
+        for i in 0..follow as u32 {
+            len |= (self.byte()? as u32) << (4 + 8 * i);
+        }
+        Ok(len)
+    }
+
+    /// Decode a package length, returning the offset of the end of the package
+    fn pkg_length(&mut self) -> Result<usize> {
+        let start = self.pos;
+        let len = self.pkg_length_raw()? as usize;
+        start
+            .checked_add(len)
+            .filter(|&end| end >= self.pos && end <= self.end)
+            .ok_or(self.error(start, ParseErrorKind::InvalidPkgLength))
+    }
+
+    // Names
+
+    fn name_seg(&mut self) -> Result<NameSeg> {
+        let start = self.pos;
+        NameSeg::new(self.take_array()?).ok_or(self.error(start, ParseErrorKind::InvalidNameSeg))
+    }
+
+    fn name_string(&mut self) -> Result<NameString> {
+        let prefix = match self.peek() {
+            Some(b'\\') => {
+                self.pos += 1;
+                NamePrefix::Root
+            }
+            Some(b'^') => {
+                let mut n = 0;
+                while self.peek() == Some(b'^') {
+                    self.pos += 1;
+                    n += 1;
+                }
+                NamePrefix::Parent(n)
+            }
+            _ => NamePrefix::None,
+        };
+        let segments = match self.peek() {
+            Some(0x00) => {
+                self.pos += 1;
+                Vec::new()
+            }
+            Some(0x2e) => {
+                self.pos += 1;
+                vec![self.name_seg()?, self.name_seg()?]
+            }
+            Some(0x2f) => {
+                self.pos += 1;
+                let count = self.byte()?;
+                (0..count)
+                    .map(|_| self.name_seg())
+                    .collect::<Result<Vec<_>>>()?
+            }
+            _ => vec![self.name_seg()?],
+        };
+        Ok(NameString { prefix, segments })
+    }
+
+    /// Returns the number of arguments taken by `name`, or `None` if it is not a method
+    fn method_arg_count(&self, name: &NameString) -> Option<u8> {
+        if name.is_search_name() {
+            (0..=self.scope.len()).rev().find_map(|len| {
+                let mut path = self.scope[..len].to_vec();
+                path.extend_from_slice(&name.segments);
+                self.methods.get(&path).copied()
+            })
+        } else {
+            self.methods.get(&name.resolve(&self.scope)?).copied()
+        }
+    }
+
+    fn declare_method(&mut self, name: &NameString, arg_count: u8) -> Result<()> {
+        let path = self.resolve(name)?;
+        self.methods.insert(path, arg_count & 0x7);
+        Ok(())
+    }
+
+    // Term lists
+
+    fn term_list(&mut self, end: usize) -> Result<Vec<Term>> {
+        self.nested(|s| {
+            s.bounded(end, |s| {
+                let mut terms = Vec::new();
+                while s.pos < end {
+                    terms.push(s.term()?);
+                }
+                Ok(terms)
+            })
+        })
+    }
+
+    /// Parse a named object containing a term list, such as `Scope` or `Device`
+    fn scope_like(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<()>,
+    ) -> Result<(NameString, Vec<Term>)> {
+        let end = self.pkg_length()?;
+        self.bounded(end, |s| {
+            let name = s.name_string()?;
+            f(s)?;
+            let terms = s.scoped(&name, |s| s.term_list(end))?;
+            Ok((name, terms))
+        })
+    }
+
+    fn term(&mut self) -> Result<Term> {
+        let op = self.byte()?;
+        // See `expr()`, terms are parsed by a separate function for each opcode.
+        let parse: fn(&mut Self, u8) -> Result<Term> = match op {
+            0x06 => |s, _| {
+                Ok(Term::Alias {
+                    source: s.name_string()?,
+                    alias: s.name_string()?,
+                })
+            },
+            0x08 => |s, _| {
+                Ok(Term::Name {
+                    name: s.name_string()?,
+                    value: s.term_arg()?,
+                })
+            },
+            0x10 => |s, _| {
+                let (name, terms) = s.scope_like(|_| Ok(()))?;
+                Ok(Term::Scope { name, terms })
+            },
+            0x14 => |s, _| s.method(),
+            0x15 => |s, _| {
+                let name = s.name_string()?;
+                let object_type = s.byte()?;
+                let arg_count = s.byte()?;
+                if object_type == EXTERNAL_METHOD {
+                    s.declare_method(&name, arg_count)?;
+                }
+                Ok(Term::External {
+                    name,
+                    object_type,
+                    arg_count,
+                })
+            },
+            0x86 => |s, _| {
+                Ok(Term::Notify {
+                    object: s.super_name()?,
+                    value: s.term_arg()?,
+                })
+            },
+            0x8a | 0x8b | 0x8c | 0x8d | 0x8f => |s, op| {
+                let kind = match op {
+                    0x8a => CreateFieldKind::DWord,
+                    0x8b => CreateFieldKind::Word,
+                    0x8c => CreateFieldKind::Byte,
+                    0x8d => CreateFieldKind::Bit,
+                    _ => CreateFieldKind::QWord,
+                };
+                Ok(Term::CreateField {
+                    buffer: s.term_arg()?,
+                    index: s.term_arg()?,
+                    kind,
+                    name: s.name_string()?,
+                })
+            },
+            0x9f => |_, _| Ok(Term::Continue),
+            0xa0 => |s, _| s.if_else(),
+            0xa2 => |s, _| {
+                let end = s.pkg_length()?;
+                let predicate = s.bounded(end, |s| s.term_arg())?;
+                Ok(Term::While {
+                    predicate,
+                    body: s.term_list(end)?,
+                })
+            },
+            0xa3 => |_, _| Ok(Term::Noop),
+            0xa4 => |s, _| Ok(Term::Return(s.term_arg()?)),
+            0xa5 => |_, _| Ok(Term::Break),
+            0xcc => |_, _| Ok(Term::BreakPoint),
+            0x5b => |s, _| s.ext_term(),
+            _ => |s, _| {
+                s.pos -= 1;
+                Ok(Term::Expr(s.term_arg()?))
+            },
+        };
+        parse(self, op)
+    }
+
+    fn ext_term(&mut self) -> Result<Term> {
+        let op = self.byte()?;
+        let parse: fn(&mut Self) -> Result<Term> = match op {
+            0x01 => |s| {
+                Ok(Term::Mutex {
+                    name: s.name_string()?,
+                    sync_level: s.byte()? & 0xf,
+                })
+            },
+            0x02 => |s| {
+                Ok(Term::Event {
+                    name: s.name_string()?,
+                })
+            },
+            0x13 => |s| {
+                let buffer = s.term_arg()?;
+                let index = s.term_arg()?;
+                let bits = s.term_arg()?;
+                Ok(Term::CreateField {
+                    kind: CreateFieldKind::Bits(Box::new(bits)),
+                    buffer,
+                    index,
+                    name: s.name_string()?,
+                })
+            },
+            0x21 => |s| Ok(Term::Stall(s.term_arg()?)),
+            0x22 => |s| Ok(Term::Sleep(s.term_arg()?)),
+            0x24 => |s| Ok(Term::Signal(s.super_name()?)),
+            0x26 => |s| Ok(Term::Reset(s.super_name()?)),
+            0x27 => |s| Ok(Term::Release(s.super_name()?)),
+            0x2a => |s| Ok(Term::Unload(s.super_name()?)),
+            0x32 => |s| {
+                Ok(Term::Fatal {
+                    kind: s.byte()?,
+                    code: s.dword()?,
+                    arg: s.term_arg()?,
+                })
+            },
+            0x80 => |s| {
+                Ok(Term::OpRegion {
+                    name: s.name_string()?,
+                    space: AddressSpace(s.byte()?),
+                    offset: s.term_arg()?,
+                    length: s.term_arg()?,
+                })
+            },
+            0x81 => |s| {
+                let end = s.pkg_length()?;
+                s.bounded(end, |s| {
+                    Ok(Term::Field {
+                        region: s.name_string()?,
+                        flags: FieldFlags(s.byte()?),
+                        elements: s.field_list(end)?,
+                    })
+                })
+            },
+            0x82 => |s| {
+                let (name, terms) = s.scope_like(|_| Ok(()))?;
+                Ok(Term::Device { name, terms })
+            },
+            0x83 => |s| {
+                let mut header = (0, 0, 0);
+                let (name, terms) = s.scope_like(|s| {
+                    header = (s.byte()?, s.dword()?, s.byte()?);
+                    Ok(())
+                })?;
+                Ok(Term::Processor {
+                    name,
+                    id: header.0,
+                    pblk_addr: header.1,
+                    pblk_len: header.2,
+                    terms,
+                })
+            },
+            0x84 => |s| {
+                let mut header = (0, 0);
+                let (name, terms) = s.scope_like(|s| {
+                    header = (s.byte()?, s.word()?);
+                    Ok(())
+                })?;
+                Ok(Term::PowerResource {
+                    name,
+                    system_level: header.0,
+                    resource_order: header.1,
+                    terms,
+                })
+            },
+            0x85 => |s| {
+                let (name, terms) = s.scope_like(|_| Ok(()))?;
+                Ok(Term::ThermalZone { name, terms })
+            },
+            0x86 => |s| {
+                let end = s.pkg_length()?;
+                s.bounded(end, |s| {
+                    Ok(Term::IndexField {
+                        index: s.name_string()?,
+                        data: s.name_string()?,
+                        flags: FieldFlags(s.byte()?),
+                        elements: s.field_list(end)?,
+                    })
+                })
+            },
+            0x87 => |s| {
+                let end = s.pkg_length()?;
+                s.bounded(end, |s| {
+                    Ok(Term::BankField {
+                        region: s.name_string()?,
+                        bank: s.name_string()?,
+                        bank_value: s.term_arg()?,
+                        flags: FieldFlags(s.byte()?),
+                        elements: s.field_list(end)?,
+                    })
+                })
+            },
+            0x88 => |s| {
+                Ok(Term::DataRegion {
+                    name: s.name_string()?,
+                    signature: s.term_arg()?,
+                    oem_id: s.term_arg()?,
+                    oem_table_id: s.term_arg()?,
+                })
+            },
+            _ => |s| {
+                s.pos -= 2;
+                Ok(Term::Expr(s.term_arg()?))
+            },
+        };
+        parse(self)
+    }
+
+    fn method(&mut self) -> Result<Term> {
+        let end = self.pkg_length()?;
+        let (name, flags) =
+            self.bounded(end, |s| Ok((s.name_string()?, MethodFlags(s.byte()?))))?;
+        self.declare_method(&name, flags.arg_count())?;
+        let body = if self.parse_method_bodies {
+            self.scoped(&name, |s| s.term_list(end))?
+        } else {
+            self.pos = end;
+            Vec::new()
+        };
+        Ok(Term::Method { name, flags, body })
+    }
+
+    fn if_else(&mut self) -> Result<Term> {
+        let outer = self.end;
+        let result = self.if_else_chain();
+        self.end = outer;
+        result
+    }
+
+    /// Parse an `If`, and any `If` starting its `Else`
+    ///
+    /// `ElseIf` and the cases of a `Switch` compile to an `If` nested within the `Else` of the
+    /// previous one, so long chains are parsed in a loop instead of as nested term lists.
+    fn if_else_chain(&mut self) -> Result<Term> {
+        // Each `If` whose `Else` starts with another `If`, with the end of that `Else`
+        let mut links = Vec::new();
+        let mut term = loop {
+            let end = self.pkg_length()?;
+            let predicate = self.bounded(end, |s| s.term_arg())?;
+            let then = self.term_list(end)?;
+            if self.peek() != Some(0xa1) {
+                break Term::If {
+                    predicate,
+                    then,
+                    otherwise: None,
+                };
+            }
+            self.pos += 1;
+            let end = self.pkg_length()?;
+            self.end = end;
+            if self.peek() == Some(0xa0) && links.len() < MAX_ELSE_IF {
+                self.pos += 1;
+                links.push((predicate, then, end));
+                continue;
+            }
+            break Term::If {
+                predicate,
+                then,
+                otherwise: Some(self.term_list(end)?),
+            };
+        };
+        while let Some((predicate, then, end)) = links.pop() {
+            // Terms following the nested `If` within the `Else`
+            let mut otherwise = vec![term];
+            otherwise.extend(self.term_list(end)?);
+            term = Term::If {
+                predicate,
+                then,
+                otherwise: Some(otherwise),
+            };
+        }
+        Ok(term)
+    }
+
+    fn field_list(&mut self, end: usize) -> Result<Vec<FieldElement>> {
+        let mut elements = Vec::new();
+        while self.pos < end {
+            let element = match self.peek() {
+                Some(0x00) => {
+                    self.pos += 1;
+                    FieldElement::Reserved {
+                        bits: self.pkg_length_raw()?,
+                    }
+                }
+                Some(0x01) => {
+                    self.pos += 1;
+                    let access_type = self.byte()?;
+                    FieldElement::Access {
+                        access_type: AccessType(access_type & 0xf),
+                        attrib_kind: access_type >> 6,
+                        attrib: self.byte()?,
+                    }
+                }
+                Some(0x02) => {
+                    self.pos += 1;
+                    let connection = if self.peek() == Some(0x11) {
+                        Connection::Buffer(self.term_arg()?)
+                    } else {
+                        Connection::Name(self.name_string()?)
+                    };
+                    FieldElement::Connection(connection)
+                }
+                Some(0x03) => {
+                    self.pos += 1;
+                    FieldElement::ExtendedAccess {
+                        access_type: AccessType(self.byte()? & 0xf),
+                        attrib: self.byte()?,
+                        length: self.byte()?,
+                    }
+                }
+                _ => FieldElement::Named {
+                    name: self.name_seg()?,
+                    bits: self.pkg_length_raw()?,
+                },
+            };
+            elements.push(element);
+        }
+        Ok(elements)
+    }
+
+    // Expressions
+
+    fn term_arg(&mut self) -> Result<Expr> {
+        self.nested(|s| s.expr())
+    }
+
+    fn boxed_term_arg(&mut self) -> Result<Box<Expr>> {
+        self.term_arg().map(Box::new)
+    }
+
+    fn target(&mut self) -> Result<Target> {
+        if self.peek() == Some(0x00) {
+            self.pos += 1;
+            return Ok(None);
+        }
+        self.super_name().map(Some)
+    }
+
+    fn super_name(&mut self) -> Result<SuperName> {
+        let start = self.pos;
+        let name = match self.peek() {
+            Some(op @ 0x60..=0x67) => {
+                self.pos += 1;
+                SuperName::Local(op - 0x60)
+            }
+            Some(op @ 0x68..=0x6e) => {
+                self.pos += 1;
+                SuperName::Arg(op - 0x68)
+            }
+            Some(0x5b) if self.aml.get(self.pos + 1) == Some(&0x31) && self.pos + 1 < self.end => {
+                self.pos += 2;
+                SuperName::Debug
+            }
+            Some(0x71 | 0x83 | 0x88) => SuperName::Reference(self.boxed_term_arg()?),
+            Some(op) if is_name_start(op) => match self.name_or_invocation()? {
+                Expr::Name(name) => SuperName::Name(name),
+                invocation => SuperName::Reference(Box::new(invocation)),
+            },
+            _ => return Err(self.error(start, ParseErrorKind::InvalidSuperName)),
+        };
+        Ok(name)
+    }
+
+    fn name_or_invocation(&mut self) -> Result<Expr> {
+        let name = self.name_string()?;
+        let Some(arg_count) = self.method_arg_count(&name) else {
+            return Ok(Expr::Name(name));
+        };
+        let args = (0..arg_count)
+            .map(|_| self.term_arg())
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Expr::Invoke { name, args })
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let start = self.pos;
+        let rest = self.aml.get(start..self.end).unwrap_or_default();
+        let len = rest
+            .iter()
+            .position(|&byte| byte == 0)
+            .ok_or(self.error(start, ParseErrorKind::InvalidString))?;
+        let string = core::str::from_utf8(&rest[..len])
+            .ok()
+            .filter(|s| s.is_ascii())
+            .ok_or(self.error(start, ParseErrorKind::InvalidString))?;
+        self.pos += len + 1;
+        Ok(String::from(string))
+    }
+
+    fn binary(&mut self, op: BinaryOp) -> Result<Expr> {
+        Ok(Expr::Binary {
+            op,
+            lhs: self.boxed_term_arg()?,
+            rhs: self.boxed_term_arg()?,
+            target: self.target()?,
+        })
+    }
+
+    fn unary(&mut self, op: UnaryOp) -> Result<Expr> {
+        Ok(Expr::Unary {
+            op,
+            operand: self.boxed_term_arg()?,
+            target: self.target()?,
+        })
+    }
+
+    fn logical(&mut self, op: LogicalOp) -> Result<Expr> {
+        Ok(Expr::Logical {
+            op,
+            lhs: self.boxed_term_arg()?,
+            rhs: self.boxed_term_arg()?,
+        })
+    }
+
+    fn package(&mut self, count: impl FnOnce(&mut Self) -> Result<Expr>) -> Result<Expr> {
+        let end = self.pkg_length()?;
+        self.bounded(end, |s| {
+            let count = Box::new(count(s)?);
+            let mut elements = Vec::new();
+            while s.pos < end {
+                let element = match s.peek() {
+                    Some(op) if is_name_start(op) => Expr::Name(s.name_string()?),
+                    _ => s.term_arg()?,
+                };
+                elements.push(element);
+            }
+            Ok(Expr::Package { count, elements })
+        })
+    }
+
+    fn expr(&mut self) -> Result<Expr> {
+        let start = self.pos;
+        let op = self.byte()?;
+        // A frame of this function is on the stack for each level of nesting. Every opcode is
+        // parsed by its own function, so the frame does not hold the temporaries of all of
+        // them, which unoptimized builds do not share.
+        let parse: fn(&mut Self, u8) -> Result<Expr> = match op {
+            0x00 => |_, _| Ok(Expr::Integer(0)),
+            0x01 => |_, _| Ok(Expr::Integer(1)),
+            0xff => |_, _| Ok(Expr::Ones),
+            0x0a => |s, _| Ok(Expr::Integer(s.byte()? as u64)),
+            0x0b => |s, _| Ok(Expr::Integer(s.word()? as u64)),
+            0x0c => |s, _| Ok(Expr::Integer(s.dword()? as u64)),
+            0x0e => |s, _| Ok(Expr::Integer(s.qword()?)),
+            0x0d => |s, _| Ok(Expr::String(s.string()?)),
+            0x11 => |s, _| {
+                let end = s.pkg_length()?;
+                s.bounded(end, |s| {
+                    let size = s.boxed_term_arg()?;
+                    let data = s.take(end - s.pos)?.to_vec();
+                    Ok(Expr::Buffer { size, data })
+                })
+            },
+            0x12 => |s, _| s.package(|s| Ok(Expr::Integer(s.byte()? as u64))),
+            0x13 => |s, _| s.package(|s| s.term_arg()),
+            0x60..=0x67 => |_, op| Ok(Expr::Local(op - 0x60)),
+            0x68..=0x6e => |_, op| Ok(Expr::Arg(op - 0x68)),
+            0x70 => |s, _| {
+                Ok(Expr::Store {
+                    source: s.boxed_term_arg()?,
+                    target: s.super_name()?,
+                })
+            },
+            0x71 => |s, _| Ok(Expr::RefOf(s.super_name()?)),
+            0x72 => |s, _| s.binary(BinaryOp::Add),
+            0x73 => |s, _| s.binary(BinaryOp::Concat),
+            0x74 => |s, _| s.binary(BinaryOp::Subtract),
+            0x75 => |s, _| Ok(Expr::Increment(s.super_name()?)),
+            0x76 => |s, _| Ok(Expr::Decrement(s.super_name()?)),
+            0x77 => |s, _| s.binary(BinaryOp::Multiply),
+            0x78 => |s, _| {
+                Ok(Expr::Divide {
+                    dividend: s.boxed_term_arg()?,
+                    divisor: s.boxed_term_arg()?,
+                    remainder: s.target()?,
+                    quotient: s.target()?,
+                })
+            },
+            0x79 => |s, _| s.binary(BinaryOp::ShiftLeft),
+            0x7a => |s, _| s.binary(BinaryOp::ShiftRight),
+            0x7b => |s, _| s.binary(BinaryOp::And),
+            0x7c => |s, _| s.binary(BinaryOp::NAnd),
+            0x7d => |s, _| s.binary(BinaryOp::Or),
+            0x7e => |s, _| s.binary(BinaryOp::NOr),
+            0x7f => |s, _| s.binary(BinaryOp::XOr),
+            0x80 => |s, _| s.unary(UnaryOp::Not),
+            0x81 => |s, _| s.unary(UnaryOp::FindSetLeftBit),
+            0x82 => |s, _| s.unary(UnaryOp::FindSetRightBit),
+            0x83 => |s, _| Ok(Expr::DerefOf(s.boxed_term_arg()?)),
+            0x84 => |s, _| s.binary(BinaryOp::ConcatRes),
+            0x85 => |s, _| s.binary(BinaryOp::Mod),
+            0x87 => |s, _| Ok(Expr::SizeOf(s.super_name()?)),
+            0x88 => |s, _| {
+                Ok(Expr::Index {
+                    source: s.boxed_term_arg()?,
+                    index: s.boxed_term_arg()?,
+                    target: s.target()?,
+                })
+            },
+            0x89 => |s, _| {
+                Ok(Expr::Match {
+                    package: s.boxed_term_arg()?,
+                    op1: MatchOp(s.byte()?),
+                    operand1: s.boxed_term_arg()?,
+                    op2: MatchOp(s.byte()?),
+                    operand2: s.boxed_term_arg()?,
+                    start: s.boxed_term_arg()?,
+                })
+            },
+            0x8e => |s, _| Ok(Expr::ObjectType(s.super_name()?)),
+            0x90 => |s, _| s.logical(LogicalOp::LAnd),
+            0x91 => |s, _| s.logical(LogicalOp::LOr),
+            0x92 => |s, _| Ok(Expr::LNot(s.boxed_term_arg()?)),
+            0x93 => |s, _| s.logical(LogicalOp::LEqual),
+            0x94 => |s, _| s.logical(LogicalOp::LGreater),
+            0x95 => |s, _| s.logical(LogicalOp::LLess),
+            0x96 => |s, _| s.unary(UnaryOp::ToBuffer),
+            0x97 => |s, _| s.unary(UnaryOp::ToDecimalString),
+            0x98 => |s, _| s.unary(UnaryOp::ToHexString),
+            0x99 => |s, _| s.unary(UnaryOp::ToInteger),
+            0x9c => |s, _| {
+                Ok(Expr::ToString {
+                    source: s.boxed_term_arg()?,
+                    length: s.boxed_term_arg()?,
+                    target: s.target()?,
+                })
+            },
+            0x9d => |s, _| {
+                Ok(Expr::CopyObject {
+                    source: s.boxed_term_arg()?,
+                    target: s.super_name()?,
+                })
+            },
+            0x9e => |s, _| {
+                Ok(Expr::Mid {
+                    source: s.boxed_term_arg()?,
+                    index: s.boxed_term_arg()?,
+                    length: s.boxed_term_arg()?,
+                    target: s.target()?,
+                })
+            },
+            0x5b => |s, _| s.ext_expr(s.pos - 1),
+            op if is_name_start(op) => |s, _| {
+                s.pos -= 1;
+                s.name_or_invocation()
+            },
+            _ => return Err(self.error(start, ParseErrorKind::InvalidOpcode(op as u16))),
+        };
+        parse(self, op)
+    }
+
+    fn ext_expr(&mut self, start: usize) -> Result<Expr> {
+        let op = self.byte()?;
+        let parse: fn(&mut Self) -> Result<Expr> = match op {
+            0x12 => |s| {
+                Ok(Expr::CondRefOf {
+                    source: s.super_name()?,
+                    target: s.target()?,
+                })
+            },
+            0x1f => |s| {
+                Ok(Expr::LoadTable {
+                    signature: s.boxed_term_arg()?,
+                    oem_id: s.boxed_term_arg()?,
+                    oem_table_id: s.boxed_term_arg()?,
+                    root_path: s.boxed_term_arg()?,
+                    parameter_path: s.boxed_term_arg()?,
+                    parameter_data: s.boxed_term_arg()?,
+                })
+            },
+            0x20 => |s| {
+                Ok(Expr::Load {
+                    name: s.name_string()?,
+                    target: s.target()?,
+                })
+            },
+            0x23 => |s| {
+                Ok(Expr::Acquire {
+                    mutex: s.super_name()?,
+                    timeout: s.word()?,
+                })
+            },
+            0x25 => |s| {
+                Ok(Expr::Wait {
+                    event: s.super_name()?,
+                    timeout: s.boxed_term_arg()?,
+                })
+            },
+            0x28 => |s| s.unary(UnaryOp::FromBcd),
+            0x29 => |s| s.unary(UnaryOp::ToBcd),
+            0x30 => |_| Ok(Expr::Revision),
+            0x31 => |_| Ok(Expr::Debug),
+            0x33 => |_| Ok(Expr::Timer),
+            _ => {
+                let op = u16::from_be_bytes([0x5b, op]);
+                return Err(self.error(start, ParseErrorKind::InvalidOpcode(op)));
+            }
+        };
+        parse(self)
+    }
+}
+
+/// Returns the current address of the stack
+#[inline(always)]
+fn stack_address() -> usize {
+    let marker = 0u8;
+    core::hint::black_box(ptr::addr_of!(marker)).addr()
+}
+
+/// Returns `true` if `byte` may start a `NameString`
+fn is_name_start(byte: u8) -> bool {
+    is_lead_name_char(byte) || matches!(byte, b'\\' | b'^' | 0x2e | 0x2f)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    extern crate std;
+
+    use super::*;
+
+    fn name(asl: &str) -> NameString {
+        NameString::from_asl(asl).unwrap()
+    }
+
+    /// Prefix `body` with its package length, in the longest encoding
+    pub(crate) fn pkg(body: &[u8]) -> Vec<u8> {
+        let len = body.len() + 4;
+        let mut bytes = vec![0xc0 | (len & 0xf) as u8];
+        bytes.extend_from_slice(&((len >> 4) as u32).to_le_bytes()[..3]);
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    /// Returns `If (One) { ... }` nested `depth` times
+    fn nested_if(depth: usize) -> Vec<u8> {
+        let mut aml = vec![0xa3];
+        for _ in 0..depth {
+            aml = [&[0xa0][..], &pkg(&[&[0x01][..], &aml].concat())].concat();
+        }
+        aml
+    }
+
+    /// Returns `If (One) { } Else { ... }` nested `depth` times
+    fn nested_else(depth: usize) -> Vec<u8> {
+        let mut aml = vec![0xa3];
+        for _ in 0..depth {
+            aml = [&[0xa0][..], &pkg(&[0x01]), &[0xa1], &pkg(&aml)].concat();
+        }
+        aml
+    }
+
+    #[test]
+    fn definition_block() {
+        // Scope (\_SB) {
+        //     Device (PCI0) {
+        //         Name (_HID, EisaId ("PNP0A08"))
+        //         Method (_STA) { Return (ADD2 (1, 0x0f)) }
+        //         Method (ADD2, 2) { Return (Arg0 + Arg1) }
+        //     }
+        // }
+        let hid = b"\x08_HID\x0c\x41\xd0\x0a\x08";
+        let sta = [&b"\x14"[..], &pkg(b"_STA\x00\xa4ADD2\x01\x0a\x0f")].concat();
+        let add2 = [&b"\x14"[..], &pkg(b"ADD2\x02\xa4\x72\x68\x69\x00")].concat();
+        let device = [
+            &b"\x5b\x82"[..],
+            &pkg(&[&b"PCI0"[..], hid, &sta, &add2].concat()),
+        ]
+        .concat();
+        let aml = [&b"\x10"[..], &pkg(&[&b"\\_SB_"[..], &device].concat())].concat();
+
+        let add = Expr::Binary {
+            op: BinaryOp::Add,
+            lhs: Box::new(Expr::Arg(0)),
+            rhs: Box::new(Expr::Arg(1)),
+            target: None,
+        };
+        let call = Expr::Invoke {
+            name: name("ADD2"),
+            args: vec![Expr::Integer(1), Expr::Integer(0x0f)],
+        };
+        let expected = vec![Term::Scope {
+            name: name("\\_SB"),
+            terms: vec![Term::Device {
+                name: name("PCI0"),
+                terms: vec![
+                    Term::Name {
+                        name: name("_HID"),
+                        value: Expr::Integer(0x080ad041),
+                    },
+                    Term::Method {
+                        name: name("_STA"),
+                        flags: MethodFlags(0),
+                        body: vec![Term::Return(call)],
+                    },
+                    Term::Method {
+                        name: name("ADD2"),
+                        flags: MethodFlags(2),
+                        body: vec![Term::Return(add)],
+                    },
+                ],
+            }],
+        }];
+        let mut parser = Parser::new();
+        assert_eq!(parser.parse(&aml), Ok(expected));
+        let path = [NameSeg(*b"_SB_"), NameSeg(*b"PCI0"), NameSeg(*b"ADD2")];
+        assert_eq!(parser.method_arg_count(&path), Some(2));
+    }
+
+    #[test]
+    fn errors() {
+        let error = |offset, kind| Err(ParseError { offset, kind });
+        // The package extends past the end of the data.
+        assert_eq!(
+            parse(b"\x10\x0a\\_SB_"),
+            error(1, ParseErrorKind::InvalidPkgLength)
+        );
+        assert_eq!(
+            parse(b"\xa3\x5b\x02"),
+            error(3, ParseErrorKind::UnexpectedEnd)
+        );
+        assert_eq!(
+            parse(b"\xa3\x5b\xff"),
+            error(1, ParseErrorKind::InvalidOpcode(0x5bff))
+        );
+        assert_eq!(
+            parse(b"\x08FOO_\x0dab"),
+            error(6, ParseErrorKind::InvalidString)
+        );
+        assert_eq!(
+            parse(b"\x08FO-_\x00"),
+            error(1, ParseErrorKind::InvalidNameSeg)
+        );
+        assert_eq!(
+            parse(b"\x75\x0a"),
+            error(1, ParseErrorKind::InvalidSuperName)
+        );
+    }
+
+    #[test]
+    fn too_deep() {
+        // `Add` nested within the first operand of each `Add`
+        let mut adds = vec![0x72; 10_000];
+        adds.push(0x01);
+        adds.extend([0x01, 0x00].repeat(10_000));
+        let ifs = nested_if(1_000);
+
+        // Parse on a stack which would overflow without the stack limit.
+        let thread = std::thread::Builder::new()
+            .stack_size(DEFAULT_STACK_LIMIT + 32 * 1024)
+            .spawn(move || (parse(&adds), parse(&ifs)))
+            .unwrap();
+        let (adds, ifs) = thread.join().unwrap();
+        let too_deep = |result| matches!(result, Err(ParseError { kind, .. }) if kind == ParseErrorKind::TooDeep);
+        assert!(too_deep(adds));
+        assert!(too_deep(ifs));
+
+        assert!(parse(&nested_if(4)).is_ok());
+        let mut parser = Parser::new();
+        parser.set_stack_limit(0);
+        assert!(too_deep(parser.parse(&nested_else(1))));
+    }
+
+    #[test]
+    fn else_if_chain() {
+        // If (One) { } Else { If (Zero) { } Noop }
+        let aml = [
+            &[0xa0][..],
+            &pkg(&[0x01]),
+            &[0xa1],
+            &pkg(&[0xa0, 0x02, 0x00, 0xa3]),
+        ]
+        .concat();
+        let inner = Term::If {
+            predicate: Expr::Integer(0),
+            then: vec![],
+            otherwise: None,
+        };
+        assert_eq!(
+            parse(&aml),
+            Ok(vec![Term::If {
+                predicate: Expr::Integer(1),
+                then: vec![],
+                otherwise: Some(vec![inner, Term::Noop]),
+            }])
+        );
+
+        // Chains are parsed without nesting, so don't count towards the stack limit.
+        let elses = nested_else(MAX_ELSE_IF);
+        let thread = std::thread::Builder::new()
+            .stack_size(DEFAULT_STACK_LIMIT + 32 * 1024)
+            .spawn(move || parse(&elses).is_ok())
+            .unwrap();
+        assert!(thread.join().unwrap());
+    }
+
+    /// Parse random and corrupted input, which must fail without panicking
+    #[test]
+    fn fuzz() {
+        // xorshift64*
+        let mut state = 0x853c_49e6_748f_ea9b_u64;
+        let mut random = move || {
+            state ^= state >> 12;
+            state ^= state << 25;
+            state ^= state >> 27;
+            state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+        };
+
+        let sample = [
+            &b"\x10"[..],
+            &pkg(&[
+                &b"\\_SB_\x5b\x82"[..],
+                &pkg(&[
+                    &b"PCI0\x08_HID\x0c\x41\xd0\x0a\x08\x14"[..],
+                    &pkg(&[
+                        &b"_CRS\x01\xa0"[..],
+                        &pkg(b"\x93\x68\x01\xa4\x11\x05\x0a\x02\x79\x00"),
+                        b"\xa4\x0d_\x00",
+                    ]
+                    .concat()),
+                    b"\x5b\x80REG0\x01\x0b\x00\x10\x0a\x08\x5b\x81",
+                    &pkg(b"REG0\x01FLD0\x08\x00\x04FLD1\x04"),
+                    b"\x08PKG0\x12",
+                    &pkg(b"\x03\x0a\x07\x0dhi\x00FLD0"),
+                ]
+                .concat()),
+                &nested_else(8),
+            ]
+            .concat()),
+        ]
+        .concat();
+        assert_eq!(parse(&sample).err(), None);
+
+        for _ in 0..20_000 {
+            let mut aml = sample.clone();
+            match random() % 4 {
+                // Flip bytes
+                0 | 1 => {
+                    for _ in 0..=random() % 4 {
+                        let index = random() as usize % aml.len();
+                        aml[index] = random() as u8;
+                    }
+                }
+                // Truncate
+                2 => aml.truncate(random() as usize % aml.len()),
+                // Random bytes, biased towards opcodes which nest
+                _ => {
+                    const OPCODES: &[u8] = b"\x10\x12\x14\x5b\x70\x72\x83\x88\xa0\xa1\xa2\xa4";
+                    aml = (0..random() % 256)
+                        .map(|_| match random() % 2 {
+                            0 => OPCODES[random() as usize % OPCODES.len()],
+                            _ => random() as u8,
+                        })
+                        .collect();
+                }
+            }
+            let _ = parse(&aml);
+        }
+    }
+}
@@ -0,0 +1,284 @@
+//! PCI Interrupt Routing
+//!
+//! The `_PRT` object of a PCI bridge maps the interrupt pins of the devices on its secondary
+//! bus to interrupts. Each pin is either connected directly to a Global System Interrupt,
+//! or to an interrupt link device (`PNP0C0F`) whose interrupt is configured with its
+//! `_CRS`, `_PRS` and `_SRS` methods.
+
+use super::{
+    interpreter::{AmlError, Host, Interpreter},
+    name::{NameSeg, NameString},
+    namespace::NodeId,
+    resource::{self, ExtendedIrq, Irq, Polarity, Resource, Trigger},
+    value::{Reference, Value},
+};
+use crate::sdt::madt::{Entry, InterruptSourceFlags, Madt};
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+
+/// Interrupt pin of a PCI function
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Pin {
+    IntA,
+    IntB,
+    IntC,
+    IntD,
+}
+
+impl Pin {
+    /// Returns the pin given by the Interrupt Pin register of a function, where 1 is INTA#
+    ///
+    /// Returns `None` if the function does not use an interrupt pin.
+    pub const fn from_register(value: u8) -> Option<Pin> {
+        match value {
+            1 => Some(Pin::IntA),
+            2 => Some(Pin::IntB),
+            3 => Some(Pin::IntC),
+            4 => Some(Pin::IntD),
+            _ => None,
+        }
+    }
+
+    /// Returns the pin which `self` is connected to on the other side of a PCI-to-PCI
+    /// bridge, for a device on the bridge's secondary bus
+    ///
+    /// This is used for bridges without a `_PRT`, whose interrupts are swizzled onto the
+    /// parent bus.
+    pub const fn swizzle(self, device: u8) -> Pin {
+        match (self as u8 + device) % 4 {
+            0 => Pin::IntA,
+            1 => Pin::IntB,
+            2 => Pin::IntC,
+            _ => Pin::IntD,
+        }
+    }
+}
+
+/// Interrupt which a PCI interrupt pin is routed to
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct PciInterrupt {
+    pub gsi: u32,
+    pub trigger: Trigger,
+    pub polarity: Polarity,
+    pub shared: bool,
+    /// The interrupt was given by an IRQ descriptor, so `gsi` is an ISA IRQ until
+    /// [`PciRouting::apply_overrides()`] is called
+    pub legacy: bool,
+    /// The interrupt link device the pin is connected to
+    pub link: Option<NodeId>,
+}
+
+/// Interrupt routing table of a PCI bridge
+///
+/// Returned by [`Interpreter::pci_routing()`], and keyed by the device number on the
+/// bridge's secondary bus and the interrupt pin. All functions of a device share its
+/// routing.
+#[derive(Clone, Debug, Default)]
+pub struct PciRouting {
+    entries: BTreeMap<(u8, Pin), PciInterrupt>,
+}
+
+impl PciRouting {
+    /// Returns the interrupt which `pin` of `device` is routed to
+    #[inline]
+    pub fn get(&self, device: u8, pin: Pin) -> Option<&PciInterrupt> {
+        self.entries.get(&(device, pin))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ((u8, Pin), &PciInterrupt)> + '_ {
+        self.entries
+            .iter()
+            .map(|(&key, interrupt)| (key, interrupt))
+    }
+
+    /// Apply the Interrupt Source Overrides of the MADT
+    ///
+    /// Interrupts given by an IRQ descriptor are moved to the GSI which their ISA IRQ is
+    /// overridden to. Interrupts routed to an overridden GSI take the polarity and trigger
+    /// mode of the override, unless it conforms to the bus.
+    pub fn apply_overrides(&mut self, madt: &Madt) {
+        for entry in madt.entries() {
+            let Entry::InterruptSourceOverride(iso) = entry else {
+                continue;
+            };
+            let (_, irq) = iso.source();
+            let gsi = iso.global_system_interrupt();
+            let flags = iso.flags();
+            for interrupt in self.entries.values_mut() {
+                if interrupt.legacy && interrupt.gsi == irq as u32 {
+                    interrupt.gsi = gsi;
+                    interrupt.legacy = false;
+                }
+                if interrupt.gsi != gsi {
+                    continue;
+                }
+                match flags.polarity() {
+                    InterruptSourceFlags::ACTIVE_HIGH => interrupt.polarity = Polarity::ActiveHigh,
+                    InterruptSourceFlags::ACTIVE_LOW => interrupt.polarity = Polarity::ActiveLow,
+                    _ => {}
+                }
+                match flags.trigger() {
+                    InterruptSourceFlags::EDGE_TRIGGERED => interrupt.trigger = Trigger::Edge,
+                    InterruptSourceFlags::LEVEL_TRIGGERED => interrupt.trigger = Trigger::Level,
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+impl<H: Host> Interpreter<H> {
+    /// Evaluate the `_PRT` of a PCI bridge, returning `None` if it has none
+    ///
+    /// Link devices which are disabled are configured with `_SRS`, using the first
+    /// interrupt listed by their `_PRS`.
+    pub fn pci_routing(&mut self, bridge: NodeId) -> Result<Option<PciRouting>, AmlError> {
+        let Some(prt) = self.evaluate_child(bridge, "_PRT", vec![])? else {
+            return Ok(None);
+        };
+        let Value::Package(entries) = prt else {
+            return Err(AmlError::InvalidObject("_PRT"));
+        };
+        let mut routing = PciRouting::default();
+        let mut links = BTreeMap::new();
+        for entry in &entries {
+            let [address, pin, source, index] = entry.as_package().unwrap_or_default() else {
+                return Err(AmlError::InvalidObject("_PRT entry"));
+            };
+            let integer = |value: &Value| {
+                value
+                    .as_integer()
+                    .ok_or(AmlError::InvalidObject("_PRT entry"))
+            };
+            let device = (integer(address)? >> 16) as u8 & 0x1f;
+            let pin = match integer(pin)? {
+                0 => Pin::IntA,
+                1 => Pin::IntB,
+                2 => Pin::IntC,
+                3 => Pin::IntD,
+                _ => return Err(AmlError::InvalidObject("_PRT entry")),
+            };
+            let index = integer(index)?;
+            let link = match source {
+                Value::Integer(0) => None,
+                Value::Reference(Reference::Named(id)) => Some(*id),
+                Value::String(path) => {
+                    let name = NameString::from_asl(path).ok_or(AmlError::InvalidName)?;
+                    let id = self.namespace().lookup(bridge, &name);
+                    Some(id.ok_or(AmlError::NotFound(name))?)
+                }
+                _ => return Err(AmlError::InvalidObject("_PRT entry")),
+            };
+            let interrupt = match link {
+                // Interrupts connected directly to a GSI are level-triggered and active-low,
+                // like the interrupt pins.
+                None => PciInterrupt {
+                    gsi: index as u32,
+                    trigger: Trigger::Level,
+                    polarity: Polarity::ActiveLow,
+                    shared: true,
+                    legacy: false,
+                    link: None,
+                },
+                Some(link) => match links.get(&(link, index)) {
+                    Some(&interrupt) => interrupt,
+                    None => {
+                        let interrupt = self.link_interrupt(link, index as usize)?;
+                        links.insert((link, index), interrupt);
+                        interrupt
+                    }
+                },
+            };
+            routing.entries.insert((device, pin), interrupt);
+        }
+        Ok(Some(routing))
+    }
+
+    /// Returns the interrupt of a link device, configuring it if it is disabled
+    ///
+    /// `index` selects one of the interrupt descriptors in the link's resource templates.
+    fn link_interrupt(&mut self, link: NodeId, index: usize) -> Result<PciInterrupt, AmlError> {
+        let current = self.link_resources(link, "_CRS")?;
+        if let Some(interrupt) = nth_interrupt(&current, index).and_then(|r| first(r, link)) {
+            return Ok(interrupt);
+        }
+
+        let possible = self.link_resources(link, "_PRS")?;
+        let (setting, interrupt) = match nth_interrupt(&possible, index) {
+            Some(Resource::Irq(irq)) => {
+                let interrupt = first(&Resource::Irq(*irq), link);
+                let setting = interrupt.map(|i| {
+                    Resource::Irq(Irq {
+                        mask: 1 << i.gsi,
+                        ..*irq
+                    })
+                });
+                (setting, interrupt)
+            }
+            Some(Resource::ExtendedIrq(irq)) => {
+                let interrupt = first(&Resource::ExtendedIrq(irq.clone()), link);
+                let setting = interrupt.map(|i| {
+                    Resource::ExtendedIrq(ExtendedIrq {
+                        interrupts: vec![i.gsi],
+                        ..irq.clone()
+                    })
+                });
+                (setting, interrupt)
+            }
+            _ => (None, None),
+        };
+        let (Some(setting), Some(interrupt)) = (setting, interrupt) else {
+            return Err(AmlError::InvalidObject("link device _PRS"));
+        };
+
+        let args = vec![Value::Buffer(resource::encode(&[setting]))];
+        if self.evaluate_child(link, "_SRS", args)?.is_none() {
+            return Err(not_found(self, link, "_SRS"));
+        }
+        Ok(interrupt)
+    }
+
+    fn link_resources(&mut self, link: NodeId, name: &str) -> Result<Vec<Resource>, AmlError> {
+        let value = self
+            .evaluate_child(link, name, vec![])?
+            .ok_or_else(|| not_found(self, link, name))?;
+        let buffer = value
+            .as_buffer()
+            .ok_or(AmlError::InvalidObject("resource template"))?;
+        Ok(resource::decode(buffer)?)
+    }
+}
+
+fn not_found<H: Host>(interpreter: &Interpreter<H>, device: NodeId, name: &str) -> AmlError {
+    let mut path = interpreter.namespace().path(device);
+    path.segments.extend(NameSeg::from_asl(name));
+    AmlError::NotFound(path)
+}
+
+/// Returns the `index`th interrupt descriptor of a resource template
+fn nth_interrupt(resources: &[Resource], index: usize) -> Option<&Resource> {
+    resources
+        .iter()
+        .filter(|r| matches!(r, Resource::Irq(_) | Resource::ExtendedIrq(_)))
+        .nth(index)
+}
+
+/// Returns the first interrupt of an interrupt descriptor, or `None` if it has none
+fn first(resource: &Resource, link: NodeId) -> Option<PciInterrupt> {
+    let (gsi, flags, legacy) = match resource {
+        Resource::Irq(irq) => (irq.irqs().next()? as u32, irq.flags(), true),
+        Resource::ExtendedIrq(irq) => (*irq.interrupts.first()?, irq.flags, false),
+        _ => return None,
+    };
+    // A disabled link device returns a descriptor with interrupt 0.
+    if gsi == 0 {
+        return None;
+    }
+    Some(PciInterrupt {
+        gsi,
+        trigger: flags.trigger,
+        polarity: flags.polarity,
+        shared: flags.shared,
+        legacy,
+        link: Some(link),
+    })
+}
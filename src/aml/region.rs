@@ -0,0 +1,208 @@
+use super::{
+    interpreter::AmlError,
+    namespace::{AccessAttrib, NodeId},
+};
+use crate::{
+    address::{AccessSize, AddressSpace, PciAddress, RegisterIo},
+    sdt::{map_nonnull, Bridge},
+};
+use core::ptr;
+
+/// Access to an operation region, passed to a [`RegionHandler`]
+#[derive(Clone, Copy, Debug)]
+pub struct RegionAccess<'a> {
+    /// The operation region
+    pub region: NodeId,
+    pub space: AddressSpace,
+    /// Address of the start of the region within its address space
+    pub base: u64,
+    /// Length of the region in bytes
+    pub length: u64,
+    /// Offset of the access from the start of the region, in bytes
+    ///
+    /// For `GeneralPurposeIo` regions, this is the index of the first pin within the
+    /// connection resource.
+    pub offset: u64,
+    /// Width of the access in bits
+    ///
+    /// This is 8, 16, 32 or 64, except for `GeneralPurposeIo` regions where it is the
+    /// number of pins, and for buffer transfers where it is the length of the field.
+    pub bit_width: u32,
+    /// Address of the PCI function containing a `PCI_Config` region, with an offset of 0
+    pub pci: Option<PciAddress>,
+    /// Access attribute of the field, used by `SMBus` and `GenericSerialBus` regions
+    pub attrib: Option<AccessAttrib>,
+    /// Resource template set by the last `Connection` before the field
+    pub connection: Option<&'a [u8]>,
+}
+
+impl RegionAccess<'_> {
+    /// Returns the size of an access to a region which is accessed by address
+    pub fn access_size(&self) -> Result<AccessSize, AmlError> {
+        match self.bit_width {
+            8 => Ok(AccessSize::BYTE),
+            16 => Ok(AccessSize::WORD),
+            32 => Ok(AccessSize::DWORD),
+            64 => Ok(AccessSize::QWORD),
+            _ => Err(AmlError::Unsupported("access width")),
+        }
+    }
+
+    /// Returns the address of the access within the address space of the region
+    pub fn address(&self) -> Result<u64, AmlError> {
+        self.base
+            .checked_add(self.offset)
+            .ok_or(AmlError::IndexOutOfBounds)
+    }
+}
+
+/// Operation Region Handler
+///
+/// Performs the accesses made by AML to operation regions in one address space. Handlers
+/// are installed with [`Interpreter::install_region_handler()`].
+///
+/// Most address spaces are accessed by address with [`RegionHandler::read()`] and
+/// [`RegionHandler::write()`]. `SMBus`, `GenericSerialBus`, `IPMI`, `PRM` and `FFixedHW`
+/// regions exchange a buffer with [`RegionHandler::transfer()`] instead.
+///
+/// [`Interpreter::install_region_handler()`]: super::Interpreter::install_region_handler
+pub trait RegionHandler {
+    fn read(&mut self, access: &RegionAccess<'_>) -> Result<u64, AmlError>;
+
+    fn write(&mut self, access: &RegionAccess<'_>, value: u64) -> Result<(), AmlError>;
+
+    /// Exchange a buffer with the region
+    ///
+    /// For writes, `buffer` holds the data written by the AML. In both cases, the handler
+    /// replaces it with the buffer returned to the AML, which starts with a status byte.
+    fn transfer(
+        &mut self,
+        access: &RegionAccess<'_>,
+        write: bool,
+        buffer: &mut [u8],
+    ) -> Result<(), AmlError> {
+        let _ = (write, buffer);
+        Err(AmlError::NoRegionHandler(access.space))
+    }
+}
+
+/// Default handler for `SystemMemory` regions
+///
+/// Each access maps the accessed bytes with the [`Bridge`] and unmaps them afterwards.
+#[derive(Clone, Copy, Debug)]
+pub struct SystemMemoryHandler<B: Bridge> {
+    bridge: B,
+}
+
+impl<B: Bridge> SystemMemoryHandler<B> {
+    pub fn new(bridge: B) -> SystemMemoryHandler<B> {
+        Self { bridge }
+    }
+
+    fn map(&self, access: &RegionAccess<'_>) -> Result<(*mut u8, usize), AmlError> {
+        let size = access.access_size()?.bytes().unwrap();
+        let phys = usize::try_from(access.address()?).map_err(|_| AmlError::IndexOutOfBounds)?;
+        let virt = map_nonnull(phys, size, self.bridge).map_err(AmlError::Region)?;
+        Ok((ptr::with_exposed_provenance_mut::<u8>(virt), size))
+    }
+}
+
+impl<B: Bridge> RegionHandler for SystemMemoryHandler<B> {
+    fn read(&mut self, access: &RegionAccess<'_>) -> Result<u64, AmlError> {
+        let (ptr, size) = self.map(access)?;
+        // SAFETY: The bridge mapped `size` bytes at `ptr`.
+        let value = unsafe {
+            match size {
+                2 if ptr.cast::<u16>().is_aligned() => ptr.cast::<u16>().read_volatile() as u64,
+                4 if ptr.cast::<u32>().is_aligned() => ptr.cast::<u32>().read_volatile() as u64,
+                8 if ptr.cast::<u64>().is_aligned() => ptr.cast::<u64>().read_volatile(),
+                _ => (0..size)
+                    .rev()
+                    .fold(0, |value, i| value << 8 | ptr.add(i).read_volatile() as u64),
+            }
+        };
+        self.bridge.unmap(ptr.addr());
+        Ok(value)
+    }
+
+    fn write(&mut self, access: &RegionAccess<'_>, value: u64) -> Result<(), AmlError> {
+        let (ptr, size) = self.map(access)?;
+        // SAFETY: The bridge mapped `size` bytes at `ptr`.
+        unsafe {
+            match size {
+                2 if ptr.cast::<u16>().is_aligned() => {
+                    ptr.cast::<u16>().write_volatile(value as u16)
+                }
+                4 if ptr.cast::<u32>().is_aligned() => {
+                    ptr.cast::<u32>().write_volatile(value as u32)
+                }
+                8 if ptr.cast::<u64>().is_aligned() => ptr.cast::<u64>().write_volatile(value),
+                _ => {
+                    for i in 0..size {
+                        ptr.add(i).write_volatile((value >> (i * 8)) as u8);
+                    }
+                }
+            }
+        }
+        self.bridge.unmap(ptr.addr());
+        Ok(())
+    }
+}
+
+/// Default handler for `SystemIO` regions, built on [`RegisterIo`]
+#[derive(Clone, Copy, Debug)]
+pub struct SystemIoHandler<R: RegisterIo> {
+    io: R,
+}
+
+impl<R: RegisterIo> SystemIoHandler<R> {
+    pub fn new(io: R) -> SystemIoHandler<R> {
+        Self { io }
+    }
+}
+
+impl<R: RegisterIo> RegionHandler for SystemIoHandler<R> {
+    fn read(&mut self, access: &RegionAccess<'_>) -> Result<u64, AmlError> {
+        self.io
+            .read_io(access.address()?, access.access_size()?)
+            .map_err(AmlError::Region)
+    }
+
+    fn write(&mut self, access: &RegionAccess<'_>, value: u64) -> Result<(), AmlError> {
+        self.io
+            .write_io(access.address()?, access.access_size()?, value)
+            .map_err(AmlError::Region)
+    }
+}
+
+/// Default handler for `PCI_Config` regions, built on [`RegisterIo`]
+#[derive(Clone, Copy, Debug)]
+pub struct PciConfigHandler<R: RegisterIo> {
+    io: R,
+}
+
+impl<R: RegisterIo> PciConfigHandler<R> {
+    pub fn new(io: R) -> PciConfigHandler<R> {
+        Self { io }
+    }
+}
+
+fn pci_address(access: &RegionAccess<'_>) -> Result<PciAddress, AmlError> {
+    let pci = access.pci.ok_or(AmlError::InvalidReference)?;
+    let offset = u16::try_from(access.address()?).map_err(|_| AmlError::IndexOutOfBounds)?;
+    Ok(PciAddress { offset, ..pci })
+}
+
+impl<R: RegisterIo> RegionHandler for PciConfigHandler<R> {
+    fn read(&mut self, access: &RegionAccess<'_>) -> Result<u64, AmlError> {
+        self.io
+            .read_pci_config(pci_address(access)?, access.access_size()?)
+            .map_err(AmlError::Region)
+    }
+
+    fn write(&mut self, access: &RegionAccess<'_>, value: u64) -> Result<(), AmlError> {
+        self.io
+            .write_pci_config(pci_address(access)?, access.access_size()?, value)
+            .map_err(AmlError::Region)
+    }
+}
@@ -0,0 +1,1584 @@
+//! Resource Templates
+//!
+//! Device configuration objects such as `_CRS`, `_PRS` and `_SRS` describe the resources of
+//! a device with a buffer containing a list of resource descriptors, terminated by an end
+//! tag. [`decode()`] parses such a buffer into [`Resource`]s, and [`encode()`] builds one
+//! from them, such as to pass to `_SRS`.
+//!
+//! Neither requires an [`Interpreter`](super::Interpreter); they operate on plain byte
+//! slices, so resource templates may also be taken directly from a definition block.
+
+use crate::address::GenericAddress;
+use alloc::{string::String, vec, vec::Vec};
+use core::fmt;
+
+const LARGE: u8 = 0x80;
+
+// Small resource descriptor names
+const IRQ: u8 = 0x04;
+const DMA: u8 = 0x05;
+const START_DEPENDENT: u8 = 0x06;
+const END_DEPENDENT: u8 = 0x07;
+const IO: u8 = 0x08;
+const FIXED_IO: u8 = 0x09;
+const FIXED_DMA: u8 = 0x0a;
+const VENDOR_SHORT: u8 = 0x0e;
+const END_TAG: u8 = 0x0f;
+
+// Large resource descriptor names
+const MEMORY24: u8 = 0x01;
+const GENERIC_REGISTER: u8 = 0x02;
+const VENDOR_LONG: u8 = 0x04;
+const MEMORY32: u8 = 0x05;
+const FIXED_MEMORY32: u8 = 0x06;
+const DWORD_ADDRESS: u8 = 0x07;
+const WORD_ADDRESS: u8 = 0x08;
+const EXTENDED_IRQ: u8 = 0x09;
+const QWORD_ADDRESS: u8 = 0x0a;
+const EXTENDED_ADDRESS: u8 = 0x0b;
+const GPIO: u8 = 0x0c;
+const PIN_FUNCTION: u8 = 0x0d;
+const SERIAL_BUS: u8 = 0x0e;
+const PIN_CONFIG: u8 = 0x0f;
+const PIN_GROUP: u8 = 0x10;
+const PIN_GROUP_FUNCTION: u8 = 0x11;
+const PIN_GROUP_CONFIG: u8 = 0x12;
+
+// Generic Serial Bus types
+const I2C: u8 = 1;
+const SPI: u8 = 2;
+const UART: u8 = 3;
+
+/// Error decoding a resource template
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ResourceError {
+    /// Offset of the malformed descriptor from the start of the buffer
+    pub offset: usize,
+    pub kind: ResourceErrorKind,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ResourceErrorKind {
+    /// A descriptor extended past the end of the buffer
+    UnexpectedEnd,
+    /// A descriptor was too short for its type
+    InvalidLength,
+    /// An offset within a descriptor pointed outside of it
+    InvalidOffset,
+    /// A field of a descriptor held a reserved value
+    InvalidValue,
+    /// The buffer ended without an end tag
+    MissingEndTag,
+}
+
+impl fmt::Display for ResourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self.kind {
+            ResourceErrorKind::UnexpectedEnd => "unexpected end of resource template",
+            ResourceErrorKind::InvalidLength => "invalid descriptor length",
+            ResourceErrorKind::InvalidOffset => "invalid offset in descriptor",
+            ResourceErrorKind::InvalidValue => "invalid value in descriptor",
+            ResourceErrorKind::MissingEndTag => "missing end tag",
+        })?;
+        write!(f, " at offset {:#x}", self.offset)
+    }
+}
+
+/// Resource Descriptor
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Resource {
+    Irq(Irq),
+    Dma(Dma),
+    /// Start Dependent Functions, with an optional priority byte
+    StartDependent(Option<u8>),
+    EndDependent,
+    Io(Io),
+    FixedIo(FixedIo),
+    FixedDma(FixedDma),
+    /// Small Vendor-Defined Descriptor, holding up to 7 bytes
+    VendorShort(Vec<u8>),
+    Memory24(Memory24),
+    GenericRegister(GenericAddress),
+    /// Large Vendor-Defined Descriptor, starting with a subtype byte and a UUID
+    VendorLong(Vec<u8>),
+    Memory32(Memory32),
+    FixedMemory32(FixedMemory32),
+    /// Word, DWord or QWord Address Space Descriptor
+    Address(Address),
+    ExtendedAddress(ExtendedAddress),
+    ExtendedIrq(ExtendedIrq),
+    Gpio(Gpio),
+    PinFunction(PinFunction),
+    SerialBus(SerialBus),
+    PinConfig(PinConfig),
+    PinGroup(PinGroup),
+    PinGroupFunction(PinGroupFunction),
+    PinGroupConfig(PinGroupConfig),
+    /// A descriptor of an unknown type, which is encoded back unchanged
+    Unknown {
+        large: bool,
+        name: u8,
+        data: Vec<u8>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Trigger {
+    Level,
+    Edge,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Polarity {
+    ActiveHigh,
+    ActiveLow,
+    /// Both edges, only valid for GPIO interrupts
+    ActiveBoth,
+}
+
+/// Interrupt Flags
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct InterruptFlags {
+    pub trigger: Trigger,
+    pub polarity: Polarity,
+    pub shared: bool,
+    /// The interrupt can wake the system from a sleep state
+    pub wake_capable: bool,
+}
+
+impl InterruptFlags {
+    /// Flags of an IRQ descriptor without an information byte
+    pub const ISA: InterruptFlags = InterruptFlags {
+        trigger: Trigger::Edge,
+        polarity: Polarity::ActiveHigh,
+        shared: false,
+        wake_capable: false,
+    };
+}
+
+/// Common source of a resource, naming the device which produces it
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ResourceSource {
+    pub index: u8,
+    /// Path to the producer device
+    pub name: String,
+}
+
+/// IRQ Descriptor
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Irq {
+    /// Mask of the ISA IRQs which may be used
+    pub mask: u16,
+    /// Information byte, which is optional in the descriptor
+    pub info: Option<InterruptFlags>,
+}
+
+impl Irq {
+    /// Returns the flags of the IRQs
+    #[inline]
+    pub fn flags(&self) -> InterruptFlags {
+        self.info.unwrap_or(InterruptFlags::ISA)
+    }
+
+    /// Returns an iterator over the IRQ numbers set in the mask
+    pub fn irqs(&self) -> impl Iterator<Item = u8> {
+        let mask = self.mask;
+        (0..16).filter(move |i| mask & 1 << i != 0)
+    }
+}
+
+/// DMA Descriptor
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Dma {
+    /// Mask of the ISA DMA channels which may be used
+    pub mask: u8,
+    pub transfer: DmaTransfer,
+    pub bus_master: bool,
+    pub speed: DmaSpeed,
+}
+
+/// Transfer size of a [`Dma`] channel
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct DmaTransfer(pub u8);
+
+impl DmaTransfer {
+    pub const BITS_8: Self = Self(0);
+    pub const BITS_8_16: Self = Self(1);
+    pub const BITS_16: Self = Self(2);
+}
+
+/// Channel speed of a [`Dma`] channel
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct DmaSpeed(pub u8);
+
+impl DmaSpeed {
+    pub const COMPATIBILITY: Self = Self(0);
+    pub const TYPE_A: Self = Self(1);
+    pub const TYPE_B: Self = Self(2);
+    pub const TYPE_F: Self = Self(3);
+}
+
+/// I/O Port Descriptor
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Io {
+    /// The device decodes all 16 bits of the address, rather than only 10
+    pub decode16: bool,
+    pub min: u16,
+    pub max: u16,
+    pub alignment: u8,
+    pub length: u8,
+}
+
+/// Fixed Location I/O Port Descriptor
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct FixedIo {
+    pub base: u16,
+    pub length: u8,
+}
+
+/// Fixed DMA Descriptor
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct FixedDma {
+    pub request_line: u16,
+    pub channel: u16,
+    /// Transfer width, as a power of two bytes
+    pub width: u8,
+}
+
+/// 24-Bit Memory Range Descriptor
+///
+/// The addresses and length are in bytes, although the descriptor only holds bits 8 to 23
+/// of each.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Memory24 {
+    pub writable: bool,
+    pub min: u32,
+    pub max: u32,
+    pub alignment: u32,
+    pub length: u32,
+}
+
+/// 32-Bit Memory Range Descriptor
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Memory32 {
+    pub writable: bool,
+    pub min: u32,
+    pub max: u32,
+    pub alignment: u32,
+    pub length: u32,
+}
+
+/// 32-Bit Fixed Memory Range Descriptor
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct FixedMemory32 {
+    pub writable: bool,
+    pub base: u32,
+    pub length: u32,
+}
+
+/// Size of the fields of an [`Address`] descriptor
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum AddressWidth {
+    Word,
+    DWord,
+    QWord,
+}
+
+impl AddressWidth {
+    const fn bytes(self) -> usize {
+        match self {
+            AddressWidth::Word => 2,
+            AddressWidth::DWord => 4,
+            AddressWidth::QWord => 8,
+        }
+    }
+
+    const fn name(self) -> u8 {
+        match self {
+            AddressWidth::Word => WORD_ADDRESS,
+            AddressWidth::DWord => DWORD_ADDRESS,
+            AddressWidth::QWord => QWORD_ADDRESS,
+        }
+    }
+}
+
+/// Resource type of an address space descriptor
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct AddressResourceType(pub u8);
+
+impl AddressResourceType {
+    pub const MEMORY: Self = Self(0);
+    pub const IO: Self = Self(1);
+    pub const BUS_NUMBER: Self = Self(2);
+}
+
+bitflags::bitflags! {
+    /// General flags of an address space descriptor
+    #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+    pub struct AddressFlags : u8 {
+        /// The device consumes this resource, rather than producing it for its children
+        const CONSUMER = 1 << 0;
+        const SUBTRACTIVE_DECODE = 1 << 1;
+        const MIN_FIXED = 1 << 2;
+        const MAX_FIXED = 1 << 3;
+    }
+}
+
+/// Word, DWord or QWord Address Space Descriptor
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Address {
+    pub width: AddressWidth,
+    pub resource_type: AddressResourceType,
+    pub flags: AddressFlags,
+    /// Flags specific to the resource type, such as the cacheability of memory
+    pub type_flags: u8,
+    pub granularity: u64,
+    pub min: u64,
+    pub max: u64,
+    /// Offset added to addresses on the secondary side of a bridge to get the primary
+    /// side address
+    pub translation: u64,
+    pub length: u64,
+    pub source: Option<ResourceSource>,
+}
+
+/// Extended Address Space Descriptor
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ExtendedAddress {
+    pub resource_type: AddressResourceType,
+    pub flags: AddressFlags,
+    pub type_flags: u8,
+    pub revision: u8,
+    pub granularity: u64,
+    pub min: u64,
+    pub max: u64,
+    pub translation: u64,
+    pub length: u64,
+    /// Attributes specific to the resource type, such as the EFI memory attributes
+    pub attribute: u64,
+}
+
+/// Extended Interrupt Descriptor
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ExtendedIrq {
+    pub consumer: bool,
+    pub flags: InterruptFlags,
+    /// Global System Interrupts, or interrupts of the source device if there is one
+    pub interrupts: Vec<u32>,
+    pub source: Option<ResourceSource>,
+}
+
+/// GPIO Connection Descriptor
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Gpio {
+    pub revision: u8,
+    pub consumer: bool,
+    pub kind: GpioKind,
+    /// Pin pull configuration, one of the [`PinConfig`] pull types
+    pub pin_config: u8,
+    /// Output drive strength, in hundredths of milliamperes
+    pub drive_strength: u16,
+    /// Debounce timeout, in hundredths of milliseconds
+    pub debounce_timeout: u16,
+    pub pins: Vec<u16>,
+    /// The GPIO controller
+    pub source: ResourceSource,
+    pub vendor_data: Vec<u8>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum GpioKind {
+    Interrupt(InterruptFlags),
+    Io {
+        restriction: IoRestriction,
+        shared: bool,
+    },
+}
+
+/// I/O restriction of a GPIO I/O connection
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct IoRestriction(pub u8);
+
+impl IoRestriction {
+    pub const NONE: Self = Self(0);
+    pub const INPUT_ONLY: Self = Self(1);
+    pub const OUTPUT_ONLY: Self = Self(2);
+    pub const PRESERVE: Self = Self(3);
+}
+
+/// Generic Serial Bus Connection Descriptor
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SerialBus {
+    pub revision: u8,
+    pub type_revision: u8,
+    /// The connection is initiated by the device, rather than the controller
+    pub device_initiated: bool,
+    pub consumer: bool,
+    pub shared: bool,
+    pub kind: SerialBusKind,
+    /// The serial bus controller
+    pub source: ResourceSource,
+    pub vendor_data: Vec<u8>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SerialBusKind {
+    I2c {
+        /// Connection speed in hertz
+        speed: u32,
+        address: u16,
+        ten_bit_address: bool,
+    },
+    Spi {
+        /// Connection speed in hertz
+        speed: u32,
+        data_bit_length: u8,
+        clock_phase: u8,
+        clock_polarity: u8,
+        device_selection: u16,
+        three_wire: bool,
+        /// The device selection line is active-high
+        active_high: bool,
+    },
+    Uart {
+        baud_rate: u32,
+        rx_fifo: u16,
+        tx_fifo: u16,
+        parity: u8,
+        /// Mask of the serial lines which are enabled
+        lines: u8,
+        /// Flow control, stop bits, data bits and endianness
+        flags: u16,
+    },
+    /// Another bus type, whose type-specific data is kept in the vendor data
+    Other { bus_type: u8, flags: u16 },
+}
+
+/// Pin Function Descriptor
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct PinFunction {
+    pub revision: u8,
+    /// General flags, bit 0 is set if the pins are shared
+    pub flags: u16,
+    /// Pin pull configuration, one of the [`PinConfig`] pull types
+    pub pin_config: u8,
+    pub function: u16,
+    pub pins: Vec<u16>,
+    /// The GPIO controller
+    pub source: ResourceSource,
+    pub vendor_data: Vec<u8>,
+}
+
+/// Pin Configuration Descriptor
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct PinConfig {
+    pub revision: u8,
+    /// General flags, bit 0 is set if the pins are shared and bit 1 if they are consumed
+    pub flags: u16,
+    pub config_type: u8,
+    pub config_value: u32,
+    pub pins: Vec<u16>,
+    /// The GPIO controller
+    pub source: ResourceSource,
+    pub vendor_data: Vec<u8>,
+}
+
+impl PinConfig {
+    pub const DEFAULT: u8 = 0x00;
+    pub const PULL_UP: u8 = 0x01;
+    pub const PULL_DOWN: u8 = 0x02;
+    pub const NO_PULL: u8 = 0x03;
+}
+
+/// Pin Group Descriptor
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct PinGroup {
+    pub revision: u8,
+    /// General flags, bit 0 is set if the group is consumed
+    pub flags: u16,
+    pub pins: Vec<u16>,
+    /// Label referenced by [`PinGroupFunction`] and [`PinGroupConfig`] descriptors
+    pub label: String,
+    pub vendor_data: Vec<u8>,
+}
+
+/// Pin Group Function Descriptor
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct PinGroupFunction {
+    pub revision: u8,
+    /// General flags, bit 0 is set if the group is shared and bit 1 if it is consumed
+    pub flags: u16,
+    pub function: u16,
+    /// The GPIO controller
+    pub source: ResourceSource,
+    /// Label of the [`PinGroup`] within the GPIO controller
+    pub label: String,
+    pub vendor_data: Vec<u8>,
+}
+
+/// Pin Group Configuration Descriptor
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct PinGroupConfig {
+    pub revision: u8,
+    /// General flags, bit 0 is set if the group is shared and bit 1 if it is consumed
+    pub flags: u16,
+    pub config_type: u8,
+    pub config_value: u32,
+    /// The GPIO controller
+    pub source: ResourceSource,
+    /// Label of the [`PinGroup`] within the GPIO controller
+    pub label: String,
+    pub vendor_data: Vec<u8>,
+}
+
+/// Decode a resource template
+///
+/// Returns an error if a descriptor is malformed or the template has no end tag.
+pub fn decode(bytes: &[u8]) -> Result<Vec<Resource>, ResourceError> {
+    let mut resources = Vec::new();
+    let mut offset = 0;
+    while let Some((resource, len)) = next(bytes, offset)? {
+        resources.push(resource);
+        offset += len;
+    }
+    Ok(resources)
+}
+
+/// Returns an iterator over the descriptors of a resource template
+///
+/// Iteration stops at the end tag, the end of the buffer, or after the first error.
+pub fn descriptors(bytes: &[u8]) -> impl Iterator<Item = Result<Resource, ResourceError>> + '_ {
+    let mut offset = 0;
+    let mut done = false;
+    core::iter::from_fn(move || {
+        if done || offset >= bytes.len() {
+            return None;
+        }
+        match next(bytes, offset) {
+            Ok(Some((resource, len))) => {
+                offset += len;
+                Some(Ok(resource))
+            }
+            Ok(None) => {
+                done = true;
+                None
+            }
+            Err(err) => {
+                done = true;
+                Some(Err(err))
+            }
+        }
+    })
+}
+
+/// Encode a resource template, terminated by an end tag
+pub fn encode(resources: &[Resource]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for resource in resources {
+        resource.encode(&mut out);
+    }
+    // A checksum of zero means the template is treated as having a valid checksum.
+    out.extend_from_slice(&[END_TAG << 3 | 1, 0]);
+    out
+}
+
+/// Decode the descriptor at `offset`, returning it and its length
+///
+/// Returns `None` at the end tag.
+fn next(bytes: &[u8], offset: usize) -> Result<Option<(Resource, usize)>, ResourceError> {
+    let error = |kind| ResourceError { offset, kind };
+    let tag = *bytes
+        .get(offset)
+        .ok_or(error(ResourceErrorKind::MissingEndTag))?;
+    let len = if tag & LARGE == 0 {
+        1 + (tag & 0x7) as usize
+    } else {
+        let len = bytes
+            .get(offset + 1..offset + 3)
+            .ok_or(error(ResourceErrorKind::UnexpectedEnd))?;
+        3 + u16::from_le_bytes([len[0], len[1]]) as usize
+    };
+    let data = bytes
+        .get(offset..offset + len)
+        .ok_or(error(ResourceErrorKind::UnexpectedEnd))?;
+    let desc = Descriptor { data, offset };
+    let resource = if tag & LARGE == 0 {
+        match tag >> 3 {
+            END_TAG => return Ok(None),
+            name => desc.small(name)?,
+        }
+    } else {
+        desc.large(tag & !LARGE)?
+    };
+    Ok(Some((resource, len)))
+}
+
+/// A single descriptor, including its header
+///
+/// Offsets within descriptors are from the start of the header, so fields are read at the
+/// offsets given by the specification.
+struct Descriptor<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Descriptor<'a> {
+    fn error(&self, kind: ResourceErrorKind) -> ResourceError {
+        ResourceError {
+            offset: self.offset,
+            kind,
+        }
+    }
+
+    fn bytes(&self, at: usize, len: usize) -> Result<&'a [u8], ResourceError> {
+        self.data
+            .get(at..at + len)
+            .ok_or(self.error(ResourceErrorKind::InvalidLength))
+    }
+
+    fn u8(&self, at: usize) -> Result<u8, ResourceError> {
+        Ok(self.bytes(at, 1)?[0])
+    }
+
+    fn u16(&self, at: usize) -> Result<u16, ResourceError> {
+        Ok(self.uint(at, 2)? as u16)
+    }
+
+    fn u32(&self, at: usize) -> Result<u32, ResourceError> {
+        Ok(self.uint(at, 4)? as u32)
+    }
+
+    fn u64(&self, at: usize) -> Result<u64, ResourceError> {
+        self.uint(at, 8)
+    }
+
+    fn uint(&self, at: usize, size: usize) -> Result<u64, ResourceError> {
+        let bytes = self.bytes(at, size)?;
+        Ok(bytes
+            .iter()
+            .rev()
+            .fold(0, |value, &b| value << 8 | b as u64))
+    }
+
+    /// Returns the bytes from `start` to `end`, which are given by fields of the descriptor
+    fn range(&self, start: usize, end: usize) -> Result<&'a [u8], ResourceError> {
+        self.data
+            .get(start..end)
+            .ok_or(self.error(ResourceErrorKind::InvalidOffset))
+    }
+
+    /// Read a null-terminated string starting at `at`
+    fn string(&self, at: usize) -> Result<String, ResourceError> {
+        let bytes = self.range(at, self.data.len())?;
+        let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        Ok(String::from_utf8_lossy(&bytes[..len]).into_owned())
+    }
+
+    /// Read an optional resource source, which is present if the descriptor extends to `at`
+    fn optional_source(&self, at: usize) -> Result<Option<ResourceSource>, ResourceError> {
+        if at >= self.data.len() {
+            return Ok(None);
+        }
+        Ok(Some(ResourceSource {
+            index: self.u8(at)?,
+            name: self.string(at + 1)?,
+        }))
+    }
+
+    fn pins(&self, start: usize, end: usize) -> Result<Vec<u16>, ResourceError> {
+        Ok(self
+            .range(start, end)?
+            .chunks_exact(2)
+            .map(|pin| u16::from_le_bytes([pin[0], pin[1]]))
+            .collect())
+    }
+
+    /// Read vendor data given by the offset at `at` and the length after it
+    fn vendor_data(&self, at: usize) -> Result<Vec<u8>, ResourceError> {
+        let offset = self.u16(at)? as usize;
+        let len = self.u16(at + 2)? as usize;
+        if len == 0 {
+            return Ok(Vec::new());
+        }
+        Ok(self.range(offset, offset + len)?.to_vec())
+    }
+
+    fn small(&self, name: u8) -> Result<Resource, ResourceError> {
+        let resource = match name {
+            IRQ => Resource::Irq(Irq {
+                mask: self.u16(1)?,
+                info: match self.data.len() {
+                    3 => None,
+                    _ => {
+                        let info = self.u8(3)?;
+                        Some(InterruptFlags {
+                            trigger: trigger(info & 1 << 0 != 0),
+                            polarity: polarity(info & 1 << 3 != 0),
+                            shared: info & 1 << 4 != 0,
+                            wake_capable: info & 1 << 5 != 0,
+                        })
+                    }
+                },
+            }),
+            DMA => {
+                let flags = self.u8(2)?;
+                Resource::Dma(Dma {
+                    mask: self.u8(1)?,
+                    transfer: DmaTransfer(flags & 0x3),
+                    bus_master: flags & 1 << 2 != 0,
+                    speed: DmaSpeed(flags >> 5 & 0x3),
+                })
+            }
+            START_DEPENDENT => Resource::StartDependent(self.data.get(1).copied()),
+            END_DEPENDENT => Resource::EndDependent,
+            IO => Resource::Io(Io {
+                decode16: self.u8(1)? & 1 != 0,
+                min: self.u16(2)?,
+                max: self.u16(4)?,
+                alignment: self.u8(6)?,
+                length: self.u8(7)?,
+            }),
+            FIXED_IO => Resource::FixedIo(FixedIo {
+                base: self.u16(1)?,
+                length: self.u8(3)?,
+            }),
+            FIXED_DMA => Resource::FixedDma(FixedDma {
+                request_line: self.u16(1)?,
+                channel: self.u16(3)?,
+                width: self.u8(5)?,
+            }),
+            VENDOR_SHORT => Resource::VendorShort(self.data[1..].to_vec()),
+            _ => Resource::Unknown {
+                large: false,
+                name,
+                data: self.data[1..].to_vec(),
+            },
+        };
+        Ok(resource)
+    }
+
+    fn large(&self, name: u8) -> Result<Resource, ResourceError> {
+        let resource = match name {
+            MEMORY24 => Resource::Memory24(Memory24 {
+                writable: self.u8(3)? & 1 != 0,
+                min: (self.u16(4)? as u32) << 8,
+                max: (self.u16(6)? as u32) << 8,
+                alignment: match self.u16(8)? {
+                    0 => 0x10000,
+                    alignment => alignment as u32,
+                },
+                length: (self.u16(10)? as u32) << 8,
+            }),
+            GENERIC_REGISTER => {
+                let bytes = self.bytes(3, GenericAddress::SIZE)?;
+                Resource::GenericRegister(GenericAddress::from_bytes(bytes.try_into().unwrap()))
+            }
+            VENDOR_LONG => Resource::VendorLong(self.data[3..].to_vec()),
+            MEMORY32 => Resource::Memory32(Memory32 {
+                writable: self.u8(3)? & 1 != 0,
+                min: self.u32(4)?,
+                max: self.u32(8)?,
+                alignment: self.u32(12)?,
+                length: self.u32(16)?,
+            }),
+            FIXED_MEMORY32 => Resource::FixedMemory32(FixedMemory32 {
+                writable: self.u8(3)? & 1 != 0,
+                base: self.u32(4)?,
+                length: self.u32(8)?,
+            }),
+            WORD_ADDRESS => self.address(AddressWidth::Word)?,
+            DWORD_ADDRESS => self.address(AddressWidth::DWord)?,
+            QWORD_ADDRESS => self.address(AddressWidth::QWord)?,
+            EXTENDED_ADDRESS => Resource::ExtendedAddress(ExtendedAddress {
+                resource_type: AddressResourceType(self.u8(3)?),
+                flags: AddressFlags::from_bits_retain(self.u8(4)?),
+                type_flags: self.u8(5)?,
+                revision: self.u8(6)?,
+                granularity: self.u64(8)?,
+                min: self.u64(16)?,
+                max: self.u64(24)?,
+                translation: self.u64(32)?,
+                length: self.u64(40)?,
+                attribute: self.u64(48)?,
+            }),
+            EXTENDED_IRQ => {
+                let flags = self.u8(3)?;
+                let count = self.u8(4)? as usize;
+                let interrupts = (0..count)
+                    .map(|i| self.u32(5 + i * 4))
+                    .collect::<Result<_, _>>()?;
+                Resource::ExtendedIrq(ExtendedIrq {
+                    consumer: flags & 1 << 0 != 0,
+                    flags: InterruptFlags {
+                        trigger: trigger(flags & 1 << 1 != 0),
+                        polarity: polarity(flags & 1 << 2 != 0),
+                        shared: flags & 1 << 3 != 0,
+                        wake_capable: flags & 1 << 4 != 0,
+                    },
+                    interrupts,
+                    source: self.optional_source(5 + count * 4)?,
+                })
+            }
+            GPIO => self.gpio()?,
+            PIN_FUNCTION => Resource::PinFunction(PinFunction {
+                revision: self.u8(3)?,
+                flags: self.u16(4)?,
+                pin_config: self.u8(6)?,
+                function: self.u16(7)?,
+                pins: self.pins(self.u16(9)? as usize, self.u16(12)? as usize)?,
+                source: ResourceSource {
+                    index: self.u8(11)?,
+                    name: self.string(self.u16(12)? as usize)?,
+                },
+                vendor_data: self.vendor_data(14)?,
+            }),
+            SERIAL_BUS => self.serial_bus()?,
+            PIN_CONFIG => Resource::PinConfig(PinConfig {
+                revision: self.u8(3)?,
+                flags: self.u16(4)?,
+                config_type: self.u8(6)?,
+                config_value: self.u32(7)?,
+                pins: self.pins(self.u16(11)? as usize, self.u16(14)? as usize)?,
+                source: ResourceSource {
+                    index: self.u8(13)?,
+                    name: self.string(self.u16(14)? as usize)?,
+                },
+                vendor_data: self.vendor_data(16)?,
+            }),
+            PIN_GROUP => Resource::PinGroup(PinGroup {
+                revision: self.u8(3)?,
+                flags: self.u16(4)?,
+                pins: self.pins(self.u16(6)? as usize, self.u16(8)? as usize)?,
+                label: self.string(self.u16(8)? as usize)?,
+                vendor_data: self.vendor_data(10)?,
+            }),
+            PIN_GROUP_FUNCTION => Resource::PinGroupFunction(PinGroupFunction {
+                revision: self.u8(3)?,
+                flags: self.u16(4)?,
+                function: self.u16(6)?,
+                source: ResourceSource {
+                    index: self.u8(8)?,
+                    name: self.string(self.u16(9)? as usize)?,
+                },
+                label: self.string(self.u16(11)? as usize)?,
+                vendor_data: self.vendor_data(13)?,
+            }),
+            PIN_GROUP_CONFIG => Resource::PinGroupConfig(PinGroupConfig {
+                revision: self.u8(3)?,
+                flags: self.u16(4)?,
+                config_type: self.u8(6)?,
+                config_value: self.u32(7)?,
+                source: ResourceSource {
+                    index: self.u8(11)?,
+                    name: self.string(self.u16(12)? as usize)?,
+                },
+                label: self.string(self.u16(14)? as usize)?,
+                vendor_data: self.vendor_data(16)?,
+            }),
+            _ => Resource::Unknown {
+                large: true,
+                name,
+                data: self.data[3..].to_vec(),
+            },
+        };
+        Ok(resource)
+    }
+
+    fn address(&self, width: AddressWidth) -> Result<Resource, ResourceError> {
+        let size = width.bytes();
+        Ok(Resource::Address(Address {
+            width,
+            resource_type: AddressResourceType(self.u8(3)?),
+            flags: AddressFlags::from_bits_retain(self.u8(4)?),
+            type_flags: self.u8(5)?,
+            granularity: self.uint(6, size)?,
+            min: self.uint(6 + size, size)?,
+            max: self.uint(6 + 2 * size, size)?,
+            translation: self.uint(6 + 3 * size, size)?,
+            length: self.uint(6 + 4 * size, size)?,
+            source: self.optional_source(6 + 5 * size)?,
+        }))
+    }
+
+    fn gpio(&self) -> Result<Resource, ResourceError> {
+        let flags = self.u16(7)?;
+        let kind = match self.u8(4)? {
+            0 => GpioKind::Interrupt(InterruptFlags {
+                trigger: trigger(flags & 1 << 0 != 0),
+                polarity: match flags >> 1 & 0x3 {
+                    0 => Polarity::ActiveHigh,
+                    1 => Polarity::ActiveLow,
+                    2 => Polarity::ActiveBoth,
+                    _ => return Err(self.error(ResourceErrorKind::InvalidValue)),
+                },
+                shared: flags & 1 << 3 != 0,
+                wake_capable: flags & 1 << 4 != 0,
+            }),
+            1 => GpioKind::Io {
+                restriction: IoRestriction((flags & 0x3) as u8),
+                shared: flags & 1 << 3 != 0,
+            },
+            _ => return Err(self.error(ResourceErrorKind::InvalidValue)),
+        };
+        let name = self.u16(17)? as usize;
+        Ok(Resource::Gpio(Gpio {
+            revision: self.u8(3)?,
+            consumer: self.u16(5)? & 1 != 0,
+            kind,
+            pin_config: self.u8(9)?,
+            drive_strength: self.u16(10)?,
+            debounce_timeout: self.u16(12)?,
+            pins: self.pins(self.u16(14)? as usize, name)?,
+            source: ResourceSource {
+                index: self.u8(16)?,
+                name: self.string(name)?,
+            },
+            vendor_data: self.vendor_data(19)?,
+        }))
+    }
+
+    fn serial_bus(&self) -> Result<Resource, ResourceError> {
+        let flags = self.u8(6)?;
+        let type_flags = self.u16(7)?;
+        let type_len = self.u16(10)? as usize;
+        let type_data = self.bytes(12, type_len)?;
+        let (kind, fixed) = match self.u8(5)? {
+            I2C => (
+                SerialBusKind::I2c {
+                    speed: self.u32(12)?,
+                    address: self.u16(16)?,
+                    ten_bit_address: type_flags & 1 << 0 != 0,
+                },
+                6,
+            ),
+            SPI => (
+                SerialBusKind::Spi {
+                    speed: self.u32(12)?,
+                    data_bit_length: self.u8(16)?,
+                    clock_phase: self.u8(17)?,
+                    clock_polarity: self.u8(18)?,
+                    device_selection: self.u16(19)?,
+                    three_wire: type_flags & 1 << 0 != 0,
+                    active_high: type_flags & 1 << 1 != 0,
+                },
+                9,
+            ),
+            UART => (
+                SerialBusKind::Uart {
+                    baud_rate: self.u32(12)?,
+                    rx_fifo: self.u16(16)?,
+                    tx_fifo: self.u16(18)?,
+                    parity: self.u8(20)?,
+                    lines: self.u8(21)?,
+                    flags: type_flags,
+                },
+                10,
+            ),
+            bus_type => (
+                SerialBusKind::Other {
+                    bus_type,
+                    flags: type_flags,
+                },
+                0,
+            ),
+        };
+        Ok(Resource::SerialBus(SerialBus {
+            revision: self.u8(3)?,
+            type_revision: self.u8(9)?,
+            device_initiated: flags & 1 << 0 != 0,
+            consumer: flags & 1 << 1 != 0,
+            shared: flags & 1 << 2 != 0,
+            kind,
+            source: ResourceSource {
+                index: self.u8(4)?,
+                name: self.string(12 + type_len)?,
+            },
+            vendor_data: type_data.get(fixed..).unwrap_or_default().to_vec(),
+        }))
+    }
+}
+
+const fn trigger(edge: bool) -> Trigger {
+    if edge {
+        Trigger::Edge
+    } else {
+        Trigger::Level
+    }
+}
+
+const fn polarity(active_low: bool) -> Polarity {
+    if active_low {
+        Polarity::ActiveLow
+    } else {
+        Polarity::ActiveHigh
+    }
+}
+
+/// Builds a large descriptor, whose length is filled in by [`Builder::finish()`]
+struct Builder(Vec<u8>);
+
+impl Builder {
+    fn new(name: u8) -> Builder {
+        Self(vec![LARGE | name, 0, 0])
+    }
+
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn uint(&mut self, value: u64, size: usize) {
+        self.0.extend_from_slice(&value.to_le_bytes()[..size]);
+    }
+
+    fn string(&mut self, s: &str) {
+        self.0.extend_from_slice(s.as_bytes());
+        self.0.push(0);
+    }
+
+    fn source(&mut self, source: &Option<ResourceSource>) {
+        if let Some(source) = source {
+            self.u8(source.index);
+            self.string(&source.name);
+        }
+    }
+
+    fn pins(&mut self, pins: &[u16]) {
+        for &pin in pins {
+            self.u16(pin);
+        }
+    }
+
+    /// Returns the offset of the next byte from the start of the descriptor
+    fn offset(&self) -> u16 {
+        self.0.len() as u16
+    }
+
+    fn set_u16(&mut self, at: usize, value: u16) {
+        self.0[at..at + 2].copy_from_slice(&value.to_le_bytes());
+    }
+
+    /// Append vendor data, and set the vendor data offset field at `at`
+    ///
+    /// The offset is set even if there is no vendor data, as iASL does.
+    fn vendor_data(&mut self, at: usize, data: &[u8]) {
+        let offset = self.offset();
+        self.set_u16(at, offset);
+        self.0.extend_from_slice(data);
+    }
+
+    fn finish(mut self, out: &mut Vec<u8>) {
+        let len = self.offset() - 3;
+        self.set_u16(1, len);
+        out.extend_from_slice(&self.0);
+    }
+}
+
+fn small(out: &mut Vec<u8>, name: u8, body: &[u8]) {
+    out.push(name << 3 | body.len() as u8);
+    out.extend_from_slice(body);
+}
+
+impl Resource {
+    /// Append the encoded descriptor to `out`
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Resource::Irq(irq) => {
+                let [lo, hi] = irq.mask.to_le_bytes();
+                match irq.info {
+                    None => small(out, IRQ, &[lo, hi]),
+                    Some(flags) => {
+                        let info = (flags.trigger == Trigger::Edge) as u8
+                            | ((flags.polarity != Polarity::ActiveHigh) as u8) << 3
+                            | (flags.shared as u8) << 4
+                            | (flags.wake_capable as u8) << 5;
+                        small(out, IRQ, &[lo, hi, info]);
+                    }
+                }
+            }
+            Resource::Dma(dma) => {
+                let flags =
+                    dma.transfer.0 & 0x3 | (dma.bus_master as u8) << 2 | (dma.speed.0 & 0x3) << 5;
+                small(out, DMA, &[dma.mask, flags]);
+            }
+            Resource::StartDependent(priority) => match priority {
+                Some(priority) => small(out, START_DEPENDENT, &[*priority]),
+                None => small(out, START_DEPENDENT, &[]),
+            },
+            Resource::EndDependent => small(out, END_DEPENDENT, &[]),
+            Resource::Io(io) => {
+                let [min_lo, min_hi] = io.min.to_le_bytes();
+                let [max_lo, max_hi] = io.max.to_le_bytes();
+                let body = [
+                    io.decode16 as u8,
+                    min_lo,
+                    min_hi,
+                    max_lo,
+                    max_hi,
+                    io.alignment,
+                    io.length,
+                ];
+                small(out, IO, &body);
+            }
+            Resource::FixedIo(io) => {
+                let [lo, hi] = io.base.to_le_bytes();
+                small(out, FIXED_IO, &[lo, hi, io.length]);
+            }
+            Resource::FixedDma(dma) => {
+                let [rl_lo, rl_hi] = dma.request_line.to_le_bytes();
+                let [ch_lo, ch_hi] = dma.channel.to_le_bytes();
+                small(out, FIXED_DMA, &[rl_lo, rl_hi, ch_lo, ch_hi, dma.width]);
+            }
+            Resource::VendorShort(data) => small(out, VENDOR_SHORT, &data[..data.len().min(7)]),
+            Resource::Memory24(mem) => {
+                let mut b = Builder::new(MEMORY24);
+                b.u8(mem.writable as u8);
+                b.u16((mem.min >> 8) as u16);
+                b.u16((mem.max >> 8) as u16);
+                b.u16(mem.alignment as u16);
+                b.u16((mem.length >> 8) as u16);
+                b.finish(out);
+            }
+            Resource::GenericRegister(gas) => {
+                let mut b = Builder::new(GENERIC_REGISTER);
+                b.0.extend_from_slice(&gas.to_bytes());
+                b.finish(out);
+            }
+            Resource::VendorLong(data) => {
+                let mut b = Builder::new(VENDOR_LONG);
+                b.0.extend_from_slice(data);
+                b.finish(out);
+            }
+            Resource::Memory32(mem) => {
+                let mut b = Builder::new(MEMORY32);
+                b.u8(mem.writable as u8);
+                b.u32(mem.min);
+                b.u32(mem.max);
+                b.u32(mem.alignment);
+                b.u32(mem.length);
+                b.finish(out);
+            }
+            Resource::FixedMemory32(mem) => {
+                let mut b = Builder::new(FIXED_MEMORY32);
+                b.u8(mem.writable as u8);
+                b.u32(mem.base);
+                b.u32(mem.length);
+                b.finish(out);
+            }
+            Resource::Address(addr) => {
+                let size = addr.width.bytes();
+                let mut b = Builder::new(addr.width.name());
+                b.u8(addr.resource_type.0);
+                b.u8(addr.flags.bits());
+                b.u8(addr.type_flags);
+                b.uint(addr.granularity, size);
+                b.uint(addr.min, size);
+                b.uint(addr.max, size);
+                b.uint(addr.translation, size);
+                b.uint(addr.length, size);
+                b.source(&addr.source);
+                b.finish(out);
+            }
+            Resource::ExtendedAddress(addr) => {
+                let mut b = Builder::new(EXTENDED_ADDRESS);
+                b.u8(addr.resource_type.0);
+                b.u8(addr.flags.bits());
+                b.u8(addr.type_flags);
+                b.u8(addr.revision);
+                b.u8(0);
+                b.u64(addr.granularity);
+                b.u64(addr.min);
+                b.u64(addr.max);
+                b.u64(addr.translation);
+                b.u64(addr.length);
+                b.u64(addr.attribute);
+                b.finish(out);
+            }
+            Resource::ExtendedIrq(irq) => {
+                let flags = irq.consumer as u8
+                    | ((irq.flags.trigger == Trigger::Edge) as u8) << 1
+                    | ((irq.flags.polarity != Polarity::ActiveHigh) as u8) << 2
+                    | (irq.flags.shared as u8) << 3
+                    | (irq.flags.wake_capable as u8) << 4;
+                let mut b = Builder::new(EXTENDED_IRQ);
+                b.u8(flags);
+                b.u8(irq.interrupts.len() as u8);
+                for &interrupt in &irq.interrupts {
+                    b.u32(interrupt);
+                }
+                b.source(&irq.source);
+                b.finish(out);
+            }
+            Resource::Gpio(gpio) => {
+                let (kind, flags) = match gpio.kind {
+                    GpioKind::Interrupt(flags) => {
+                        let polarity = match flags.polarity {
+                            Polarity::ActiveHigh => 0,
+                            Polarity::ActiveLow => 1,
+                            Polarity::ActiveBoth => 2,
+                        };
+                        let flags = (flags.trigger == Trigger::Edge) as u16
+                            | polarity << 1
+                            | (flags.shared as u16) << 3
+                            | (flags.wake_capable as u16) << 4;
+                        (0, flags)
+                    }
+                    GpioKind::Io {
+                        restriction,
+                        shared,
+                    } => (1, (restriction.0 & 0x3) as u16 | (shared as u16) << 3),
+                };
+                let mut b = Builder::new(GPIO);
+                b.u8(gpio.revision);
+                b.u8(kind);
+                b.u16(gpio.consumer as u16);
+                b.u16(flags);
+                b.u8(gpio.pin_config);
+                b.u16(gpio.drive_strength);
+                b.u16(gpio.debounce_timeout);
+                b.u16(0);
+                b.u8(gpio.source.index);
+                b.u16(0);
+                b.u16(0);
+                b.u16(gpio.vendor_data.len() as u16);
+                let pins = b.offset();
+                b.set_u16(14, pins);
+                b.pins(&gpio.pins);
+                let name = b.offset();
+                b.set_u16(17, name);
+                b.string(&gpio.source.name);
+                b.vendor_data(19, &gpio.vendor_data);
+                b.finish(out);
+            }
+            Resource::PinFunction(pf) => {
+                let mut b = Builder::new(PIN_FUNCTION);
+                b.u8(pf.revision);
+                b.u16(pf.flags);
+                b.u8(pf.pin_config);
+                b.u16(pf.function);
+                b.u16(0);
+                b.u8(pf.source.index);
+                b.u16(0);
+                b.u16(0);
+                b.u16(pf.vendor_data.len() as u16);
+                let pins = b.offset();
+                b.set_u16(9, pins);
+                b.pins(&pf.pins);
+                let name = b.offset();
+                b.set_u16(12, name);
+                b.string(&pf.source.name);
+                b.vendor_data(14, &pf.vendor_data);
+                b.finish(out);
+            }
+            Resource::SerialBus(bus) => {
+                let (bus_type, type_flags) = match bus.kind {
+                    SerialBusKind::I2c {
+                        ten_bit_address, ..
+                    } => (I2C, ten_bit_address as u16),
+                    SerialBusKind::Spi {
+                        three_wire,
+                        active_high,
+                        ..
+                    } => (SPI, three_wire as u16 | (active_high as u16) << 1),
+                    SerialBusKind::Uart { flags, .. } => (UART, flags),
+                    SerialBusKind::Other { bus_type, flags } => (bus_type, flags),
+                };
+                let flags = bus.device_initiated as u8
+                    | (bus.consumer as u8) << 1
+                    | (bus.shared as u8) << 2;
+                let mut b = Builder::new(SERIAL_BUS);
+                b.u8(bus.revision);
+                b.u8(bus.source.index);
+                b.u8(bus_type);
+                b.u8(flags);
+                b.u16(type_flags);
+                b.u8(bus.type_revision);
+                b.u16(0);
+                match bus.kind {
+                    SerialBusKind::I2c { speed, address, .. } => {
+                        b.u32(speed);
+                        b.u16(address);
+                    }
+                    SerialBusKind::Spi {
+                        speed,
+                        data_bit_length,
+                        clock_phase,
+                        clock_polarity,
+                        device_selection,
+                        ..
+                    } => {
+                        b.u32(speed);
+                        b.u8(data_bit_length);
+                        b.u8(clock_phase);
+                        b.u8(clock_polarity);
+                        b.u16(device_selection);
+                    }
+                    SerialBusKind::Uart {
+                        baud_rate,
+                        rx_fifo,
+                        tx_fifo,
+                        parity,
+                        lines,
+                        ..
+                    } => {
+                        b.u32(baud_rate);
+                        b.u16(rx_fifo);
+                        b.u16(tx_fifo);
+                        b.u8(parity);
+                        b.u8(lines);
+                    }
+                    SerialBusKind::Other { .. } => {}
+                }
+                b.0.extend_from_slice(&bus.vendor_data);
+                let type_len = b.offset() - 12;
+                b.set_u16(10, type_len);
+                b.string(&bus.source.name);
+                b.finish(out);
+            }
+            Resource::PinConfig(pc) => {
+                let mut b = Builder::new(PIN_CONFIG);
+                b.u8(pc.revision);
+                b.u16(pc.flags);
+                b.u8(pc.config_type);
+                b.u32(pc.config_value);
+                b.u16(0);
+                b.u8(pc.source.index);
+                b.u16(0);
+                b.u16(0);
+                b.u16(pc.vendor_data.len() as u16);
+                let pins = b.offset();
+                b.set_u16(11, pins);
+                b.pins(&pc.pins);
+                let name = b.offset();
+                b.set_u16(14, name);
+                b.string(&pc.source.name);
+                b.vendor_data(16, &pc.vendor_data);
+                b.finish(out);
+            }
+            Resource::PinGroup(pg) => {
+                let mut b = Builder::new(PIN_GROUP);
+                b.u8(pg.revision);
+                b.u16(pg.flags);
+                b.u16(0);
+                b.u16(0);
+                b.u16(0);
+                b.u16(pg.vendor_data.len() as u16);
+                let pins = b.offset();
+                b.set_u16(6, pins);
+                b.pins(&pg.pins);
+                let label = b.offset();
+                b.set_u16(8, label);
+                b.string(&pg.label);
+                b.vendor_data(10, &pg.vendor_data);
+                b.finish(out);
+            }
+            Resource::PinGroupFunction(pgf) => {
+                let mut b = Builder::new(PIN_GROUP_FUNCTION);
+                b.u8(pgf.revision);
+                b.u16(pgf.flags);
+                b.u16(pgf.function);
+                b.u8(pgf.source.index);
+                b.u16(0);
+                b.u16(0);
+                b.u16(0);
+                b.u16(pgf.vendor_data.len() as u16);
+                let name = b.offset();
+                b.set_u16(9, name);
+                b.string(&pgf.source.name);
+                let label = b.offset();
+                b.set_u16(11, label);
+                b.string(&pgf.label);
+                b.vendor_data(13, &pgf.vendor_data);
+                b.finish(out);
+            }
+            Resource::PinGroupConfig(pgc) => {
+                let mut b = Builder::new(PIN_GROUP_CONFIG);
+                b.u8(pgc.revision);
+                b.u16(pgc.flags);
+                b.u8(pgc.config_type);
+                b.u32(pgc.config_value);
+                b.u8(pgc.source.index);
+                b.u16(0);
+                b.u16(0);
+                b.u16(0);
+                b.u16(pgc.vendor_data.len() as u16);
+                let name = b.offset();
+                b.set_u16(12, name);
+                b.string(&pgc.source.name);
+                let label = b.offset();
+                b.set_u16(14, label);
+                b.string(&pgc.label);
+                b.vendor_data(16, &pgc.vendor_data);
+                b.finish(out);
+            }
+            Resource::Unknown { large, name, data } => {
+                if *large {
+                    let mut b = Builder::new(*name);
+                    b.0.extend_from_slice(data);
+                    b.finish(out);
+                } else {
+                    small(out, *name, &data[..data.len().min(7)]);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn round_trip() {
+        #[rustfmt::skip]
+        let template: &[&[u8]] = &[
+            // IRQNoFlags () {1}
+            b"\x22\x02\x00",
+            // IO (Decode16, 0x0060, 0x0060, 0x01, 0x01)
+            b"\x47\x01\x60\x00\x60\x00\x01\x01",
+            // Memory32Fixed (ReadWrite, 0xFED00000, 0x00000400)
+            b"\x86\x09\x00\x01\x00\x00\xd0\xfe\x00\x04\x00\x00",
+            // QWordMemory (ResourceProducer, PosDecode, MinFixed, MaxFixed, Cacheable,
+            //     ReadWrite, 0x0, 0x100000000, 0x1FFFFFFFF, 0x0, 0x100000000, 0x01,
+            //     "\\_SB.PCI0")
+            b"\x8a\x36\x00\x00\x0c\x03",
+            b"\x00\x00\x00\x00\x00\x00\x00\x00",
+            b"\x00\x00\x00\x00\x01\x00\x00\x00",
+            b"\xff\xff\xff\xff\x01\x00\x00\x00",
+            b"\x00\x00\x00\x00\x00\x00\x00\x00",
+            b"\x00\x00\x00\x00\x01\x00\x00\x00",
+            b"\x01\\_SB.PCI0\x00",
+            // GpioInt (Edge, ActiveLow, ExclusiveAndWake, PullUp, 0x0000, "\\_SB.GPO0",
+            //     0x00, ResourceConsumer) {0x0012}
+            b"\x8c\x20\x00\x01\x00\x01\x00\x13\x00\x01\x00\x00\x00\x00",
+            b"\x17\x00\x00\x19\x00\x23\x00\x00\x00\x12\x00\\_SB.GPO0\x00",
+            // I2cSerialBusV2 (0x0050, ControllerInitiated, 400000, AddressingMode7Bit,
+            //     "\\_SB.I2C1", 0x00, ResourceConsumer, , Exclusive)
+            b"\x8e\x19\x00\x02\x00\x01\x02\x00\x00\x01\x06\x00\x80\x1a\x06\x00",
+            b"\x50\x00\\_SB.I2C1\x00",
+            // EndTag
+            b"\x79\x00",
+        ];
+        let template = template.concat();
+
+        let resources = decode(&template).unwrap();
+        assert_eq!(resources, [
+            Resource::Irq(Irq {
+                mask: 1 << 1,
+                info: None,
+            }),
+            Resource::Io(Io {
+                decode16: true,
+                min: 0x60,
+                max: 0x60,
+                alignment: 1,
+                length: 1,
+            }),
+            Resource::FixedMemory32(FixedMemory32 {
+                writable: true,
+                base: 0xfed0_0000,
+                length: 0x400,
+            }),
+            Resource::Address(Address {
+                width: AddressWidth::QWord,
+                resource_type: AddressResourceType::MEMORY,
+                flags: AddressFlags::MIN_FIXED | AddressFlags::MAX_FIXED,
+                type_flags: 0x03,
+                granularity: 0,
+                min: 0x1_0000_0000,
+                max: 0x1_ffff_ffff,
+                translation: 0,
+                length: 0x1_0000_0000,
+                source: Some(ResourceSource {
+                    index: 1,
+                    name: "\\_SB.PCI0".to_string(),
+                }),
+            }),
+            Resource::Gpio(Gpio {
+                revision: 1,
+                consumer: true,
+                kind: GpioKind::Interrupt(InterruptFlags {
+                    trigger: Trigger::Edge,
+                    polarity: Polarity::ActiveLow,
+                    shared: false,
+                    wake_capable: true,
+                }),
+                pin_config: PinConfig::PULL_UP,
+                drive_strength: 0,
+                debounce_timeout: 0,
+                pins: vec![0x12],
+                source: ResourceSource {
+                    index: 0,
+                    name: "\\_SB.GPO0".to_string(),
+                },
+                vendor_data: Vec::new(),
+            }),
+            Resource::SerialBus(SerialBus {
+                revision: 2,
+                type_revision: 1,
+                device_initiated: false,
+                consumer: true,
+                shared: false,
+                kind: SerialBusKind::I2c {
+                    speed: 400_000,
+                    address: 0x50,
+                    ten_bit_address: false,
+                },
+                source: ResourceSource {
+                    index: 0,
+                    name: "\\_SB.I2C1".to_string(),
+                },
+                vendor_data: Vec::new(),
+            }),
+        ]);
+        assert_eq!(encode(&resources), template);
+    }
+
+    #[test]
+    fn errors() {
+        // Truncated I/O descriptor
+        assert_eq!(
+            decode(b"\x22\x02\x00\x47\x01\x60"),
+            Err(ResourceError {
+                offset: 3,
+                kind: ResourceErrorKind::UnexpectedEnd,
+            })
+        );
+        assert_eq!(
+            decode(b"\x22\x02\x00"),
+            Err(ResourceError {
+                offset: 3,
+                kind: ResourceErrorKind::MissingEndTag,
+            })
+        );
+        // Pin table offset beyond the end of a GPIO descriptor
+        let gpio = [
+            &b"\x8c\x14\x00\x01\x00\x01\x00\x00\x00\x00\x00\x00\x00\x00"[..],
+            b"\xff\x00\x00\x17\x00\x17\x00\x00\x00\x79\x00",
+        ];
+        assert_eq!(
+            decode(&gpio.concat()),
+            Err(ResourceError {
+                offset: 0,
+                kind: ResourceErrorKind::InvalidOffset,
+            })
+        );
+    }
+}
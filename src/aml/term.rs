@@ -0,0 +1,508 @@
+use super::name::{NameSeg, NameString};
+use crate::address::AddressSpace;
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::fmt;
+
+/// Term Object
+///
+/// An element of a term list, such as the body of a definition block, a scope, or a method.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Term {
+    // Namespace modifier objects
+    Alias {
+        source: NameString,
+        alias: NameString,
+    },
+    Name {
+        name: NameString,
+        value: Expr,
+    },
+    Scope {
+        name: NameString,
+        terms: Vec<Term>,
+    },
+
+    // Named objects
+    BankField {
+        region: NameString,
+        bank: NameString,
+        bank_value: Expr,
+        flags: FieldFlags,
+        elements: Vec<FieldElement>,
+    },
+    CreateField {
+        kind: CreateFieldKind,
+        buffer: Expr,
+        index: Expr,
+        name: NameString,
+    },
+    DataRegion {
+        name: NameString,
+        signature: Expr,
+        oem_id: Expr,
+        oem_table_id: Expr,
+    },
+    Device {
+        name: NameString,
+        terms: Vec<Term>,
+    },
+    Event {
+        name: NameString,
+    },
+    External {
+        name: NameString,
+        object_type: u8,
+        arg_count: u8,
+    },
+    Field {
+        region: NameString,
+        flags: FieldFlags,
+        elements: Vec<FieldElement>,
+    },
+    IndexField {
+        index: NameString,
+        data: NameString,
+        flags: FieldFlags,
+        elements: Vec<FieldElement>,
+    },
+    Method {
+        name: NameString,
+        flags: MethodFlags,
+        body: Vec<Term>,
+    },
+    Mutex {
+        name: NameString,
+        sync_level: u8,
+    },
+    OpRegion {
+        name: NameString,
+        space: AddressSpace,
+        offset: Expr,
+        length: Expr,
+    },
+    PowerResource {
+        name: NameString,
+        system_level: u8,
+        resource_order: u16,
+        terms: Vec<Term>,
+    },
+    Processor {
+        name: NameString,
+        id: u8,
+        pblk_addr: u32,
+        pblk_len: u8,
+        terms: Vec<Term>,
+    },
+    ThermalZone {
+        name: NameString,
+        terms: Vec<Term>,
+    },
+
+    // Statements
+    Break,
+    BreakPoint,
+    Continue,
+    Fatal {
+        kind: u8,
+        code: u32,
+        arg: Expr,
+    },
+    If {
+        predicate: Expr,
+        then: Vec<Term>,
+        otherwise: Option<Vec<Term>>,
+    },
+    Noop,
+    Notify {
+        object: SuperName,
+        value: Expr,
+    },
+    Release(SuperName),
+    Reset(SuperName),
+    Return(Expr),
+    Signal(SuperName),
+    Sleep(Expr),
+    Stall(Expr),
+    Unload(SuperName),
+    While {
+        predicate: Expr,
+        body: Vec<Term>,
+    },
+
+    /// An expression evaluated for its side effects
+    Expr(Expr),
+}
+
+/// Expression
+///
+/// Anything which evaluates to a value, found where the AML grammar expects a `TermArg`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Expr {
+    // Data objects
+    /// `Zero`, `One`, or a byte, word, dword, or qword constant
+    Integer(u64),
+    /// `Ones`, whose value depends on the integer width of the definition block
+    Ones,
+    /// `Revision`, the revision of the AML interpreter
+    Revision,
+    String(String),
+    Buffer {
+        size: Box<Expr>,
+        data: Vec<u8>,
+    },
+    /// `Package` or `VarPackage`
+    ///
+    /// Elements are data objects, or [`Expr::Name`] references to named objects.
+    Package {
+        count: Box<Expr>,
+        elements: Vec<Expr>,
+    },
+
+    // Simple names
+    Name(NameString),
+    Arg(u8),
+    Local(u8),
+    Debug,
+
+    /// Invocation of a control method
+    Invoke {
+        name: NameString,
+        args: Vec<Expr>,
+    },
+
+    // Operators
+    Acquire {
+        mutex: SuperName,
+        timeout: u16,
+    },
+    /// Binary operator with an optional target, such as `Add` or `ShiftLeft`
+    Binary {
+        op: BinaryOp,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+        target: Target,
+    },
+    /// Unary operator with an optional target, such as `Not` or `ToInteger`
+    Unary {
+        op: UnaryOp,
+        operand: Box<Expr>,
+        target: Target,
+    },
+    /// Logical operator, such as `LAnd` or `LEqual`
+    Logical {
+        op: LogicalOp,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+    },
+    LNot(Box<Expr>),
+    CondRefOf {
+        source: SuperName,
+        target: Target,
+    },
+    CopyObject {
+        source: Box<Expr>,
+        target: SuperName,
+    },
+    Decrement(SuperName),
+    DerefOf(Box<Expr>),
+    Divide {
+        dividend: Box<Expr>,
+        divisor: Box<Expr>,
+        remainder: Target,
+        quotient: Target,
+    },
+    Increment(SuperName),
+    Index {
+        source: Box<Expr>,
+        index: Box<Expr>,
+        target: Target,
+    },
+    Load {
+        name: NameString,
+        target: Target,
+    },
+    LoadTable {
+        signature: Box<Expr>,
+        oem_id: Box<Expr>,
+        oem_table_id: Box<Expr>,
+        root_path: Box<Expr>,
+        parameter_path: Box<Expr>,
+        parameter_data: Box<Expr>,
+    },
+    Match {
+        package: Box<Expr>,
+        op1: MatchOp,
+        operand1: Box<Expr>,
+        op2: MatchOp,
+        operand2: Box<Expr>,
+        start: Box<Expr>,
+    },
+    Mid {
+        source: Box<Expr>,
+        index: Box<Expr>,
+        length: Box<Expr>,
+        target: Target,
+    },
+    ObjectType(SuperName),
+    RefOf(SuperName),
+    SizeOf(SuperName),
+    Store {
+        source: Box<Expr>,
+        target: SuperName,
+    },
+    Timer,
+    ToString {
+        source: Box<Expr>,
+        length: Box<Expr>,
+        target: Target,
+    },
+    Wait {
+        event: SuperName,
+        timeout: Box<Expr>,
+    },
+}
+
+/// Super Name
+///
+/// Something which can be stored to or referenced.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SuperName {
+    Name(NameString),
+    Arg(u8),
+    Local(u8),
+    Debug,
+    /// A `RefOf`, `DerefOf`, `Index`, or method invocation which yields a reference
+    Reference(Box<Expr>),
+}
+
+/// Target of an operator, or `None` if the result is discarded
+pub type Target = Option<SuperName>;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum BinaryOp {
+    Add,
+    And,
+    Concat,
+    ConcatRes,
+    Mod,
+    Multiply,
+    NAnd,
+    NOr,
+    Or,
+    ShiftLeft,
+    ShiftRight,
+    Subtract,
+    XOr,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum UnaryOp {
+    FindSetLeftBit,
+    FindSetRightBit,
+    FromBcd,
+    Not,
+    ToBcd,
+    ToBuffer,
+    ToDecimalString,
+    ToHexString,
+    ToInteger,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum LogicalOp {
+    LAnd,
+    LOr,
+    LEqual,
+    LGreater,
+    LLess,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CreateFieldKind {
+    Bit,
+    Byte,
+    Word,
+    DWord,
+    QWord,
+    /// `CreateField`, with the width in bits given by the expression
+    Bits(Box<Expr>),
+}
+
+/// Comparison performed by `Match`
+#[repr(transparent)]
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
+pub struct MatchOp(pub u8);
+
+impl MatchOp {
+    pub const MTR: MatchOp = MatchOp(0);
+    pub const MEQ: MatchOp = MatchOp(1);
+    pub const MLE: MatchOp = MatchOp(2);
+    pub const MLT: MatchOp = MatchOp(3);
+    pub const MGE: MatchOp = MatchOp(4);
+    pub const MGT: MatchOp = MatchOp(5);
+}
+
+impl fmt::Debug for MatchOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match *self {
+            Self::MTR => "MTR",
+            Self::MEQ => "MEQ",
+            Self::MLE => "MLE",
+            Self::MLT => "MLT",
+            Self::MGE => "MGE",
+            Self::MGT => "MGT",
+            _ => return write!(f, "MatchOp({})", self.0),
+        };
+        f.write_str(name)
+    }
+}
+
+/// Method Flags
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct MethodFlags(pub u8);
+
+impl MethodFlags {
+    /// Returns the number of arguments taken by the method, from 0 to 7
+    #[inline]
+    pub fn arg_count(self) -> u8 {
+        self.0 & 0x7
+    }
+
+    /// Returns `true` if the method is serialized
+    #[inline]
+    pub fn is_serialized(self) -> bool {
+        self.0 & (1 << 3) != 0
+    }
+
+    /// Returns the synchronization level of a serialized method
+    #[inline]
+    pub fn sync_level(self) -> u8 {
+        self.0 >> 4
+    }
+}
+
+/// Field Flags
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct FieldFlags(pub u8);
+
+impl FieldFlags {
+    #[inline]
+    pub fn access_type(self) -> AccessType {
+        AccessType(self.0 & 0xf)
+    }
+
+    /// Returns `true` if the Global Lock must be held while accessing the field
+    #[inline]
+    pub fn lock_rule(self) -> bool {
+        self.0 & (1 << 4) != 0
+    }
+
+    #[inline]
+    pub fn update_rule(self) -> UpdateRule {
+        UpdateRule((self.0 >> 5) & 0x3)
+    }
+}
+
+/// Field Access Type
+#[repr(transparent)]
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
+pub struct AccessType(pub u8);
+
+impl AccessType {
+    pub const ANY: AccessType = AccessType(0);
+    pub const BYTE: AccessType = AccessType(1);
+    pub const WORD: AccessType = AccessType(2);
+    pub const DWORD: AccessType = AccessType(3);
+    pub const QWORD: AccessType = AccessType(4);
+    pub const BUFFER: AccessType = AccessType(5);
+
+    /// Returns the width of each access in bytes, or `None` for `AnyAcc` and `BufferAcc`
+    #[inline]
+    pub fn bytes(self) -> Option<usize> {
+        match self {
+            Self::BYTE => Some(1),
+            Self::WORD => Some(2),
+            Self::DWORD => Some(4),
+            Self::QWORD => Some(8),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Debug for AccessType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match *self {
+            Self::ANY => "AnyAcc",
+            Self::BYTE => "ByteAcc",
+            Self::WORD => "WordAcc",
+            Self::DWORD => "DWordAcc",
+            Self::QWORD => "QWordAcc",
+            Self::BUFFER => "BufferAcc",
+            _ => return write!(f, "AccessType({})", self.0),
+        };
+        f.write_str(name)
+    }
+}
+
+/// Field Update Rule
+///
+/// Decides the value of bits outside of a field when it is written.
+#[repr(transparent)]
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
+pub struct UpdateRule(pub u8);
+
+impl UpdateRule {
+    pub const PRESERVE: UpdateRule = UpdateRule(0);
+    pub const WRITE_AS_ONES: UpdateRule = UpdateRule(1);
+    pub const WRITE_AS_ZEROS: UpdateRule = UpdateRule(2);
+}
+
+impl fmt::Debug for UpdateRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match *self {
+            Self::PRESERVE => "Preserve",
+            Self::WRITE_AS_ONES => "WriteAsOnes",
+            Self::WRITE_AS_ZEROS => "WriteAsZeros",
+            _ => return write!(f, "UpdateRule({})", self.0),
+        };
+        f.write_str(name)
+    }
+}
+
+/// Element of a field list
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FieldElement {
+    /// A named field unit of `bits` bits
+    Named { name: NameSeg, bits: u32 },
+    /// `bits` unnamed bits, skipped over
+    Reserved { bits: u32 },
+    /// Change the access type and attributes of the following field units
+    ///
+    /// `attrib_kind` is taken from the upper two bits of the access type byte, and decides
+    /// whether `attrib` is an access attribute (0), or the length of a `AttribBytes` (1),
+    /// `AttribRawBytes` (2) or `AttribRawProcessBytes` (3) access.
+    Access {
+        access_type: AccessType,
+        attrib_kind: u8,
+        attrib: u8,
+    },
+    /// Change the access type and attributes of the following field units, with an access
+    /// length for attributes which need one
+    ExtendedAccess {
+        access_type: AccessType,
+        attrib: u8,
+        length: u8,
+    },
+    /// Set the connection resource used by the following field units
+    Connection(Connection),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Connection {
+    /// A named buffer containing a resource template
+    Name(NameString),
+    /// A resource template
+    Buffer(Expr),
+}
@@ -0,0 +1,351 @@
+use super::{namespace::NodeId, term::MatchOp};
+use crate::sdt::dsdt::IntegerWidth;
+use alloc::{boxed::Box, format, string::String, vec::Vec};
+use core::{cmp::Ordering, fmt};
+
+/// Largest buffer, string or package the interpreter will create
+///
+/// This stops buggy firmware from exhausting the heap with a single `Buffer` or `VarPackage`.
+pub const MAX_OBJECT_SIZE: usize = 1 << 20;
+
+/// AML Value
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum Value {
+    #[default]
+    Uninitialized,
+    Integer(u64),
+    String(String),
+    Buffer(Vec<u8>),
+    Package(Vec<Value>),
+    Reference(Reference),
+}
+
+/// Reference to a storage location or named object
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Reference {
+    /// A named object
+    Named(NodeId),
+    /// A local variable of the method invocation numbered `frame`
+    Local { frame: usize, index: u8 },
+    /// An argument of the method invocation numbered `frame`
+    Arg { frame: usize, index: u8 },
+    /// An element of the package, buffer or string at `base`
+    Index { base: Box<Reference>, index: usize },
+    /// A temporary value which is not stored anywhere, such as the result of an expression
+    Temporary(Box<Value>),
+    /// The debug object
+    Debug,
+}
+
+/// Object type, as returned by `ObjectType`
+#[repr(transparent)]
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
+pub struct ObjectType(pub u8);
+
+impl ObjectType {
+    pub const UNINITIALIZED: ObjectType = ObjectType(0);
+    pub const INTEGER: ObjectType = ObjectType(1);
+    pub const STRING: ObjectType = ObjectType(2);
+    pub const BUFFER: ObjectType = ObjectType(3);
+    pub const PACKAGE: ObjectType = ObjectType(4);
+    pub const FIELD_UNIT: ObjectType = ObjectType(5);
+    pub const DEVICE: ObjectType = ObjectType(6);
+    pub const EVENT: ObjectType = ObjectType(7);
+    pub const METHOD: ObjectType = ObjectType(8);
+    pub const MUTEX: ObjectType = ObjectType(9);
+    pub const OPERATION_REGION: ObjectType = ObjectType(10);
+    pub const POWER_RESOURCE: ObjectType = ObjectType(11);
+    pub const PROCESSOR: ObjectType = ObjectType(12);
+    pub const THERMAL_ZONE: ObjectType = ObjectType(13);
+    pub const BUFFER_FIELD: ObjectType = ObjectType(14);
+    pub const DDB_HANDLE: ObjectType = ObjectType(15);
+    pub const DEBUG: ObjectType = ObjectType(16);
+}
+
+impl fmt::Debug for ObjectType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match *self {
+            Self::UNINITIALIZED => "Uninitialized",
+            Self::INTEGER => "Integer",
+            Self::STRING => "String",
+            Self::BUFFER => "Buffer",
+            Self::PACKAGE => "Package",
+            Self::FIELD_UNIT => "FieldUnit",
+            Self::DEVICE => "Device",
+            Self::EVENT => "Event",
+            Self::METHOD => "Method",
+            Self::MUTEX => "Mutex",
+            Self::OPERATION_REGION => "OperationRegion",
+            Self::POWER_RESOURCE => "PowerResource",
+            Self::PROCESSOR => "Processor",
+            Self::THERMAL_ZONE => "ThermalZone",
+            Self::BUFFER_FIELD => "BufferField",
+            Self::DDB_HANDLE => "DDBHandle",
+            Self::DEBUG => "Debug",
+            _ => return write!(f, "ObjectType({})", self.0),
+        };
+        f.write_str(name)
+    }
+}
+
+/// Error returned when a value cannot be converted to the required type
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ConversionError {
+    pub from: ObjectType,
+    pub to: ObjectType,
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cannot convert {:?} to {:?}", self.from, self.to)
+    }
+}
+
+impl Value {
+    /// Returns the AML boolean for `value`, `Ones` for true and `Zero` for false
+    #[inline]
+    pub fn from_bool(value: bool, width: IntegerWidth) -> Value {
+        Value::Integer(if value { width.max() } else { 0 })
+    }
+
+    /// Returns the type of this value
+    ///
+    /// References are reported as such, without looking at the object they refer to.
+    pub fn object_type(&self) -> ObjectType {
+        match self {
+            Self::Uninitialized => ObjectType::UNINITIALIZED,
+            Self::Integer(_) => ObjectType::INTEGER,
+            Self::String(_) => ObjectType::STRING,
+            Self::Buffer(_) => ObjectType::BUFFER,
+            Self::Package(_) => ObjectType::PACKAGE,
+            Self::Reference(Reference::Debug) => ObjectType::DEBUG,
+            // References have no type code of their own, `ObjectType` reports the type of
+            // the object they refer to.
+            Self::Reference(_) => ObjectType::UNINITIALIZED,
+        }
+    }
+
+    fn conversion_error(&self, to: ObjectType) -> ConversionError {
+        ConversionError {
+            from: self.object_type(),
+            to,
+        }
+    }
+
+    #[inline]
+    pub fn as_integer(&self) -> Option<u64> {
+        match *self {
+            Self::Integer(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_buffer(&self) -> Option<&[u8]> {
+        match self {
+            Self::Buffer(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_package(&self) -> Option<&[Value]> {
+        match self {
+            Self::Package(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Convert this value to an integer, following the implicit conversion rules
+    ///
+    /// Strings are parsed as hexadecimal up to the first non-hex character, and the first
+    /// bytes of buffers are read in little-endian order.
+    pub fn to_integer(&self, width: IntegerWidth) -> Result<u64, ConversionError> {
+        let value = match self {
+            Self::Integer(value) => *value,
+            Self::String(s) => s
+                .trim_start()
+                .bytes()
+                .map_while(|c| (c as char).to_digit(16))
+                .take(width.bits() as usize / 4)
+                .fold(0, |acc, digit| acc << 4 | digit as u64),
+            Self::Buffer(bytes) => bytes
+                .iter()
+                .take(width.bits() as usize / 8)
+                .rev()
+                .fold(0, |acc, &byte| acc << 8 | byte as u64),
+            _ => return Err(self.conversion_error(ObjectType::INTEGER)),
+        };
+        Ok(width.truncate(value))
+    }
+
+    /// Convert this value to a buffer, following the implicit conversion rules
+    ///
+    /// Integers are converted to 4 or 8 little-endian bytes, and strings are copied
+    /// including their null terminator.
+    pub fn to_buffer(&self, width: IntegerWidth) -> Result<Vec<u8>, ConversionError> {
+        match self {
+            Self::Integer(value) => Ok(value.to_le_bytes()[..width.bits() as usize / 8].to_vec()),
+            Self::String(s) => {
+                let mut bytes = s.as_bytes().to_vec();
+                bytes.push(0);
+                Ok(bytes)
+            }
+            Self::Buffer(bytes) => Ok(bytes.clone()),
+            _ => Err(self.conversion_error(ObjectType::BUFFER)),
+        }
+    }
+
+    /// Convert this value to a string, following the implicit conversion rules
+    ///
+    /// Integers are converted to hexadecimal, and buffers to a list of hexadecimal bytes.
+    pub fn to_string(&self, width: IntegerWidth) -> Result<String, ConversionError> {
+        match self {
+            Self::Integer(value) => Ok(match width {
+                IntegerWidth::Bits32 => format!("{value:08X}"),
+                IntegerWidth::Bits64 => format!("{value:016X}"),
+            }),
+            Self::String(s) => Ok(s.clone()),
+            Self::Buffer(bytes) => {
+                let mut s = String::new();
+                for (i, byte) in bytes.iter().enumerate() {
+                    if i != 0 {
+                        s.push(' ');
+                    }
+                    s.push_str(&format!("{byte:02X}"));
+                }
+                Ok(s)
+            }
+            _ => Err(self.conversion_error(ObjectType::STRING)),
+        }
+    }
+
+    /// Convert `other` to the type of this value, for use with `Store` to a named object
+    ///
+    /// Buffers keep their length, and are truncated or padded with zeros. Values which are
+    /// not integers, strings or buffers are replaced by `other`.
+    pub fn convert_to_type_of(
+        &self,
+        other: Value,
+        width: IntegerWidth,
+    ) -> Result<Value, ConversionError> {
+        Ok(match self {
+            Self::Integer(_) => Self::Integer(other.to_integer(width)?),
+            Self::String(_) => Self::String(other.to_string(width)?),
+            Self::Buffer(bytes) => {
+                let mut new = other.to_buffer(width)?;
+                new.resize(bytes.len(), 0);
+                Self::Buffer(new)
+            }
+            _ => other,
+        })
+    }
+
+    /// Compare two values as `LEqual`, `LGreater` and `LLess` do
+    ///
+    /// `other` is converted to the type of `self`, which must be an integer, string or
+    /// buffer.
+    pub fn compare(&self, other: &Value, width: IntegerWidth) -> Result<Ordering, ConversionError> {
+        match self {
+            Self::Integer(lhs) => Ok(lhs.cmp(&other.to_integer(width)?)),
+            Self::String(lhs) => Ok(lhs.as_str().cmp(other.to_string(width)?.as_str())),
+            Self::Buffer(lhs) => Ok(lhs.as_slice().cmp(other.to_buffer(width)?.as_slice())),
+            _ => Err(self.conversion_error(ObjectType::INTEGER)),
+        }
+    }
+
+    /// Returns `true` if the `Match` comparison `op` holds between this value and `other`
+    pub(crate) fn matches(&self, op: MatchOp, other: &Value, width: IntegerWidth) -> bool {
+        let ordering = match op {
+            MatchOp::MTR => return true,
+            _ => match self.compare(other, width) {
+                Ok(ordering) => ordering,
+                Err(_) => return false,
+            },
+        };
+        match op {
+            MatchOp::MEQ => ordering == Ordering::Equal,
+            MatchOp::MLE => ordering != Ordering::Greater,
+            MatchOp::MLT => ordering == Ordering::Less,
+            MatchOp::MGE => ordering != Ordering::Less,
+            MatchOp::MGT => ordering == Ordering::Greater,
+            _ => false,
+        }
+    }
+}
+
+impl From<u64> for Value {
+    fn from(value: u64) -> Self {
+        Self::Integer(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Self::String(String::from(value))
+    }
+}
+
+impl From<Vec<u8>> for Value {
+    fn from(value: Vec<u8>) -> Self {
+        Self::Buffer(value)
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    fn from(value: Vec<Value>) -> Self {
+        Self::Package(value)
+    }
+}
+
+/// Read `bit_length` bits starting at `bit_offset` from `bytes`
+///
+/// Bits past the end of `bytes` are read as zero.
+pub(crate) fn read_bits(bytes: &[u8], bit_offset: u64, bit_length: u64) -> Vec<u8> {
+    let mut out = alloc::vec![0; bit_length.div_ceil(8) as usize];
+    for i in 0..bit_length {
+        let src = bit_offset + i;
+        let bit = bytes
+            .get((src / 8) as usize)
+            .map_or(0, |byte| (byte >> (src % 8)) & 1);
+        out[(i / 8) as usize] |= bit << (i % 8);
+    }
+    out
+}
+
+/// Write the low `bit_length` bits of `value` to `bytes` starting at `bit_offset`
+///
+/// Bits past the end of `bytes` are ignored, and missing bits of `value` are written as zero.
+pub(crate) fn write_bits(bytes: &mut [u8], bit_offset: u64, bit_length: u64, value: &[u8]) {
+    for i in 0..bit_length {
+        let dst = bit_offset + i;
+        let Some(byte) = bytes.get_mut((dst / 8) as usize) else {
+            break;
+        };
+        let bit = value
+            .get((i / 8) as usize)
+            .map_or(0, |byte| (byte >> (i % 8)) & 1);
+        *byte = (*byte & !(1 << (dst % 8))) | (bit << (dst % 8));
+    }
+}
+
+/// Convert the bytes of a field to a value, an integer if it fits and a buffer otherwise
+pub(crate) fn field_value(bytes: Vec<u8>, bit_length: u64, width: IntegerWidth) -> Value {
+    if bit_length <= width.bits() as u64 {
+        let value = bytes
+            .iter()
+            .rev()
+            .fold(0, |acc, &byte| acc << 8 | byte as u64);
+        Value::Integer(value)
+    } else {
+        Value::Buffer(bytes)
+    }
+}
@@ -0,0 +1,293 @@
+//! RSDP Discovery
+//!
+//! Helpers for locating the [`Rsdp`] on legacy BIOS systems and on UEFI systems. Both return
+//! the physical address of a validated RSDP, which can then be passed to
+//! [`RootTable::try_new()`](crate::RootTable::try_new) once mapped.
+
+use crate::{sdt::map_nonnull, sdt::Bridge, sdt::Mapped, Error, Rsdp, RsdpError};
+use core::ptr;
+
+/// Physical address of the word in the BIOS Data Area holding the EBDA segment
+const EBDA_SEGMENT_PTR: usize = 0x40e;
+
+/// Number of bytes at the start of the EBDA which are searched
+const EBDA_SEARCH_LEN: usize = 1024;
+
+/// Physical address range of the BIOS read-only memory area
+const BIOS_AREA_START: usize = 0xe0000;
+const BIOS_AREA_END: usize = 0x100000;
+
+/// The RSDP is always found on a 16-byte boundary when searching BIOS memory.
+const RSDP_ALIGN: usize = 16;
+
+/// Largest RSDP `length` accepted, so that a corrupt length is not mapped
+const MAX_RSDP_LEN: usize = 4096;
+
+/// EFI GUID
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Guid {
+    pub data1: u32,
+    pub data2: u16,
+    pub data3: u16,
+    pub data4: [u8; 8],
+}
+
+impl Guid {
+    /// `EFI_ACPI_TABLE_GUID`, identifying an ACPI 1.0 RSDP
+    pub const ACPI_10_TABLE: Guid = Guid {
+        data1: 0xeb9d2d30,
+        data2: 0x2d88,
+        data3: 0x11d3,
+        data4: [0x9a, 0x16, 0x00, 0x90, 0x27, 0x3f, 0xc1, 0x4d],
+    };
+
+    /// `EFI_ACPI_20_TABLE_GUID`, identifying an ACPI 2.0 or later RSDP
+    pub const ACPI_20_TABLE: Guid = Guid {
+        data1: 0x8868e871,
+        data2: 0xe4f1,
+        data3: 0x11d3,
+        data4: [0xbc, 0x22, 0x00, 0x80, 0xc7, 0x3c, 0x88, 0x81],
+    };
+}
+
+/// EFI Configuration Table
+///
+/// An entry in the `ConfigurationTable` array of the EFI System Table.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct EfiConfigurationTable {
+    pub vendor_guid: Guid,
+    pub vendor_table: usize,
+}
+
+/// Search the legacy BIOS memory areas for the RSDP
+///
+/// The first KiB of the Extended BIOS Data Area is searched first, followed by the BIOS
+/// read-only memory area between `0xe0000` and `0xfffff`. Returns the physical address of
+/// the first valid RSDP found on a 16-byte boundary, or `None` if there is none.
+pub fn find_rsdp_bios<B: Bridge>(bridge: B) -> Result<Option<usize>, Error> {
+    let ebda_segment = map_bytes(EBDA_SEGMENT_PTR, 2, bridge)?;
+    let ebda_base = (u16::from_le_bytes([ebda_segment[0], ebda_segment[1]]) as usize) << 4;
+    drop(ebda_segment);
+
+    if ebda_base != 0 {
+        let ebda = map_bytes(ebda_base, EBDA_SEARCH_LEN, bridge)?;
+        if let Some(phys) = scan(&ebda, ebda_base) {
+            return Ok(Some(phys));
+        }
+    }
+
+    let bios_area = map_bytes(BIOS_AREA_START, BIOS_AREA_END - BIOS_AREA_START, bridge)?;
+    Ok(scan(&bios_area, BIOS_AREA_START))
+}
+
+/// Find the RSDP in the EFI configuration tables
+///
+/// An RSDP identified by [`Guid::ACPI_20_TABLE`] is preferred over one identified by
+/// [`Guid::ACPI_10_TABLE`]. Returns the physical address of the first valid RSDP found, or
+/// `None` if there is none.
+pub fn find_rsdp_efi<B: Bridge>(
+    config_tables: &[EfiConfigurationTable],
+    bridge: B,
+) -> Result<Option<usize>, Error> {
+    for guid in [Guid::ACPI_20_TABLE, Guid::ACPI_10_TABLE] {
+        for table in config_tables
+            .iter()
+            .filter(|table| table.vendor_guid == guid)
+        {
+            if is_valid_at(table.vendor_table, bridge)? {
+                return Ok(Some(table.vendor_table));
+            }
+        }
+    }
+    Ok(None)
+}
+
+fn map_bytes<B: Bridge>(phys: usize, len: usize, bridge: B) -> Result<Mapped<[u8], B>, Error> {
+    let virt = map_nonnull(phys, len, bridge)?;
+    let ptr = ptr::with_exposed_provenance::<u8>(virt);
+    Ok(Mapped::new(ptr::slice_from_raw_parts(ptr, len), bridge))
+}
+
+/// Returns the address of the first valid RSDP in `bytes`, which is mapped at `base`
+fn scan(bytes: &[u8], base: usize) -> Option<usize> {
+    (0..bytes.len())
+        .step_by(RSDP_ALIGN)
+        .find(|&offset| is_valid(&bytes[offset..], base + offset))
+        .map(|offset| base + offset)
+}
+
+/// Returns the number of bytes needed to validate the RSDP at the start of `bytes`
+fn required_len(bytes: &[u8]) -> Option<usize> {
+    if !bytes.starts_with(&Rsdp::SIGNATURE) {
+        return None;
+    }
+    match *bytes.get(15)? {
+        0 | 1 => Some(Rsdp::V1_LENGTH),
+        _ => {
+            let length = u32::from_le_bytes(bytes.get(20..24)?.try_into().unwrap());
+            Some((length as usize).max(Rsdp::V2_LENGTH))
+        }
+    }
+}
+
+/// Returns `true` if `bytes` starts with a valid RSDP at physical address `phys`
+fn is_valid(bytes: &[u8], phys: usize) -> bool {
+    let Some(len) = required_len(bytes) else {
+        return false;
+    };
+    if len > MAX_RSDP_LEN {
+        let error = RsdpError::Length(len as u32);
+        log::warn!("acpi: ignoring RSDP at {phys:#x}: {error}");
+        return false;
+    }
+    if bytes.len() < len {
+        log::warn!("acpi: ignoring truncated RSDP at {phys:#x}");
+        return false;
+    }
+    // SAFETY: `bytes` covers every byte read by `validate()`.
+    match unsafe { Rsdp::validate(bytes.as_ptr().cast()) } {
+        Ok(()) => true,
+        Err(error) => {
+            log::warn!("acpi: ignoring RSDP at {phys:#x}: {error}");
+            false
+        }
+    }
+}
+
+/// Returns `true` if a valid RSDP is found at physical address `phys`
+fn is_valid_at<B: Bridge>(phys: usize, bridge: B) -> Result<bool, Error> {
+    let bytes = map_bytes(phys, Rsdp::V2_LENGTH, bridge)?;
+    match required_len(&bytes) {
+        None => Ok(false),
+        // Overlong RSDPs are rejected by `is_valid()` without mapping them.
+        Some(len) if len <= bytes.len() || len > MAX_RSDP_LEN => Ok(is_valid(&bytes, phys)),
+        Some(len) => {
+            drop(bytes);
+            Ok(is_valid(&map_bytes(phys, len, bridge)?, phys))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use core::cell::RefCell;
+    use std::{vec, vec::Vec};
+
+    /// Maps physical addresses to offsets into `memory`, recording the size of each mapping
+    #[derive(Clone, Copy)]
+    struct MemoryBridge<'a> {
+        memory: &'a [u8],
+        sizes: &'a RefCell<Vec<usize>>,
+    }
+
+    impl Bridge for MemoryBridge<'_> {
+        fn map(&self, phys: usize, size: usize) -> usize {
+            self.sizes.borrow_mut().push(size);
+            match self.memory.get(phys..phys.saturating_add(size)) {
+                Some(bytes) => bytes.as_ptr().expose_provenance(),
+                None => 0,
+            }
+        }
+
+        fn remap(&self, _virt: usize, _new_size: usize) -> usize {
+            unimplemented!()
+        }
+
+        fn unmap(&self, _virt: usize) {}
+    }
+
+    /// Returns an RSDP with valid checksums, of `length` bytes for revision 2 and above
+    fn rsdp(revision: u8, length: u32) -> Vec<u8> {
+        let mut bytes = Vec::from(Rsdp::SIGNATURE);
+        bytes.resize(Rsdp::V1_LENGTH, 0);
+        bytes[15] = revision;
+        if revision >= 2 {
+            bytes.extend_from_slice(&length.to_le_bytes());
+            bytes.resize(length as usize, 0);
+        }
+        let sum = |bytes: &[u8]| bytes.iter().fold(0u8, |sum, &b| sum.wrapping_add(b));
+        bytes[8] = sum(&bytes[..Rsdp::V1_LENGTH]).wrapping_neg();
+        if revision >= 2 {
+            bytes[32] = sum(&bytes).wrapping_neg();
+        }
+        bytes
+    }
+
+    #[test]
+    fn lengths() {
+        assert_eq!(required_len(&rsdp(0, 0)), Some(Rsdp::V1_LENGTH));
+        assert_eq!(required_len(&rsdp(2, 36)), Some(36));
+        assert_eq!(required_len(&rsdp(2, 48)), Some(48));
+        let mut bytes = rsdp(2, 36);
+        bytes[20..24].copy_from_slice(&20u32.to_le_bytes());
+        assert_eq!(required_len(&bytes), Some(Rsdp::V2_LENGTH));
+        bytes[20..24].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(required_len(&bytes), Some(u32::MAX as usize));
+        assert_eq!(required_len(&bytes[..22]), None);
+        assert_eq!(required_len(&bytes[..15]), None);
+        assert_eq!(required_len(b"RSD PTR_"), None);
+    }
+
+    #[test]
+    fn bios_scan() {
+        let mut area = vec![0; 0x100];
+        // Not on a 16-byte boundary
+        area[0x08..0x1c].copy_from_slice(&rsdp(0, 0));
+        // Bad checksum
+        let mut bytes = rsdp(0, 0);
+        bytes[9] = 1;
+        area[0x20..0x34].copy_from_slice(&bytes);
+        assert_eq!(scan(&area, 0xe0000), None);
+        area[0x80..0xa4].copy_from_slice(&rsdp(2, 36));
+        assert_eq!(scan(&area, 0xe0000), Some(0xe0080));
+
+        let mut memory = vec![0; BIOS_AREA_END];
+        memory[0xf0010..0xf0024].copy_from_slice(&rsdp(0, 0));
+        let sizes = RefCell::default();
+        let bridge = MemoryBridge {
+            memory: &memory,
+            sizes: &sizes,
+        };
+        assert_eq!(find_rsdp_bios(bridge), Ok(Some(0xf0010)));
+
+        // The EBDA is searched first.
+        memory[EBDA_SEGMENT_PTR..EBDA_SEGMENT_PTR + 2].copy_from_slice(&0x9fc0u16.to_le_bytes());
+        memory[0x9fc30..0x9fc44].copy_from_slice(&rsdp(0, 0));
+        let bridge = MemoryBridge {
+            memory: &memory,
+            sizes: &sizes,
+        };
+        assert_eq!(find_rsdp_bios(bridge), Ok(Some(0x9fc30)));
+    }
+
+    #[test]
+    fn efi_lengths() {
+        let mut memory = vec![0; 0x3000];
+        memory[0x1000..0x1030].copy_from_slice(&rsdp(2, 48));
+        let mut overlong = rsdp(2, 36);
+        overlong[20..24].copy_from_slice(&u32::MAX.to_le_bytes());
+        memory[0x2000..0x2024].copy_from_slice(&overlong);
+        let sizes = RefCell::default();
+        let bridge = MemoryBridge {
+            memory: &memory,
+            sizes: &sizes,
+        };
+        let table = |vendor_table| EfiConfigurationTable {
+            vendor_guid: Guid::ACPI_20_TABLE,
+            vendor_table,
+        };
+
+        // The RSDP is mapped again to cover its length.
+        assert_eq!(find_rsdp_efi(&[table(0x1000)], bridge), Ok(Some(0x1000)));
+        assert_eq!(sizes.take(), [Rsdp::V2_LENGTH, 48]);
+
+        // An overlong RSDP is rejected without mapping its length.
+        assert_eq!(find_rsdp_efi(&[table(0x2000)], bridge), Ok(None));
+        assert_eq!(sizes.take(), [Rsdp::V2_LENGTH]);
+    }
+}
@@ -0,0 +1,92 @@
+use crate::{address::AddressSpace, sdt::Signature, GenericAddress, RsdpError};
+use core::{fmt, str::Utf8Error};
+
+/// Errors returned when firmware-provided structures cannot be used
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The RSDP or the root table it points to is invalid.
+    Rsdp(RsdpError),
+    /// A table does not have the expected signature.
+    Signature {
+        expected: Signature,
+        found: Signature,
+    },
+    /// The `length` of a table is smaller than its fixed-size portion.
+    TooShort {
+        signature: Signature,
+        length: usize,
+        min: usize,
+    },
+    /// The `length` of a table does not match the size given by its contents.
+    LengthMismatch {
+        signature: Signature,
+        length: usize,
+        expected: usize,
+    },
+    /// A structure within a table extends past the end of the table, or past the end of
+    /// the structure containing it.
+    Truncated { offset: usize, len: usize },
+    /// An offset within a table points outside of the table.
+    OutOfBounds { offset: usize },
+    /// A string within a table is not valid UTF-8.
+    Utf8(Utf8Error),
+    /// A structure is not correctly aligned in memory.
+    Misaligned { phys: usize },
+    /// A physical memory range could not be mapped.
+    Map { phys: usize, size: usize },
+    /// An existing mapping could not be remapped with a new size.
+    Remap { virt: usize, size: usize },
+    /// Accesses to an address space are not supported.
+    AddressSpace(AddressSpace),
+    /// A register has an invalid bit width, bit offset or access size.
+    Register(GenericAddress),
+    /// The platform does not support the requested operation.
+    NotSupported,
+}
+
+impl From<RsdpError> for Error {
+    fn from(error: RsdpError) -> Self {
+        Self::Rsdp(error)
+    }
+}
+
+impl From<Utf8Error> for Error {
+    fn from(error: Utf8Error) -> Self {
+        Self::Utf8(error)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rsdp(error) => fmt::Display::fmt(error, f),
+            Self::Signature { expected, found } => {
+                write!(f, "expected table signature {expected}, found {found}")
+            }
+            Self::TooShort {
+                signature,
+                length,
+                min,
+            } => write!(f, "{signature} is too short ({length} < {min} bytes)"),
+            Self::LengthMismatch {
+                signature,
+                length,
+                expected,
+            } => write!(f, "{signature} is {length} bytes, expected {expected}"),
+            Self::Truncated { offset, len } => {
+                write!(f, "structure at {offset:#x} ({len} bytes) is truncated")
+            }
+            Self::OutOfBounds { offset } => write!(f, "offset {offset:#x} is out of bounds"),
+            Self::Utf8(error) => fmt::Display::fmt(error, f),
+            Self::Misaligned { phys } => write!(f, "structure at {phys:#x} is misaligned"),
+            Self::Map { phys, size } => write!(f, "failed to map {size:#x} bytes at {phys:#x}"),
+            Self::Remap { virt, size } => {
+                write!(f, "failed to remap {virt:#x} with {size:#x} bytes")
+            }
+            Self::AddressSpace(space) => write!(f, "unsupported address space {space:?}"),
+            Self::Register(gas) => write!(f, "invalid register {gas:?}"),
+            Self::NotSupported => f.write_str("operation not supported by the platform"),
+        }
+    }
+}
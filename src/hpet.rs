@@ -0,0 +1,252 @@
+//! High Precision Event Timer
+
+use crate::{
+    address::AddressSpace,
+    sdt::{
+        hpet::{EventTimerBlockId, Hpet},
+        map_nonnull, Bridge,
+    },
+    Error,
+};
+use core::{mem::align_of, ptr};
+
+const CAPABILITIES: usize = 0x000;
+const CONFIGURATION: usize = 0x010;
+const MAIN_COUNTER: usize = 0x0f0;
+
+const ENABLE_CNF: u64 = 1 << 0;
+
+/// Event timer block of a High Precision Event Timer
+///
+/// Maps the registers of the block described by an [`Hpet`] table, and unmaps them when
+/// dropped. Only the main counter is managed; the comparators are left to the caller.
+#[derive(Debug)]
+pub struct EventTimerBlock<B: Bridge> {
+    regs: *mut u64,
+    period: u32,
+    bridge: B,
+}
+
+impl<B: Bridge> EventTimerBlock<B> {
+    /// Size of the register block, in bytes
+    pub const SIZE: usize = 0x400;
+
+    /// Largest valid counter period, in femtoseconds
+    pub const MAX_PERIOD: u32 = 100_000_000;
+
+    const FEMTOS_PER_NANO: u128 = 1_000_000;
+
+    /// Map the event timer block described by `hpet`
+    ///
+    /// Fails if the block is not in system memory or reports an invalid counter period.
+    pub fn new(hpet: &Hpet, bridge: B) -> Result<EventTimerBlock<B>, Error> {
+        let base = hpet.base_address();
+        if base.address_space != AddressSpace::SYSTEM_MEMORY {
+            return Err(Error::AddressSpace(base.address_space));
+        }
+        let phys = base.address as usize;
+        if phys & (align_of::<u64>() - 1) != 0 {
+            return Err(Error::Misaligned { phys });
+        }
+        let virt = map_nonnull(phys, Self::SIZE, bridge)?;
+        let mut block = Self {
+            regs: ptr::with_exposed_provenance_mut(virt),
+            period: 0,
+            bridge,
+        };
+        block.period = (block.capabilities() >> 32) as u32;
+        if block.period == 0 || block.period > Self::MAX_PERIOD {
+            return Err(Error::NotSupported);
+        }
+        Ok(block)
+    }
+
+    fn read(&self, offset: usize) -> u64 {
+        // SAFETY: The registers are mapped for the lifetime of `self`.
+        unsafe { self.regs.byte_add(offset).read_volatile() }
+    }
+
+    fn write(&self, offset: usize, value: u64) {
+        // SAFETY: The registers are mapped for the lifetime of `self`.
+        unsafe { self.regs.byte_add(offset).write_volatile(value) }
+    }
+
+    /// Read the General Capabilities and ID register
+    #[inline]
+    pub fn capabilities(&self) -> u64 {
+        self.read(CAPABILITIES)
+    }
+
+    /// Returns the low half of the capabilities register
+    #[inline]
+    pub fn id(&self) -> EventTimerBlockId {
+        EventTimerBlockId(self.capabilities() as u32)
+    }
+
+    /// Returns the period of the main counter, in femtoseconds
+    #[inline]
+    pub fn period(&self) -> u32 {
+        self.period
+    }
+
+    /// Returns the frequency of the main counter, in Hz
+    #[inline]
+    pub fn frequency(&self) -> u64 {
+        1_000_000_000_000_000 / self.period as u64
+    }
+
+    /// Returns the number of comparators in the block
+    #[inline]
+    pub fn comparators(&self) -> u8 {
+        self.id().comparators()
+    }
+
+    /// Start the main counter
+    pub fn enable(&self) {
+        self.write(CONFIGURATION, self.read(CONFIGURATION) | ENABLE_CNF);
+    }
+
+    /// Stop the main counter
+    pub fn disable(&self) {
+        self.write(CONFIGURATION, self.read(CONFIGURATION) & !ENABLE_CNF);
+    }
+
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.read(CONFIGURATION) & ENABLE_CNF != 0
+    }
+
+    /// Read the current value of the main counter
+    ///
+    /// The upper half is always zero for 32-bit counters.
+    #[inline]
+    pub fn counter(&self) -> u64 {
+        self.read(MAIN_COUNTER)
+    }
+
+    /// Convert a number of ticks into nanoseconds
+    #[inline]
+    pub fn ticks_to_nanos(&self, ticks: u64) -> u64 {
+        (ticks as u128 * self.period as u128 / Self::FEMTOS_PER_NANO) as u64
+    }
+
+    /// Convert a number of nanoseconds into ticks, rounding up
+    #[inline]
+    pub fn nanos_to_ticks(&self, nanos: u64) -> u64 {
+        (nanos as u128 * Self::FEMTOS_PER_NANO).div_ceil(self.period as u128) as u64
+    }
+
+    /// Busy-wait for at least `nanos` nanoseconds
+    ///
+    /// The main counter must be enabled.
+    pub fn delay(&self, nanos: u64) {
+        let mask = if self.id().is_64bit() {
+            u64::MAX
+        } else {
+            u32::MAX as u64
+        };
+        let target = self.nanos_to_ticks(nanos);
+        let mut elapsed = 0;
+        let mut last = self.counter();
+        while elapsed < target {
+            core::hint::spin_loop();
+            let now = self.counter();
+            elapsed += now.wrapping_sub(last) & mask;
+            last = now;
+        }
+    }
+}
+
+impl<B: Bridge> Drop for EventTimerBlock<B> {
+    fn drop(&mut self) {
+        self.bridge.unmap(self.regs.addr());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GenericAddress;
+    use core::mem::size_of;
+
+    /// Maps physical memory one-to-one, or fails every mapping with a null address
+    #[derive(Clone, Copy)]
+    struct TestBridge {
+        null: bool,
+    }
+
+    impl Bridge for TestBridge {
+        fn map(&self, phys: usize, _size: usize) -> usize {
+            phys
+        }
+
+        fn remap(&self, virt: usize, _new_size: usize) -> usize {
+            virt
+        }
+
+        fn unmap(&self, _virt: usize) {}
+
+        fn try_map(&self, phys: usize, _size: usize) -> Result<usize, Error> {
+            Ok(if self.null { 0 } else { phys })
+        }
+    }
+
+    /// HPET at 14.31818 MHz
+    const PERIOD: u32 = 69_841_279;
+
+    /// Returns an `Hpet` table describing the event timer block at `regs`
+    fn hpet(regs: &mut [u64]) -> [u8; size_of::<Hpet>()] {
+        let base = GenericAddress {
+            address_space: AddressSpace::SYSTEM_MEMORY,
+            bit_width: 64,
+            address: regs.as_mut_ptr().expose_provenance() as u64,
+            ..Default::default()
+        };
+        let mut bytes = [0; size_of::<Hpet>()];
+        bytes[40..52].copy_from_slice(&base.to_bytes());
+        bytes
+    }
+
+    fn block(regs: &mut [u64], bridge: TestBridge) -> Result<EventTimerBlock<TestBridge>, Error> {
+        let bytes = hpet(regs);
+        // SAFETY: `Hpet` is packed, and `bytes` is large enough.
+        let hpet = unsafe { &*bytes.as_ptr().cast::<Hpet>() };
+        EventTimerBlock::new(hpet, bridge)
+    }
+
+    #[test]
+    fn conversions() {
+        let mut regs = [0; EventTimerBlock::<TestBridge>::SIZE / 8];
+        regs[CAPABILITIES / 8] = (PERIOD as u64) << 32 | 1 << 13 | 2 << 8;
+        let block = block(&mut regs, TestBridge { null: false }).unwrap();
+        assert_eq!(block.period(), PERIOD);
+        assert_eq!(block.frequency(), 14_318_179);
+        assert_eq!(block.comparators(), 3);
+
+        assert_eq!(block.ticks_to_nanos(0), 0);
+        assert_eq!(block.ticks_to_nanos(1), 69);
+        assert_eq!(block.ticks_to_nanos(14_318_180), 1_000_000_004);
+        assert_eq!(block.nanos_to_ticks(0), 0);
+        assert_eq!(block.nanos_to_ticks(1), 1);
+        assert_eq!(block.nanos_to_ticks(1_000), 15);
+        assert_eq!(block.nanos_to_ticks(1_000_000_000), 14_318_180);
+        // Nanoseconds are rounded down and ticks up, so neither direction loses time.
+        for n in [1, 1_000, 14_318_180, u32::MAX as u64] {
+            assert!(block.nanos_to_ticks(block.ticks_to_nanos(n)) <= n);
+            assert!(block.ticks_to_nanos(block.nanos_to_ticks(n)) >= n);
+        }
+    }
+
+    #[test]
+    fn invalid_block() {
+        let mut regs = [0; EventTimerBlock::<TestBridge>::SIZE / 8];
+        let bridge = TestBridge { null: false };
+        assert!(matches!(block(&mut regs, bridge), Err(Error::NotSupported)));
+        regs[CAPABILITIES / 8] = (EventTimerBlock::<TestBridge>::MAX_PERIOD as u64 + 1) << 32;
+        assert!(matches!(block(&mut regs, bridge), Err(Error::NotSupported)));
+
+        regs[CAPABILITIES / 8] = (PERIOD as u64) << 32;
+        let bridge = TestBridge { null: true };
+        assert!(matches!(block(&mut regs, bridge), Err(Error::Map { .. })));
+    }
+}
@@ -9,15 +9,28 @@
     exposed_provenance,                         // https://github.com/rust-lang/rust/issues/95228
 )]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+pub mod address;
+#[cfg(feature = "alloc")]
+pub mod aml;
+pub mod discovery;
+mod error;
+pub mod hpet;
+pub mod pm_timer;
 pub mod sdt;
 
-pub use sdt::{RootTable, Sdt};
+pub use address::GenericAddress;
+pub use error::Error;
+pub use sdt::{ChecksumPolicy, RootTable, Sdt};
 
 use core::{
-    mem,
-    ptr::{self, Pointee},
+    fmt, mem,
+    ptr::{self, addr_of, Pointee},
 };
 
+/// Root System Description Pointer
 #[repr(C)]
 pub struct Rsdp {
     pub signature: [u8; 8],
@@ -33,6 +46,85 @@ pub struct Rsdp {
     pub reserved: [u8; 3],
 }
 
+impl Rsdp {
+    /// Signature found in the `signature` field of a valid RSDP
+    pub const SIGNATURE: [u8; 8] = *b"RSD PTR ";
+
+    /// Number of bytes covered by `checksum`, i.e. the size of the ACPI 1.0 structure
+    pub const V1_LENGTH: usize = 20;
+
+    /// Minimum value of `length` for ACPI revision 2 and above
+    pub const V2_LENGTH: usize = 36;
+
+    /// Validate the RSDP pointed to by `rsdp`
+    ///
+    /// This checks the signature and the ACPI 1.0 checksum, and for revision 2 and above,
+    /// the `length` field and the extended checksum.
+    ///
+    /// # Safety
+    ///
+    /// `rsdp` must be valid for reads of [`V1_LENGTH`](Rsdp::V1_LENGTH) bytes. If the
+    /// `revision` field is 2 or greater, it must be valid for reads of `length` bytes.
+    /// `rsdp` does not need to be aligned.
+    pub unsafe fn validate(rsdp: *const Rsdp) -> Result<(), RsdpError> {
+        if addr_of!((*rsdp).signature).read_unaligned() != Self::SIGNATURE {
+            return Err(RsdpError::Signature);
+        }
+        if checksum(rsdp.cast(), Self::V1_LENGTH) != 0 {
+            return Err(RsdpError::Checksum);
+        }
+        if addr_of!((*rsdp).revision).read() >= 2 {
+            let length = addr_of!((*rsdp).length).read_unaligned();
+            if (length as usize) < Self::V2_LENGTH {
+                return Err(RsdpError::Length(length));
+            }
+            if checksum(rsdp.cast(), length as usize) != 0 {
+                return Err(RsdpError::ExtendedChecksum);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reasons an [`Rsdp`] or the root table it points to may be rejected
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RsdpError {
+    /// The `signature` field is not `"RSD PTR "`.
+    Signature,
+    /// The bytes of the ACPI 1.0 structure do not sum to zero.
+    Checksum,
+    /// The `length` field is smaller than the ACPI 2.0 structure.
+    Length(u32),
+    /// The `length` bytes of the structure do not sum to zero.
+    ExtendedChecksum,
+    /// Neither the XSDT nor the RSDT address points to a usable root table.
+    NoRootTable,
+}
+
+impl fmt::Display for RsdpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Signature => f.write_str("invalid RSDP signature"),
+            Self::Checksum => f.write_str("invalid RSDP checksum"),
+            Self::Length(length) => write!(f, "invalid RSDP length ({length})"),
+            Self::ExtendedChecksum => f.write_str("invalid RSDP extended checksum"),
+            Self::NoRootTable => f.write_str("no valid RSDT or XSDT"),
+        }
+    }
+}
+
+/// Returns the 8-bit sum of `len` bytes starting at `ptr`
+///
+/// ACPI structures are valid when all of their bytes, including the checksum field, sum to
+/// zero.
+///
+/// # Safety
+///
+/// `ptr` must be valid for reads of `len` bytes.
+unsafe fn checksum(ptr: *const u8, len: usize) -> u8 {
+    (0..len).fold(0u8, |sum, i| sum.wrapping_add(ptr.add(i).read()))
+}
+
 fn size_of_unsized<T: ?Sized + Pointee<Metadata = usize>>() -> usize {
     let ptr = ptr::from_raw_parts::<T>((1usize << (usize::BITS - 1)) as *const (), 0);
     unsafe { mem::size_of_val_raw(ptr) }
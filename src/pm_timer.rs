@@ -0,0 +1,208 @@
+//! ACPI Power Management Timer
+
+use crate::{
+    address::{AccessSize, RegisterIo},
+    sdt::fadt::{Fadt, FadtFlags},
+    Error, GenericAddress,
+};
+
+/// ACPI Power Management Timer
+///
+/// A free-running 24- or 32-bit counter incrementing at [`PmTimer::FREQUENCY`], described by
+/// the [`Fadt`]. It is not present on HW-reduced ACPI platforms.
+#[derive(Clone, Copy, Debug)]
+pub struct PmTimer {
+    reg: GenericAddress,
+    mask: u32,
+}
+
+impl PmTimer {
+    /// Frequency of the PM timer, in Hz
+    pub const FREQUENCY: u64 = 3_579_545;
+
+    const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+    /// Create a `PmTimer` from the timer register described by the FADT
+    ///
+    /// Returns `None` if the platform does not have a PM timer.
+    pub fn new(fadt: &Fadt) -> Option<PmTimer> {
+        let flags = fadt.flags();
+        if flags.contains(FadtFlags::HW_REDUCED_ACPI) {
+            return None;
+        }
+        let reg = fadt.pm_tmr_blk()?;
+        let mask = if flags.contains(FadtFlags::TMR_VAL_EXT) {
+            u32::MAX
+        } else {
+            0x00ff_ffff
+        };
+        // The timer is always read as a full 32-bit register, only the upper bits of the
+        // value differ between the 24- and 32-bit variants.
+        let reg = GenericAddress {
+            bit_width: 32,
+            bit_offset: 0,
+            access_size: match reg.access_size {
+                AccessSize::UNDEFINED => AccessSize::DWORD,
+                size => size,
+            },
+            ..reg
+        };
+        Some(Self { reg, mask })
+    }
+
+    /// Returns the width of the counter in bits, either 24 or 32
+    #[inline]
+    pub fn bits(&self) -> u32 {
+        self.mask.count_ones()
+    }
+
+    /// Returns the register used to read the timer
+    #[inline]
+    pub fn register(&self) -> GenericAddress {
+        self.reg
+    }
+
+    /// Read the current value of the counter
+    pub fn read(&self, io: &impl RegisterIo) -> Result<u32, Error> {
+        Ok(self.reg.read(io)? as u32 & self.mask)
+    }
+
+    /// Returns the number of ticks between two counter values
+    ///
+    /// The counter is assumed to have wrapped around at most once between `start` and `end`.
+    #[inline]
+    pub fn ticks_between(&self, start: u32, end: u32) -> u32 {
+        end.wrapping_sub(start) & self.mask
+    }
+
+    /// Convert a number of ticks into nanoseconds
+    #[inline]
+    pub fn ticks_to_nanos(ticks: u64) -> u64 {
+        (ticks as u128 * Self::NANOS_PER_SEC / Self::FREQUENCY as u128) as u64
+    }
+
+    /// Convert a number of nanoseconds into ticks, rounding up
+    #[inline]
+    pub fn nanos_to_ticks(nanos: u64) -> u64 {
+        (nanos as u128 * Self::FREQUENCY as u128).div_ceil(Self::NANOS_PER_SEC) as u64
+    }
+
+    /// Busy-wait for at least `nanos` nanoseconds
+    ///
+    /// The counter is polled continuously, so delays longer than a single period of the
+    /// counter are handled correctly.
+    pub fn delay(&self, io: &impl RegisterIo, nanos: u64) -> Result<(), Error> {
+        let target = Self::nanos_to_ticks(nanos);
+        let mut elapsed = 0;
+        let mut last = self.read(io)?;
+        while elapsed < target {
+            core::hint::spin_loop();
+            let now = self.read(io)?;
+            elapsed += self.ticks_between(last, now) as u64;
+            last = now;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::address::{tests::Access, AddressSpace};
+    use core::cell::RefCell;
+    use std::{collections::VecDeque, vec::Vec};
+
+    /// Timer register returning each of `values` in turn, recording every access
+    #[derive(Default)]
+    struct ScriptedIo {
+        values: RefCell<VecDeque<u64>>,
+        accesses: RefCell<Vec<Access>>,
+    }
+
+    impl ScriptedIo {
+        fn new(values: &[u64]) -> ScriptedIo {
+            Self {
+                values: RefCell::new(values.iter().copied().collect()),
+                ..Default::default()
+            }
+        }
+    }
+
+    impl RegisterIo for ScriptedIo {
+        fn read_io(&self, port: u64, size: AccessSize) -> Result<u64, Error> {
+            self.accesses
+                .borrow_mut()
+                .push(Access::Read(AddressSpace::SYSTEM_IO, port, size));
+            self.values
+                .borrow_mut()
+                .pop_front()
+                .ok_or(Error::NotSupported)
+        }
+    }
+
+    fn timer(bits: u32) -> PmTimer {
+        PmTimer {
+            reg: GenericAddress {
+                bit_width: 32,
+                access_size: AccessSize::DWORD,
+                ..GenericAddress::system_io(0x808, 4)
+            },
+            mask: u32::MAX >> (32 - bits),
+        }
+    }
+
+    #[test]
+    fn wrap_around() {
+        let timer24 = timer(24);
+        assert_eq!(timer24.bits(), 24);
+        assert_eq!(timer24.ticks_between(0xff_fff0, 0x10), 0x20);
+        assert_eq!(timer24.ticks_between(0x10, 0xff_fff0), 0xff_ffe0);
+        // The upper byte of a 24-bit counter is ignored.
+        let io = ScriptedIo::new(&[0xab00_0010]);
+        assert_eq!(timer24.read(&io), Ok(0x10));
+
+        let timer32 = timer(32);
+        assert_eq!(timer32.bits(), 32);
+        assert_eq!(timer32.ticks_between(0xffff_fff0, 0x10), 0x20);
+        assert_eq!(timer32.ticks_between(0xff_fff0, 0x10), 0xff00_0020);
+        let io = ScriptedIo::new(&[0xab00_0010]);
+        assert_eq!(timer32.read(&io), Ok(0xab00_0010));
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(PmTimer::ticks_to_nanos(0), 0);
+        assert_eq!(PmTimer::ticks_to_nanos(1), 279);
+        assert_eq!(PmTimer::ticks_to_nanos(PmTimer::FREQUENCY), 1_000_000_000);
+        assert_eq!(PmTimer::nanos_to_ticks(0), 0);
+        assert_eq!(PmTimer::nanos_to_ticks(1), 1);
+        assert_eq!(PmTimer::nanos_to_ticks(1_000), 4);
+        assert_eq!(PmTimer::nanos_to_ticks(1_000_000_000), PmTimer::FREQUENCY);
+        // Nanoseconds are rounded down and ticks up, so ticks survive a round trip and
+        // delays are never shortened.
+        for n in [1, 2, 1_000, 0xff_ffff, u32::MAX as u64, 1 << 40] {
+            assert_eq!(PmTimer::nanos_to_ticks(PmTimer::ticks_to_nanos(n)), n);
+            assert!(PmTimer::ticks_to_nanos(PmTimer::nanos_to_ticks(n)) >= n);
+        }
+    }
+
+    #[test]
+    fn delay() {
+        let read = Access::Read(AddressSpace::SYSTEM_IO, 0x808, AccessSize::DWORD);
+
+        // 10 µs is 36 ticks, the counter wraps after 16.
+        let io = ScriptedIo::new(&[0xff_fff0, 0xff_fffa, 0x04, 0x13, 0x14, 0x15]);
+        timer(24).delay(&io, 10_000).unwrap();
+        assert_eq!(io.accesses.take(), [read; 5]);
+
+        let io = ScriptedIo::new(&[0x1234]);
+        timer(24).delay(&io, 0).unwrap();
+        assert_eq!(io.accesses.take(), [read]);
+
+        // Read errors are returned.
+        let io = ScriptedIo::new(&[0, 1]);
+        assert_eq!(timer(24).delay(&io, 1_000), Err(Error::NotSupported));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{size_of_unsized, Rsdp};
+use crate::{checksum, size_of_unsized, Error, Rsdp, RsdpError};
 use core::{
     fmt,
     mem::size_of,
@@ -6,22 +6,62 @@ use core::{
 };
 use libsa::endian::{u32_le, u64_le};
 
+pub mod dmar;
+pub mod dsdt;
 pub mod fadt;
+pub mod hmat;
+pub mod hpet;
 pub mod madt;
 pub mod mcfg;
 pub mod rhct;
+pub mod slit;
+pub mod srat;
 
 pub use mapped::Mapped;
 
+use dsdt::{DefinitionBlock, Dsdt, Psdt, Ssdt};
+use fadt::{Facs, Fadt};
+
 pub trait Bridge: Copy {
     fn map(&self, phys: usize, size: usize) -> usize;
     fn remap(&self, virt: usize, new_size: usize) -> usize;
     fn unmap(&self, virt: usize);
+
+    /// Map `size` bytes of physical memory at `phys`, returning an error on failure
+    ///
+    /// The default implementation calls [`Bridge::map()`] and treats a null address as
+    /// failure. Implementations which can fail should override this method.
+    fn try_map(&self, phys: usize, size: usize) -> Result<usize, Error> {
+        match self.map(phys, size) {
+            0 => Err(Error::Map { phys, size }),
+            virt => Ok(virt),
+        }
+    }
+
+    /// Remap the mapping at `virt` with a size of `new_size` bytes, returning an error on
+    /// failure
+    ///
+    /// The default implementation calls [`Bridge::remap()`] and treats a null address as
+    /// failure. Implementations which can fail should override this method.
+    fn try_remap(&self, virt: usize, new_size: usize) -> Result<usize, Error> {
+        match self.remap(virt, new_size) {
+            0 => Err(Error::Remap {
+                virt,
+                size: new_size,
+            }),
+            virt => Ok(virt),
+        }
+    }
 }
 
 mod mapped {
-    use super::{Bridge, Header, Sdt};
-    use core::{num::NonZeroUsize, ops::Deref, ptr::NonNull};
+    use super::{fadt::Facs, Bridge, Header, Sdt};
+    use crate::Error;
+    use core::{
+        num::NonZeroUsize,
+        ops::{Deref, DerefMut},
+        ptr::NonNull,
+    };
 
     pub struct Mapped<T: ?Sized, B: Bridge> {
         ptr: NonNull<T>,
@@ -35,6 +75,12 @@ mod mapped {
     }
 
     impl<T: ?Sized, B: Bridge> Mapped<T, B> {
+        /// Take ownership of a mapping created by `bridge`
+        ///
+        /// # Panics
+        ///
+        /// This function panics if `ptr` is null. Use [`map_nonnull()`](super::map_nonnull)
+        /// to map memory, which returns an error instead of a null address.
         pub(crate) fn new(ptr: *const T, bridge: B) -> Mapped<T, B> {
             let ptr = NonNull::new(ptr.cast_mut()).unwrap();
             Mapped { ptr, bridge }
@@ -47,33 +93,88 @@ mod mapped {
         }
     }
 
+    impl<T: ?Sized, B: Bridge> Mapped<T, B> {
+        /// Create a new mapping of the same memory with a size of `size` bytes
+        fn try_remap(&self, size: usize) -> Result<Self, Error> {
+            let addr = self.bridge.try_remap(self.ptr.addr().get(), size)?;
+            let addr = NonZeroUsize::new(addr).ok_or(Error::Remap {
+                virt: self.ptr.addr().get(),
+                size,
+            })?;
+            Ok(Self {
+                ptr: self.ptr.with_addr(addr),
+                bridge: self.bridge,
+            })
+        }
+    }
+
     impl<B: Bridge> Mapped<Header, B> {
+        /// Create a new mapping of the full table described by this header
+        ///
+        /// # Panics
+        ///
+        /// This function panics if the table cannot be mapped, see
+        /// [`Mapped::try_clone_header()`] for a non-panicking version.
         pub fn clone_header(&self) -> Self {
-            let size = self.length as usize;
-            let addr = self.bridge.remap(self.ptr.addr().get(), size);
-            Self {
-                ptr: self.ptr.with_addr(NonZeroUsize::new(addr).unwrap()),
-                bridge: self.bridge,
+            match self.try_clone_header() {
+                Ok(header) => header,
+                Err(error) => panic!("acpi: {error}"),
             }
         }
 
+        /// Create a new mapping of the full table described by this header
+        pub fn try_clone_header(&self) -> Result<Self, Error> {
+            self.try_remap(self.length as usize)
+        }
+
+        /// Returns `true` if the bytes of the table sum to zero
+        ///
+        /// The full table must have been mapped, as is the case for headers returned by
+        /// [`RootTable`](super::RootTable).
+        pub fn is_checksum_valid(&self) -> bool {
+            // SAFETY: The mapping covers `length` bytes of the table.
+            unsafe { Header::is_checksum_valid(self.ptr.as_ptr()) }
+        }
+
+        /// Map the full table described by this header
+        ///
+        /// # Panics
+        ///
+        /// This function panics if the header does not describe a valid `T`, see
+        /// [`Mapped::try_map_full()`] for a non-panicking version.
         pub fn map_full<T: ?Sized + Sdt>(self) -> Mapped<T, B> {
-            assert!(self.signature == T::SIGNATURE);
+            match self.try_map_full() {
+                Ok(table) => table,
+                Err(error) => panic!("acpi: {error}"),
+            }
+        }
+
+        /// Map the full table described by this header
+        ///
+        /// Returns an error if the signature does not match `T`, if `length` is smaller
+        /// than [`T::MIN_LENGTH`](Sdt::MIN_LENGTH), or if the table cannot be mapped.
+        pub fn try_map_full<T: ?Sized + Sdt>(self) -> Result<Mapped<T, B>, Error> {
+            if self.signature != T::SIGNATURE {
+                return Err(Error::Signature {
+                    expected: T::SIGNATURE,
+                    found: self.signature,
+                });
+            }
+            if (self.length as usize) < T::MIN_LENGTH {
+                return Err(Error::TooShort {
+                    signature: T::SIGNATURE,
+                    length: self.length as usize,
+                    min: T::MIN_LENGTH,
+                });
+            }
 
             // Map the full size of the table (in bytes) according to the header.
-            let addr = self.ptr.addr().get();
-            let size = self.length as usize;
-            let addr = self.bridge.remap(addr, size);
-            let ptr = self.ptr.as_ptr().with_addr(addr);
+            let header = self.try_remap(self.length as usize)?.into_inner();
 
             // Let the table handle determining the pointer metadata, if any.
-            let ptr = unsafe { T::from_header_ptr(ptr) };
-            let ptr = NonNull::new(ptr.cast_mut()).unwrap();
+            let ptr = unsafe { T::from_header_ptr(header) };
 
-            Mapped {
-                ptr,
-                bridge: self.bridge,
-            }
+            Ok(Mapped::new(ptr, self.bridge))
         }
     }
 
@@ -85,13 +186,29 @@ mod mapped {
         }
     }
 
+    // Tables are not mutable, as their length and the offsets they contain determine how
+    // much is mapped. The FACS is always mapped in full, and its waking vectors are written
+    // by OSPM.
+    impl<B: Bridge> DerefMut for Mapped<Facs, B> {
+        fn deref_mut(&mut self) -> &mut Self::Target {
+            unsafe { self.ptr.as_mut() }
+        }
+    }
+
+    impl<T: ?Sized + Sdt, B: Bridge> Mapped<T, B> {
+        /// Create a new mapping of this table
+        ///
+        /// This is the non-panicking version of [`Clone::clone()`].
+        pub fn try_clone(&self) -> Result<Self, Error> {
+            self.try_remap(self.header().length as usize)
+        }
+    }
+
     impl<T: ?Sized + Sdt, B: Bridge> Clone for Mapped<T, B> {
         fn clone(&self) -> Self {
-            let size = self.header().length as usize;
-            let addr = self.bridge.remap(self.ptr.addr().get(), size);
-            Self {
-                ptr: self.ptr.with_addr(NonZeroUsize::new(addr).unwrap()),
-                bridge: self.bridge,
+            match self.try_clone() {
+                Ok(table) => table,
+                Err(error) => panic!("acpi: {error}"),
             }
         }
     }
@@ -111,6 +228,12 @@ pub unsafe trait Sdt {
     /// that a pointer to a`Header` with a matching singature can be cast to a pointer to `Self`.
     const SIGNATURE: Signature;
 
+    /// Minimum value of the `length` field of a valid table
+    ///
+    /// Tables with a smaller `length` are rejected by [`Mapped::try_map_full()`]. This must
+    /// be at least the size of the fixed portion of `Self` for dynamically-sized tables.
+    const MIN_LENGTH: usize = size_of::<Header>();
+
     /// Create a pointer to a full table from a pointer to its `Header`
     ///
     /// This function handles creating the (potentially fat) pointer to `Self`.
@@ -143,6 +266,13 @@ pub unsafe trait Sdt {
         //         See the safety docs on `.header_raw()`.
         unsafe { &*Self::header_raw(self) }
     }
+
+    /// Returns `true` if all bytes of this table, as specified by the `length` field of its
+    /// [`Header`], sum to zero
+    fn is_checksum_valid(&self) -> bool {
+        // SAFETY: `self` has provenance over all bytes of the table.
+        unsafe { Header::is_checksum_valid(Self::header_raw(self)) }
+    }
 }
 
 // impl<T: ?Sized + Sdt, B: Bridge> AsRef<Header> for Mapped<T, B> {
@@ -184,6 +314,60 @@ pub struct Header {
     pub creator_revision: u32,
 }
 
+impl Header {
+    /// Returns `true` if all bytes of the table sum to zero
+    ///
+    /// # Safety
+    ///
+    /// `self` must be valid for reads of `length` bytes.
+    pub unsafe fn is_checksum_valid(self: *const Self) -> bool {
+        checksum(self.cast(), (*self).length as usize) == 0
+    }
+}
+
+/// How tables with an invalid checksum are handled by a [`RootTable`]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum ChecksumPolicy {
+    /// Log an error and treat the table as if it were not present.
+    Reject,
+    /// Log a warning and use the table anyway.
+    #[default]
+    Warn,
+    /// Use the table without verifying its checksum.
+    Ignore,
+}
+
+impl ChecksumPolicy {
+    /// Returns `true` if a table should be used according to this policy
+    ///
+    /// Nothing is logged, since tables are checked every time they are returned by a
+    /// [`RootTable`]. Invalid tables are instead logged by [`ChecksumPolicy::check()`] when
+    /// the policy is given to the `RootTable`.
+    fn accept(self, is_valid: impl FnOnce() -> bool) -> bool {
+        match self {
+            Self::Ignore | Self::Warn => true,
+            Self::Reject => is_valid(),
+        }
+    }
+
+    /// Returns `true` if a table should be used according to this policy, logging it if its
+    /// checksum is invalid
+    fn check(self, signature: Signature, is_valid: impl FnOnce() -> bool) -> bool {
+        match self {
+            Self::Ignore => true,
+            _ if is_valid() => true,
+            Self::Reject => {
+                log::error!("acpi: {signature} has an invalid checksum, ignoring table");
+                false
+            }
+            Self::Warn => {
+                log::warn!("acpi: {signature} has an invalid checksum");
+                true
+            }
+        }
+    }
+}
+
 #[derive(Clone, Copy)]
 enum RootPtrs {
     Rsdt(*const [u32_le]),
@@ -201,7 +385,7 @@ impl RootPtrs {
     unsafe fn iter<'a, B: Bridge + 'a>(
         &'a self,
         bridge: B,
-    ) -> impl Iterator<Item = Mapped<Header, B>> + 'a {
+    ) -> impl Iterator<Item = Result<Mapped<Header, B>, Error>> + 'a {
         (0..self.len()).map(move |index| {
             let phys = match self {
                 Self::Rsdt(ptrs) => ptrs.get_unchecked(index).read_unaligned().get() as usize,
@@ -216,6 +400,7 @@ impl RootPtrs {
 pub struct RootTable<B: Bridge> {
     pub acpi_revision: u8,
     root_ptrs: RootPtrs,
+    checksum_policy: ChecksumPolicy,
     bridge: B,
 }
 
@@ -225,6 +410,15 @@ unsafe impl<B: Bridge + Sync> Sync for RootTable<B> {}
 impl<B: Bridge> RootTable<B> {
     /// Create a new `RootTable` from a pointer to the RSDP
     ///
+    /// The RSDP is trusted as-is, see [`RootTable::try_new()`] for a version which validates
+    /// it first. Only the root table is mapped, so tables with an invalid checksum are not
+    /// logged.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the RSDT or XSDT cannot be mapped or has an invalid header,
+    /// see [`RootTable::try_new()`] for a non-panicking version.
+    ///
     /// # Safety
     ///
     /// `ptr` must be a valid pointer to an `Rsdp`.
@@ -232,26 +426,179 @@ impl<B: Bridge> RootTable<B> {
         let acpi_revision = (*rsdp).revision;
         let root_ptrs = if acpi_revision < 2 {
             let rsdt = map_table::<RootSdt<u32_le>, _>((*rsdp).rsdt_addr as usize, bridge);
-            RootPtrs::Rsdt(addr_of!(rsdt.tables))
+            RootPtrs::Rsdt(addr_of!((*rsdt).tables))
         } else {
             let xsdt = map_table::<RootSdt<u64_le>, _>((*rsdp).xsdt_addr as usize, bridge);
-            RootPtrs::Xsdt(addr_of!(xsdt.tables))
+            RootPtrs::Xsdt(addr_of!((*xsdt).tables))
         };
+        Self::with_root_ptrs(acpi_revision, root_ptrs, ChecksumPolicy::default(), bridge)
+    }
+
+    /// Create a new `RootTable` from a pointer to the RSDP, validating it first
+    ///
+    /// The RSDP is checked with [`Rsdp::validate()`]. The XSDT is used when the revision is
+    /// 2 or greater and `xsdt_addr` points to a table with the correct signature, otherwise
+    /// this falls back to the RSDT.
+    ///
+    /// The checksum of the root table itself is checked according to the default
+    /// [`ChecksumPolicy`], see [`RootTable::try_new_with_policy()`].
+    ///
+    /// # Safety
+    ///
+    /// `rsdp` must be valid for reads as described by [`Rsdp::validate()`].
+    pub unsafe fn try_new(rsdp: *const Rsdp, bridge: B) -> Result<RootTable<B>, Error> {
+        Self::try_new_with_policy(rsdp, bridge, ChecksumPolicy::default())
+    }
+
+    /// Create a new `RootTable` from a pointer to the RSDP, with the given checksum policy
+    ///
+    /// This is the same as [`RootTable::try_new()`], except that `checksum_policy` applies to
+    /// the XSDT or RSDT as well as every table returned by the `RootTable`. A root table
+    /// rejected by the policy is treated the same way as one with an invalid signature.
+    ///
+    /// Unless the policy is [`ChecksumPolicy::Ignore`], every table and the DSDT are mapped
+    /// once here to log those with an invalid checksum, rather than every time they are
+    /// returned.
+    ///
+    /// If the XSDT cannot be mapped, the RSDT is used instead. Errors returned by the
+    /// [`Bridge`] while mapping the RSDT are propagated, as is the error for the XSDT if
+    /// there is no RSDT.
+    ///
+    /// # Safety
+    ///
+    /// `rsdp` must be valid for reads as described by [`Rsdp::validate()`].
+    pub unsafe fn try_new_with_policy(
+        rsdp: *const Rsdp,
+        bridge: B,
+        checksum_policy: ChecksumPolicy,
+    ) -> Result<RootTable<B>, Error> {
+        Rsdp::validate(rsdp)?;
+        let acpi_revision = (*rsdp).revision;
+        let root_ptrs = Self::find_root_ptrs(rsdp, bridge, checksum_policy)?;
+        let root = Self::with_root_ptrs(acpi_revision, root_ptrs, checksum_policy, bridge);
+        root.log_invalid_checksums();
+        Ok(root)
+    }
+
+    /// Map the XSDT, or the RSDT if the XSDT is missing or invalid
+    unsafe fn find_root_ptrs(
+        rsdp: *const Rsdp,
+        bridge: B,
+        checksum_policy: ChecksumPolicy,
+    ) -> Result<RootPtrs, Error> {
+        let xsdt_addr = if (*rsdp).revision >= 2 {
+            (*rsdp).xsdt_addr as usize
+        } else {
+            0
+        };
+        let mut xsdt_error = None;
+        if xsdt_addr != 0 {
+            match map_root_sdt::<RootSdt<u64_le>, _>(xsdt_addr, bridge, checksum_policy) {
+                Ok(Some(xsdt)) => return Ok(RootPtrs::Xsdt(addr_of!((*xsdt).tables))),
+                Ok(None) => {
+                    log::warn!("acpi: invalid XSDT at {xsdt_addr:#x}, falling back to RSDT")
+                }
+                Err(error) => {
+                    log::warn!("acpi: XSDT at {xsdt_addr:#x}: {error}, falling back to RSDT");
+                    xsdt_error = Some(error);
+                }
+            }
+        }
+
+        let rsdt_addr = (*rsdp).rsdt_addr as usize;
+        if rsdt_addr != 0 {
+            match map_root_sdt::<RootSdt<u32_le>, _>(rsdt_addr, bridge, checksum_policy)? {
+                Some(rsdt) => return Ok(RootPtrs::Rsdt(addr_of!((*rsdt).tables))),
+                None => log::warn!("acpi: invalid RSDT at {rsdt_addr:#x}"),
+            }
+        }
+
+        Err(xsdt_error.unwrap_or(RsdpError::NoRootTable.into()))
+    }
+
+    fn with_root_ptrs(
+        acpi_revision: u8,
+        root_ptrs: RootPtrs,
+        checksum_policy: ChecksumPolicy,
+        bridge: B,
+    ) -> RootTable<B> {
         Self {
             acpi_revision,
-            bridge,
             root_ptrs,
+            checksum_policy,
+            bridge,
         }
     }
 
+    /// Log every table with an invalid checksum, according to the [`ChecksumPolicy`]
+    ///
+    /// This is done once when the policy is set, rather than every time a table is returned,
+    /// and maps every table as well as the DSDT.
+    fn log_invalid_checksums(&self) {
+        if self.checksum_policy == ChecksumPolicy::Ignore {
+            return;
+        }
+        let tables = unsafe { self.root_ptrs.iter(self.bridge) }
+            .filter_map(|header| header.and_then(|header| header.try_clone_header()).ok());
+        for header in tables {
+            self.checksum_policy
+                .check(header.signature, || header.is_checksum_valid());
+        }
+        if let Ok(Some(dsdt)) = self.map_dsdt() {
+            self.checksum_policy
+                .check(Dsdt::SIGNATURE, || dsdt.is_checksum_valid());
+        }
+    }
+
+    /// Returns the policy applied to tables with an invalid checksum
+    #[inline]
+    pub fn checksum_policy(&self) -> ChecksumPolicy {
+        self.checksum_policy
+    }
+
+    /// Set the policy applied to tables with an invalid checksum
+    ///
+    /// Tables with an invalid checksum are logged again according to the new policy, which
+    /// maps every table as well as the DSDT.
+    pub fn set_checksum_policy(&mut self, checksum_policy: ChecksumPolicy) {
+        self.checksum_policy = checksum_policy;
+        self.log_invalid_checksums();
+    }
+
+    /// Returns an iterator over all tables accepted by the [`ChecksumPolicy`]
+    ///
+    /// # Panics
+    ///
+    /// The iterator panics if a table cannot be mapped, see [`RootTable::try_all_tables()`]
+    /// for a non-panicking version.
     pub fn all_tables(&self) -> impl Iterator<Item = Mapped<Header, B>> + '_ {
+        self.try_all_tables().map(|header| match header {
+            Ok(header) => header,
+            Err(error) => panic!("acpi: {error}"),
+        })
+    }
+
+    /// Returns an iterator over all tables accepted by the [`ChecksumPolicy`]
+    ///
+    /// Tables which cannot be mapped are returned as errors.
+    pub fn try_all_tables(&self) -> impl Iterator<Item = Result<Mapped<Header, B>, Error>> + '_ {
         unsafe {
             self.root_ptrs
                 .iter(self.bridge)
-                .map(|header| header.clone_header())
+                .map(|header| header?.try_clone_header())
+                .filter(|header| match header {
+                    Ok(header) => self.checksum_policy.accept(|| header.is_checksum_valid()),
+                    Err(_) => true,
+                })
         }
     }
 
+    /// Returns the `index`th table with the given signature
+    ///
+    /// # Panics
+    ///
+    /// This function panics if a table cannot be mapped, see
+    /// [`RootTable::try_get_table_by_signature()`] for a non-panicking version.
     pub fn get_table_by_signature(
         &self,
         signature: Signature,
@@ -262,10 +609,144 @@ impl<B: Bridge> RootTable<B> {
             .nth(index)
     }
 
+    /// Returns the `index`th table with the given signature
+    ///
+    /// Tables whose header cannot be mapped are skipped with a warning. If no table is
+    /// found, the first such error is returned, since the table may have been among them.
+    /// Otherwise, returns `Ok(None)` if there is no such table, or an error if it cannot be
+    /// mapped.
+    pub fn try_get_table_by_signature(
+        &self,
+        signature: Signature,
+        index: usize,
+    ) -> Result<Option<Mapped<Header, B>>, Error> {
+        let mut skipped = None;
+        let mut index = index;
+        for header in unsafe { self.root_ptrs.iter(self.bridge) } {
+            let header = match header {
+                Ok(header) => header,
+                Err(error) => {
+                    log::warn!("acpi: skipping table: {error}");
+                    skipped.get_or_insert(error);
+                    continue;
+                }
+            };
+            // Only map the full table if it is the one being looked for.
+            if header.signature != signature {
+                continue;
+            }
+            let header = header.try_clone_header()?;
+            if !self.checksum_policy.accept(|| header.is_checksum_valid()) {
+                continue;
+            }
+            if index == 0 {
+                return Ok(Some(header));
+            }
+            index -= 1;
+        }
+        skipped.map_or(Ok(None), Err)
+    }
+
+    /// Returns the `index`th table with the signature of `T`
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the table is malformed, see [`RootTable::try_get_table()`]
+    /// for a non-panicking version.
     pub fn get_table<T: ?Sized + Sdt>(&self, index: usize) -> Option<Mapped<T, B>> {
         self.get_table_by_signature(T::SIGNATURE, index)
             .map(Mapped::map_full)
     }
+
+    /// Returns the `index`th table with the signature of `T`
+    ///
+    /// Returns `Ok(None)` if there is no such table, or an error if it is malformed or
+    /// cannot be mapped.
+    pub fn try_get_table<T: ?Sized + Sdt>(
+        &self,
+        index: usize,
+    ) -> Result<Option<Mapped<T, B>>, Error> {
+        self.try_get_table_by_signature(T::SIGNATURE, index)?
+            .map(Mapped::try_map_full)
+            .transpose()
+    }
+
+    /// Map the FACS referenced by the FADT
+    ///
+    /// Returns `Ok(None)` if there is no FADT, or if it does not reference a FACS.
+    pub fn facs(&self) -> Result<Option<Mapped<Facs, B>>, Error> {
+        match self.try_get_table::<Fadt>(0)? {
+            Some(fadt) => fadt.facs(self.bridge),
+            None => Ok(None),
+        }
+    }
+
+    /// Map the DSDT referenced by the FADT
+    ///
+    /// Returns `Ok(None)` if there is no FADT, if it does not reference a DSDT, or if the
+    /// DSDT is rejected by the [`ChecksumPolicy`].
+    pub fn dsdt(&self) -> Result<Option<Mapped<Dsdt, B>>, Error> {
+        let Some(dsdt) = self.map_dsdt()? else {
+            return Ok(None);
+        };
+        if !self.checksum_policy.accept(|| dsdt.is_checksum_valid()) {
+            return Ok(None);
+        }
+        Ok(Some(dsdt))
+    }
+
+    /// Map the DSDT referenced by the FADT, regardless of its checksum
+    fn map_dsdt(&self) -> Result<Option<Mapped<Dsdt, B>>, Error> {
+        let Some(fadt) = self.try_get_table::<Fadt>(0)? else {
+            return Ok(None);
+        };
+        let phys = fadt.dsdt() as usize;
+        if phys == 0 {
+            return Ok(None);
+        }
+        let dsdt = unsafe { map_header(phys, self.bridge)? }.try_map_full::<Dsdt>()?;
+        Ok(Some(dsdt))
+    }
+
+    /// Returns an iterator over all definition blocks, in the order they must be loaded
+    ///
+    /// The DSDT is returned first, followed by every SSDT and PSDT in the order they appear
+    /// in the root table.
+    ///
+    /// # Panics
+    ///
+    /// The iterator panics if a table is malformed, see [`RootTable::try_definition_blocks()`]
+    /// for a non-panicking version.
+    pub fn definition_blocks(&self) -> impl Iterator<Item = DefinitionBlock<B>> + '_ {
+        self.try_definition_blocks().map(|table| match table {
+            Ok(table) => table,
+            Err(error) => panic!("acpi: {error}"),
+        })
+    }
+
+    /// Returns an iterator over all definition blocks, in the order they must be loaded
+    ///
+    /// This is the same as [`RootTable::definition_blocks()`], except that tables which are
+    /// malformed or cannot be mapped are returned as errors.
+    pub fn try_definition_blocks(
+        &self,
+    ) -> impl Iterator<Item = Result<DefinitionBlock<B>, Error>> + '_ {
+        let dsdt = core::iter::once_with(|| self.dsdt().transpose())
+            .flatten()
+            .map(|dsdt| dsdt.map(DefinitionBlock::Dsdt));
+        let tables = self.try_all_tables().filter_map(|header| {
+            let header = match header {
+                Ok(header) => header,
+                Err(error) => return Some(Err(error)),
+            };
+            match header.signature {
+                Ssdt::SIGNATURE => Some(header.try_map_full().map(DefinitionBlock::Ssdt)),
+                Psdt::SIGNATURE => Some(header.try_map_full().map(DefinitionBlock::Psdt)),
+                _ => None,
+            }
+        });
+        dsdt.chain(tables)
+    }
 }
 
 #[repr(C, packed)]
@@ -315,13 +796,138 @@ where
     ptr::from_raw_parts(header, len)
 }
 
-unsafe fn map_header<B: Bridge>(phys: usize, bridge: B) -> Mapped<Header, B> {
-    let header = ptr::with_exposed_provenance::<Header>(bridge.map(phys, size_of::<Header>()));
-    Mapped::new(header, bridge)
+/// A structure from a list of variable-length structures, such as the entries of the SRAT
+///
+/// Each structure starts with a header giving its type and length in bytes.
+struct Structure<'a> {
+    r#type: u16,
+    bytes: &'a [u8],
+}
+
+impl<'a> Structure<'a> {
+    /// Returns an iterator over the structures in `bytes`
+    ///
+    /// `header` returns the type and length of a structure from its first `header_len`
+    /// bytes. Iteration stops at a structure which is shorter than its header or extends
+    /// past the end of `bytes`.
+    fn iter(
+        bytes: &'a [u8],
+        header_len: usize,
+        header: fn(&[u8]) -> (u16, usize),
+    ) -> impl Iterator<Item = Structure<'a>> {
+        let mut offset = 0;
+        core::iter::from_fn(move || {
+            let (r#type, length) = header(bytes.get(offset..offset + header_len)?);
+            if length < header_len {
+                return None;
+            }
+            let bytes = bytes.get(offset..offset + length)?;
+            offset += length;
+            Some(Structure { r#type, bytes })
+        })
+    }
+
+    /// Cast the structure to `T`, or return `None` if it is too short
+    ///
+    /// # Safety
+    ///
+    /// `T` must have an alignment of 1, and be valid for any bit pattern.
+    unsafe fn cast<T>(&self) -> Option<&'a T> {
+        (self.bytes.len() >= size_of::<T>()).then(|| &*self.bytes.as_ptr().cast::<T>())
+    }
+
+    /// Cast the structure to `T`, which ends in a slice of bytes holding the rest of the
+    /// structure, or return `None` if it is too short
+    ///
+    /// # Safety
+    ///
+    /// `T` must have an alignment of 1, and be valid for any bit pattern.
+    unsafe fn cast_unsized<T>(&self) -> Option<&'a T>
+    where
+        T: ?Sized + Pointee<Metadata = usize>,
+    {
+        let len = self.bytes.len().checked_sub(size_of_unsized::<T>())?;
+        Some(&*ptr::from_raw_parts::<T>(self.bytes.as_ptr(), len))
+    }
+}
+
+/// Map `size` bytes at `phys`, treating a null address as failure
+///
+/// [`Bridge::try_map()`] may be overridden by an implementation which returns `Ok(0)`, which
+/// must not reach [`Mapped::new()`].
+pub(crate) fn map_nonnull<B: Bridge>(phys: usize, size: usize, bridge: B) -> Result<usize, Error> {
+    match bridge.try_map(phys, size)? {
+        0 => Err(Error::Map { phys, size }),
+        virt => Ok(virt),
+    }
+}
+
+unsafe fn map_header<B: Bridge>(phys: usize, bridge: B) -> Result<Mapped<Header, B>, Error> {
+    let virt = map_nonnull(phys, size_of::<Header>(), bridge)?;
+    let header = ptr::with_exposed_provenance::<Header>(virt);
+    Ok(Mapped::new(header, bridge))
+}
+
+/// Map a root table if its header looks sane and it is accepted by `checksum_policy`
+///
+/// The table stays mapped for the lifetime of the [`RootTable`].
+unsafe fn map_root_sdt<T: ?Sized + Sdt, B: Bridge>(
+    phys: usize,
+    bridge: B,
+    checksum_policy: ChecksumPolicy,
+) -> Result<Option<*const T>, Error> {
+    let table = match map_header(phys, bridge)?.try_map_full::<T>() {
+        Ok(table) => table,
+        Err(error @ (Error::Map { .. } | Error::Remap { .. })) => return Err(error),
+        Err(_) => return Ok(None),
+    };
+    if !checksum_policy.check(T::SIGNATURE, || table.is_checksum_valid()) {
+        return Ok(None);
+    }
+    Ok(Some(table.into_inner()))
+}
+
+/// Map a root table without validating it
+///
+/// The table stays mapped for the lifetime of the [`RootTable`].
+///
+/// # Panics
+///
+/// This function panics if the table cannot be mapped, or its header does not describe a
+/// valid `T`.
+unsafe fn map_table<T: ?Sized + Sdt, B: Bridge>(phys: usize, bridge: B) -> *const T {
+    match map_header(phys, bridge).and_then(|header| header.try_map_full::<T>()) {
+        Ok(table) => table.into_inner(),
+        Err(error) => panic!("acpi: {error}"),
+    }
 }
 
-unsafe fn map_table<T: ?Sized + Sdt, B: Bridge>(phys: usize, bridge: B) -> Mapped<T, B> {
-    let header = ptr::with_exposed_provenance::<Header>(bridge.map(phys, size_of::<Header>()));
-    let table = T::from_header_ptr(header);
-    Mapped::new(table, bridge)
+#[cfg(test)]
+pub(crate) mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::vec::Vec;
+
+    /// Returns the bytes of a table of type `T`, with `body` following its header
+    pub(crate) fn table<T: ?Sized + Sdt>(body: &[u8]) -> Vec<u8> {
+        let length = (size_of::<Header>() + body.len()) as u32;
+        let mut bytes = Vec::from(T::SIGNATURE.0);
+        bytes.extend_from_slice(&length.to_le_bytes());
+        bytes.resize(size_of::<Header>(), 0);
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    /// Cast the bytes of a table to `T`
+    ///
+    /// # Panics
+    ///
+    /// If the length of the table is less than `T::MIN_LENGTH` or greater than `bytes`.
+    pub(crate) fn as_table<T: ?Sized + Sdt>(bytes: &[u8]) -> &T {
+        let length = u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize;
+        assert!(T::MIN_LENGTH <= length && length <= bytes.len());
+        // SAFETY: `bytes` holds the whole table, and tables have an alignment of 1.
+        unsafe { &*T::from_header_ptr(bytes.as_ptr().cast()) }
+    }
 }
@@ -0,0 +1,479 @@
+use crate::{Error, Sdt};
+use core::mem::size_of;
+use libsa::endian::{u16_le, u32_le, u64_le};
+
+/// DMA Remapping Table
+///
+/// Describes the DMA remapping hardware units (IOMMUs) of an Intel VT-d platform and the
+/// devices behind each of them.
+#[repr(C, packed)]
+pub struct Dmar {
+    header: super::Header,
+    host_address_width: u8,
+    flags: u8,
+    reserved: [u8; 10],
+    structures: [u8],
+}
+
+bitflags::bitflags! {
+    #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+    pub struct DmarFlags : u8 {
+        /// Interrupt remapping is supported
+        const INTR_REMAP = 1 << 0;
+        /// The firmware requests that x2APIC mode is not enabled
+        const X2APIC_OPT_OUT = 1 << 1;
+        /// The firmware set up DMA protection before handing over, and the platform
+        /// supports keeping it enabled
+        const DMA_CTRL_PLATFORM_OPT_IN = 1 << 2;
+    }
+}
+
+unsafe impl Sdt for Dmar {
+    const SIGNATURE: super::Signature = super::Signature(*b"DMAR");
+    const MIN_LENGTH: usize = size_of::<super::Header>() + 12;
+
+    unsafe fn from_header_ptr(header: *const super::Header) -> *const Self {
+        super::from_header_ptr_slice_of::<u8, _>(header)
+    }
+}
+
+impl Dmar {
+    /// Returns the maximum DMA physical address width supported by the platform, in bits
+    #[inline]
+    pub fn host_address_width(&self) -> u16 {
+        self.host_address_width as u16 + 1
+    }
+
+    #[inline]
+    pub fn flags(&self) -> DmarFlags {
+        DmarFlags::from_bits_retain(self.flags)
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = Entry> + '_ {
+        let header = |bytes: &[u8]| {
+            let length = u16::from_le_bytes([bytes[2], bytes[3]]);
+            (u16::from_le_bytes([bytes[0], bytes[1]]), length as usize)
+        };
+        let structures = super::Structure::iter(&self.structures, size_of::<Header>(), header);
+        structures.filter_map(|structure| unsafe {
+            // Structures which are too short for their type are passed through as unknown.
+            let entry = match structure.r#type {
+                0 => structure.cast_unsized().map(Entry::Drhd),
+                1 => structure.cast_unsized().map(Entry::Rmrr),
+                2 => structure.cast_unsized().map(Entry::Atsr),
+                3 => structure.cast().map(Entry::Rhsa),
+                4 => structure.cast_unsized().map(Entry::Andd),
+                5 => structure.cast_unsized().map(Entry::Satc),
+                _ => None,
+            };
+            entry.or_else(|| structure.cast_unsized().map(Entry::Unknown))
+        })
+    }
+}
+
+pub enum Entry<'a> {
+    Drhd(&'a Drhd),
+    Rmrr(&'a Rmrr),
+    Atsr(&'a Atsr),
+    Rhsa(&'a Rhsa),
+    Andd(&'a Andd),
+    Satc(&'a Satc),
+    Unknown(&'a Unknown),
+}
+
+#[repr(C, packed)]
+pub struct Header {
+    pub r#type: u16_le,
+    pub length: u16_le,
+}
+
+#[repr(C, packed)]
+pub struct Unknown {
+    pub header: Header,
+    pub data: [u8],
+}
+
+/// Device Scope
+///
+/// Identifies a device, or a hierarchy of devices, within the scope of a remapping
+/// structure. The device is found by starting at `start_bus` and following the `path`
+/// through PCI-to-PCI bridges.
+#[derive(Clone, Copy, Debug)]
+pub struct DeviceScope<'a> {
+    pub r#type: DeviceScopeType,
+    pub flags: u8,
+    /// I/O APIC ID, HPET number or ACPI device number, for those types of device
+    pub enumeration_id: u8,
+    pub start_bus: u8,
+    path: &'a [u8],
+}
+
+impl DeviceScope<'_> {
+    /// Returns an iterator over the path to the device, as `(device, function)` pairs
+    pub fn path(&self) -> impl Iterator<Item = (u8, u8)> + '_ {
+        self.path.chunks_exact(2).map(|entry| (entry[0], entry[1]))
+    }
+}
+
+/// Type of a [`DeviceScope`]
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct DeviceScopeType(pub u8);
+
+impl DeviceScopeType {
+    pub const PCI_ENDPOINT: Self = Self(1);
+    /// A PCI-to-PCI bridge and all devices below it
+    pub const PCI_SUB_HIERARCHY: Self = Self(2);
+    pub const IOAPIC: Self = Self(3);
+    pub const MSI_CAPABLE_HPET: Self = Self(4);
+    pub const ACPI_NAMESPACE_DEVICE: Self = Self(5);
+}
+
+/// Returns an iterator over the device scopes in `bytes`
+fn device_scopes(bytes: &[u8]) -> impl Iterator<Item = DeviceScope<'_>> {
+    let mut offset = 0;
+    core::iter::from_fn(move || {
+        let header = bytes.get(offset..offset + 6)?;
+        let length = header[1] as usize;
+        if length < 6 {
+            return None;
+        }
+        let scope = bytes.get(offset..offset + length)?;
+        offset += length;
+        Some(DeviceScope {
+            r#type: DeviceScopeType(scope[0]),
+            flags: scope[2],
+            enumeration_id: scope[4],
+            start_bus: scope[5],
+            path: &scope[6..],
+        })
+    })
+}
+
+/// DMA Remapping Hardware Unit Definition
+#[repr(C, packed)]
+pub struct Drhd {
+    header: Header,
+    flags: u8,
+    size: u8,
+    segment: u16_le,
+    register_base_addr: u64_le,
+    device_scopes: [u8],
+}
+
+bitflags::bitflags! {
+    #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+    pub struct DrhdFlags : u8 {
+        /// The unit covers every PCI device in its segment which is not covered by another
+        /// unit, rather than only the devices in its scope
+        const INCLUDE_PCI_ALL = 1 << 0;
+    }
+}
+
+impl Drhd {
+    #[inline]
+    pub fn flags(&self) -> DrhdFlags {
+        DrhdFlags::from_bits_retain(self.flags)
+    }
+
+    /// Returns the size of the unit's register set, in 4 KiB pages
+    #[inline]
+    pub fn register_pages(&self) -> u64 {
+        1 << (self.size & 0xf)
+    }
+
+    /// Returns the PCI segment of the devices covered by the unit
+    #[inline]
+    pub fn segment(&self) -> u16 {
+        self.segment.get()
+    }
+
+    /// Returns the physical address of the unit's registers
+    #[inline]
+    pub fn register_base_addr(&self) -> u64 {
+        self.register_base_addr.get()
+    }
+
+    pub fn device_scopes(&self) -> impl Iterator<Item = DeviceScope<'_>> {
+        device_scopes(&self.device_scopes)
+    }
+}
+
+/// Reserved Memory Region Reporting
+///
+/// A memory region used by devices for DMA, which must be identity-mapped for them.
+#[repr(C, packed)]
+pub struct Rmrr {
+    header: Header,
+    reserved: u16_le,
+    segment: u16_le,
+    base_addr: u64_le,
+    limit_addr: u64_le,
+    device_scopes: [u8],
+}
+
+impl Rmrr {
+    #[inline]
+    pub fn segment(&self) -> u16 {
+        self.segment.get()
+    }
+
+    #[inline]
+    pub fn base_addr(&self) -> u64 {
+        self.base_addr.get()
+    }
+
+    /// Returns the last address of the region, inclusive
+    #[inline]
+    pub fn limit_addr(&self) -> u64 {
+        self.limit_addr.get()
+    }
+
+    pub fn device_scopes(&self) -> impl Iterator<Item = DeviceScope<'_>> {
+        device_scopes(&self.device_scopes)
+    }
+}
+
+/// Root Port ATS Capability Reporting
+#[repr(C, packed)]
+pub struct Atsr {
+    header: Header,
+    flags: u8,
+    reserved: u8,
+    segment: u16_le,
+    device_scopes: [u8],
+}
+
+bitflags::bitflags! {
+    #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+    pub struct AtsrFlags : u8 {
+        /// Every root port in the segment supports ATS, rather than only those in scope
+        const ALL_PORTS = 1 << 0;
+    }
+}
+
+impl Atsr {
+    #[inline]
+    pub fn flags(&self) -> AtsrFlags {
+        AtsrFlags::from_bits_retain(self.flags)
+    }
+
+    #[inline]
+    pub fn segment(&self) -> u16 {
+        self.segment.get()
+    }
+
+    pub fn device_scopes(&self) -> impl Iterator<Item = DeviceScope<'_>> {
+        device_scopes(&self.device_scopes)
+    }
+}
+
+/// Remapping Hardware Static Affinity
+#[repr(C, packed)]
+pub struct Rhsa {
+    header: Header,
+    reserved: u32_le,
+    register_base_addr: u64_le,
+    proximity_domain: u32_le,
+}
+
+impl Rhsa {
+    /// Returns the register base address of the [`Drhd`] this structure applies to
+    #[inline]
+    pub fn register_base_addr(&self) -> u64 {
+        self.register_base_addr.get()
+    }
+
+    #[inline]
+    pub fn proximity_domain(&self) -> u32 {
+        self.proximity_domain.get()
+    }
+}
+
+/// ACPI Name-space Device Declaration
+///
+/// Names a device in the namespace which is referred to by device scopes of type
+/// [`DeviceScopeType::ACPI_NAMESPACE_DEVICE`].
+#[repr(C, packed)]
+pub struct Andd {
+    header: Header,
+    reserved: [u8; 3],
+    acpi_device_number: u8,
+    object_name: [u8],
+}
+
+impl Andd {
+    /// Returns the number used as the enumeration ID of device scopes for the device
+    #[inline]
+    pub fn acpi_device_number(&self) -> u8 {
+        self.acpi_device_number
+    }
+
+    /// Returns the absolute path of the device
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the name is not valid UTF-8, see [`Andd::try_object_name()`]
+    /// for a non-panicking version.
+    #[inline]
+    pub fn object_name(&self) -> &str {
+        match self.try_object_name() {
+            Ok(s) => s,
+            Err(error) => panic!("acpi: DMAR: {error}"),
+        }
+    }
+
+    /// Returns the absolute path of the device
+    pub fn try_object_name(&self) -> Result<&str, Error> {
+        let len = self
+            .object_name
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(self.object_name.len());
+        Ok(core::str::from_utf8(&self.object_name[..len])?)
+    }
+}
+
+/// SoC Integrated Address Translation Cache
+#[repr(C, packed)]
+pub struct Satc {
+    header: Header,
+    flags: u8,
+    reserved: u8,
+    segment: u16_le,
+    device_scopes: [u8],
+}
+
+bitflags::bitflags! {
+    #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+    pub struct SatcFlags : u8 {
+        /// The devices in scope require ATS to function
+        const ATC_REQUIRED = 1 << 0;
+    }
+}
+
+impl Satc {
+    #[inline]
+    pub fn flags(&self) -> SatcFlags {
+        SatcFlags::from_bits_retain(self.flags)
+    }
+
+    #[inline]
+    pub fn segment(&self) -> u16 {
+        self.segment.get()
+    }
+
+    pub fn device_scopes(&self) -> impl Iterator<Item = DeviceScope<'_>> {
+        device_scopes(&self.device_scopes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::sdt::tests::{as_table, table};
+    use std::vec::Vec;
+
+    fn structure(r#type: u16, body: &[u8]) -> Vec<u8> {
+        let length = 4 + body.len() as u16;
+        [&r#type.to_le_bytes()[..], &length.to_le_bytes(), body].concat()
+    }
+
+    fn scope(r#type: u8, enumeration_id: u8, start_bus: u8, path: &[(u8, u8)]) -> Vec<u8> {
+        let length = 6 + path.len() as u8 * 2;
+        let mut bytes = [r#type, length, 0, 0, enumeration_id, start_bus].to_vec();
+        bytes.extend(
+            path.iter()
+                .flat_map(|&(device, function)| [device, function]),
+        );
+        bytes
+    }
+
+    fn dmar(structures: &[&[u8]]) -> Vec<u8> {
+        let body = [&[45, 0x05][..], &[0; 10], &structures.concat()].concat();
+        table::<Dmar>(&body)
+    }
+
+    #[test]
+    fn remapping_structures() {
+        // A unit covering an endpoint and a bridge. The third scope is too short for its
+        // header, which ends the list.
+        let scopes = [
+            scope(1, 0, 0, &[(2, 0)]),
+            scope(2, 0, 0x10, &[(0x1c, 0), (0, 1)]),
+            [1, 4, 0, 0, 0, 0].to_vec(),
+            scope(1, 0, 0, &[(3, 0)]),
+        ]
+        .concat();
+        let drhd = [
+            &[0x01, 0x02][..],
+            &1u16.to_le_bytes(),
+            &0xfed9_0000u64.to_le_bytes(),
+        ];
+        let drhd = structure(0, &[&drhd.concat()[..], &scopes].concat());
+        let rmrr = [
+            &[0; 2][..],
+            &0u16.to_le_bytes(),
+            &0x7c00_0000u64.to_le_bytes(),
+            &0x7c7f_ffffu64.to_le_bytes(),
+            &scope(1, 0, 0, &[(0x14, 0)]),
+        ];
+        let rmrr = structure(1, &rmrr.concat());
+        let bytes = dmar(&[&drhd, &rmrr]);
+        let dmar = as_table::<Dmar>(&bytes);
+        assert_eq!(dmar.host_address_width(), 46);
+        assert_eq!(
+            dmar.flags(),
+            DmarFlags::INTR_REMAP | DmarFlags::DMA_CTRL_PLATFORM_OPT_IN
+        );
+
+        let mut entries = dmar.entries();
+        let Some(Entry::Drhd(drhd)) = entries.next() else {
+            panic!("expected a DRHD");
+        };
+        assert_eq!(drhd.flags(), DrhdFlags::INCLUDE_PCI_ALL);
+        assert_eq!(drhd.register_pages(), 4);
+        assert_eq!(drhd.segment(), 1);
+        assert_eq!(drhd.register_base_addr(), 0xfed9_0000);
+        let scopes = drhd.device_scopes().collect::<Vec<_>>();
+        assert_eq!(scopes.len(), 2);
+        assert_eq!(scopes[0].r#type, DeviceScopeType::PCI_ENDPOINT);
+        assert!(scopes[0].path().eq([(2, 0)]));
+        assert_eq!(scopes[1].r#type, DeviceScopeType::PCI_SUB_HIERARCHY);
+        assert_eq!(scopes[1].start_bus, 0x10);
+        assert!(scopes[1].path().eq([(0x1c, 0), (0, 1)]));
+
+        let Some(Entry::Rmrr(rmrr)) = entries.next() else {
+            panic!("expected an RMRR");
+        };
+        assert_eq!(rmrr.segment(), 0);
+        assert_eq!(rmrr.base_addr(), 0x7c00_0000);
+        assert_eq!(rmrr.limit_addr(), 0x7c7f_ffff);
+        let scopes = rmrr.device_scopes().collect::<Vec<_>>();
+        assert_eq!(scopes.len(), 1);
+        assert!(scopes[0].path().eq([(0x14, 0)]));
+        assert!(entries.next().is_none());
+    }
+
+    #[test]
+    fn namespace_devices() {
+        let andd = |name: &[u8]| structure(4, &[&[0, 0, 0, 1][..], name].concat());
+        let bytes = dmar(&[&andd(b"\\_SB.PCI0.I2C0\0\0"), &andd(b"\\_SB.\xff")]);
+        let mut entries = as_table::<Dmar>(&bytes).entries();
+        let Some(Entry::Andd(andd)) = entries.next() else {
+            panic!("expected an ANDD");
+        };
+        assert_eq!(andd.acpi_device_number(), 1);
+        assert_eq!(andd.try_object_name(), Ok("\\_SB.PCI0.I2C0"));
+        let Some(Entry::Andd(andd)) = entries.next() else {
+            panic!("expected an ANDD");
+        };
+        assert!(matches!(andd.try_object_name(), Err(Error::Utf8(_))));
+    }
+}
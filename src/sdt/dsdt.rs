@@ -0,0 +1,139 @@
+use crate::Sdt;
+use core::ptr::addr_of;
+
+use super::{Bridge, Header, Mapped};
+
+macro_rules! definition_block {
+    ($(#[$attr:meta])* $name:ident, $signature:literal) => {
+        $(#[$attr])*
+        #[repr(C, packed)]
+        pub struct $name {
+            pub header: Header,
+            aml: [u8],
+        }
+
+        unsafe impl Sdt for $name {
+            const SIGNATURE: super::Signature = super::Signature(*$signature);
+
+            fn header(&self) -> &Header {
+                &self.header
+            }
+
+            unsafe fn from_header_ptr(header: *const Header) -> *const Self {
+                super::from_header_ptr_slice_of::<u8, _>(header)
+            }
+        }
+
+        impl $name {
+            /// Returns the AML byte code of the definition block
+            #[inline]
+            pub fn aml(&self) -> &[u8] {
+                // SAFETY: `aml` has an alignment of 1, so the reference is aligned.
+                unsafe { &*addr_of!(self.aml) }
+            }
+        }
+    };
+}
+
+definition_block! {
+    /// Differentiated System Description Table
+    ///
+    /// The DSDT is referenced by the [`Fadt`](super::fadt::Fadt) rather than the root table.
+    Dsdt, b"DSDT"
+}
+
+definition_block! {
+    /// Secondary System Description Table
+    Ssdt, b"SSDT"
+}
+
+definition_block! {
+    /// Persistent System Description Table
+    ///
+    /// PSDTs were removed in ACPI 2.0, and are treated the same as SSDTs.
+    Psdt, b"PSDT"
+}
+
+impl Dsdt {
+    /// Returns the width of integers used by all definition blocks
+    ///
+    /// This is decided by the revision of the DSDT alone, the revisions of SSDTs and PSDTs
+    /// are ignored.
+    #[inline]
+    pub fn integer_width(&self) -> IntegerWidth {
+        IntegerWidth::from_revision(self.header.revision)
+    }
+}
+
+/// Width of AML integers
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum IntegerWidth {
+    Bits32,
+    Bits64,
+}
+
+impl IntegerWidth {
+    /// Returns the integer width selected by a DSDT with revision `revision`
+    ///
+    /// Revisions below 2 use 32-bit integers, all others use 64-bit integers.
+    #[inline]
+    pub fn from_revision(revision: u8) -> IntegerWidth {
+        if revision < 2 {
+            Self::Bits32
+        } else {
+            Self::Bits64
+        }
+    }
+
+    /// Returns the number of bits in an integer
+    #[inline]
+    pub fn bits(self) -> u32 {
+        match self {
+            Self::Bits32 => 32,
+            Self::Bits64 => 64,
+        }
+    }
+
+    /// Returns the largest representable integer, also used as the value of `Ones`
+    #[inline]
+    pub fn max(self) -> u64 {
+        match self {
+            Self::Bits32 => u32::MAX as u64,
+            Self::Bits64 => u64::MAX,
+        }
+    }
+
+    /// Truncate `value` to this width
+    #[inline]
+    pub fn truncate(self, value: u64) -> u64 {
+        value & self.max()
+    }
+}
+
+/// A mapped table containing a definition block of AML byte code
+pub enum DefinitionBlock<B: Bridge> {
+    Dsdt(Mapped<Dsdt, B>),
+    Ssdt(Mapped<Ssdt, B>),
+    Psdt(Mapped<Psdt, B>),
+}
+
+impl<B: Bridge> DefinitionBlock<B> {
+    #[inline]
+    pub fn header(&self) -> &Header {
+        match self {
+            Self::Dsdt(table) => table.header(),
+            Self::Ssdt(table) => table.header(),
+            Self::Psdt(table) => table.header(),
+        }
+    }
+
+    /// Returns the AML byte code of the definition block
+    #[inline]
+    pub fn aml(&self) -> &[u8] {
+        match self {
+            Self::Dsdt(table) => table.aml(),
+            Self::Ssdt(table) => table.aml(),
+            Self::Psdt(table) => table.aml(),
+        }
+    }
+}
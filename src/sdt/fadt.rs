@@ -1,8 +1,35 @@
+use core::{
+    mem::{offset_of, size_of},
+    ptr::{self, addr_of},
+    sync::atomic::{AtomicU32, Ordering},
+};
 use libsa::endian::{u16_le, u32_le, u64_le};
 
-use crate::Sdt;
+use crate::{
+    address::{AccessSize, AddressSpace, RegisterIo, RegisterPair},
+    sdt::{Bridge, Mapped},
+    Error, GenericAddress, Sdt,
+};
+
+/// Read a field which was added in a later revision of the FADT
+///
+/// Evaluates to `None` if the `length` of the table does not cover the entire field.
+macro_rules! field {
+    ($fadt:ident, $field:ident) => {{
+        let end = offset_of!(Fadt, $field) + size_of_pointee(addr_of!($fadt.$field));
+        ($fadt.header.length as usize >= end).then(|| $fadt.$field)
+    }};
+}
+
+fn size_of_pointee<T>(_: *const T) -> usize {
+    size_of::<T>()
+}
 
 /// Fixed ACPI Description Table
+///
+/// Tables from older firmware may end before the fields added in later revisions, which
+/// begin at `reset_reg`. The accessor methods check the `length` of the table before reading
+/// these fields, and should be used instead of accessing them directly.
 #[repr(C, packed)]
 pub struct Fadt {
     pub header: super::Header,
@@ -65,6 +92,8 @@ pub struct Fadt {
 
 unsafe impl Sdt for Fadt {
     const SIGNATURE: super::Signature = super::Signature(*b"FACP");
+    // The ACPI 1.0 FADT ends after the `flags` field.
+    const MIN_LENGTH: usize = offset_of!(Fadt, reset_reg);
 
     fn header(&self) -> &super::Header {
         &self.header
@@ -80,12 +109,289 @@ impl Fadt {
         FadtFlags::from_bits_retain(self.flags.get())
     }
 
+    /// Returns the revision of the FADT as a tuple of `(major, minor)`
+    ///
+    /// The minor version was introduced in ACPI 5.1, and is `0` for older tables.
+    pub fn version(&self) -> (u8, u8) {
+        let minor = field!(self, fadt_minor_version).unwrap_or(0);
+        (self.header.revision, minor & 0xf)
+    }
+
+    /// Returns the physical address of the DSDT
+    ///
+    /// `x_dsdt` is used if it is present and non-zero.
     pub fn dsdt(&self) -> u64 {
-        if self.header.revision >= 2 {
-            self.x_dsdt.get()
-        } else {
-            self.dsdt.get() as u64
+        field!(self, x_dsdt)
+            .map(|x_dsdt| x_dsdt.get())
+            .filter(|&x_dsdt| x_dsdt != 0)
+            .unwrap_or(self.dsdt.get() as u64)
+    }
+
+    /// Returns the physical address of the FACS
+    ///
+    /// `x_firmware_ctrl` is used if it is present and non-zero. Returns `None` if there is
+    /// no FACS, as is the case on HW-reduced ACPI platforms.
+    pub fn firmware_ctrl(&self) -> Option<u64> {
+        field!(self, x_firmware_ctrl)
+            .map(|x_firmware_ctrl| x_firmware_ctrl.get())
+            .filter(|&x_firmware_ctrl| x_firmware_ctrl != 0)
+            .or_else(|| Some(self.firmware_ctrl.get() as u64).filter(|&addr| addr != 0))
+    }
+
+    /// Map the FACS
+    ///
+    /// Returns `Ok(None)` if there is no FACS, see [`Fadt::firmware_ctrl()`].
+    pub fn facs<B: Bridge>(&self, bridge: B) -> Result<Option<Mapped<Facs, B>>, Error> {
+        match self.firmware_ctrl() {
+            Some(phys) => Facs::map(phys as usize, bridge).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the preferred power management profile
+    ///
+    /// Returns `None` if the table predates ACPI 2.0 (FADT revision 3).
+    pub fn preferred_pm_profile(&self) -> Option<PreferredPmProfile> {
+        if self.header.revision < 3 {
+            return None;
+        }
+        Some(PreferredPmProfile::from_raw(self.preferred_pm_profile))
+    }
+
+    /// Returns the IA-PC boot architecture flags
+    ///
+    /// Returns `None` if the table predates ACPI 2.0 (FADT revision 3). Undefined flags are
+    /// retained.
+    pub fn iapc_boot_arch(&self) -> Option<IapcBootFlags> {
+        if self.header.revision < 3 {
+            return None;
+        }
+        let flags = u16::from_le_bytes(self.iapc_boot_arch);
+        Some(IapcBootFlags::from_bits_retain(flags))
+    }
+
+    /// Returns the ARM boot architecture flags
+    ///
+    /// Returns `None` if the table predates ACPI 5.1. Undefined flags are retained.
+    pub fn arm_boot_arch(&self) -> Option<ArmBootFlags> {
+        if self.version() < (5, 1) {
+            return None;
         }
+        let flags = u16::from_le_bytes(field!(self, arm_boot_arch)?);
+        Some(ArmBootFlags::from_bits_retain(flags))
+    }
+
+    /// Returns the Hypervisor Vendor Identity
+    ///
+    /// Returns `None` if the table predates ACPI 6.0.
+    pub fn hypervisor_vendor_identity(&self) -> Option<u64> {
+        field!(self, hypervisor_vendor_identity).map(|id| id.get())
+    }
+
+    /// Decode an extended register block, falling back to the legacy I/O port block if the
+    /// extended one is absent or zero
+    fn register(&self, x_blk: Option<[u8; 12]>, blk: u32, len: u8) -> Option<GenericAddress> {
+        x_blk
+            .map(GenericAddress::from_bytes)
+            .filter(|gas| !gas.is_null())
+            .map(|gas| match gas.bit_width {
+                // Some firmware leaves the width of the extended block empty.
+                0 => GenericAddress {
+                    bit_width: len.saturating_mul(8),
+                    ..gas
+                },
+                _ => gas,
+            })
+            .or_else(|| (blk != 0).then(|| GenericAddress::system_io(blk as u64, len)))
+    }
+
+    /// Returns the Reset Register
+    ///
+    /// Support for the reset register is indicated by [`FadtFlags::RESET_REG_SUP`].
+    #[inline]
+    pub fn reset_reg(&self) -> Option<GenericAddress> {
+        field!(self, reset_reg)
+            .map(GenericAddress::from_bytes)
+            .filter(|gas| !gas.is_null())
+    }
+
+    /// Reset the system by writing `reset_value` to the reset register
+    ///
+    /// Returns [`Error::NotSupported`] if [`FadtFlags::RESET_REG_SUP`] is not set or the table
+    /// does not contain a reset register, and [`Error::AddressSpace`] if the register is not
+    /// in system I/O, system memory or PCI configuration space. The register is always
+    /// written as a single byte.
+    ///
+    /// If this function returns `Ok`, the write was performed, but the reset may still take
+    /// some time, or fail to happen at all. Callers should wait for a short while before
+    /// falling back to another reset mechanism.
+    pub fn reset(&self, io: &impl RegisterIo) -> Result<(), Error> {
+        if !self.flags().contains(FadtFlags::RESET_REG_SUP) {
+            return Err(Error::NotSupported);
+        }
+        let (Some(reset_reg), Some(reset_value)) = (self.reset_reg(), field!(self, reset_value))
+        else {
+            return Err(Error::NotSupported);
+        };
+        match reset_reg.address_space {
+            AddressSpace::SYSTEM_IO | AddressSpace::SYSTEM_MEMORY | AddressSpace::PCI_CONFIG => {}
+            space => return Err(Error::AddressSpace(space)),
+        }
+        let reset_reg = GenericAddress {
+            bit_width: 8,
+            bit_offset: 0,
+            access_size: AccessSize::BYTE,
+            ..reset_reg
+        };
+        reset_reg.write(io, reset_value as u64)
+    }
+
+    /// Returns the PM1a Event Register Block
+    #[inline]
+    pub fn pm1a_evt_blk(&self) -> Option<GenericAddress> {
+        self.register(
+            field!(self, x_pm1a_evt_blk),
+            self.pm1a_evt_blk.get(),
+            self.pm1_evt_len,
+        )
+    }
+
+    /// Returns the PM1b Event Register Block
+    #[inline]
+    pub fn pm1b_evt_blk(&self) -> Option<GenericAddress> {
+        self.register(
+            field!(self, x_pm1b_evt_blk),
+            self.pm1b_evt_blk.get(),
+            self.pm1_evt_len,
+        )
+    }
+
+    /// Returns the PM1a Control Register Block
+    #[inline]
+    pub fn pm1a_cnt_blk(&self) -> Option<GenericAddress> {
+        self.register(
+            field!(self, x_pm1a_cnt_blk),
+            self.pm1a_cnt_blk.get(),
+            self.pm1_cnt_len,
+        )
+    }
+
+    /// Returns the PM1b Control Register Block
+    #[inline]
+    pub fn pm1b_cnt_blk(&self) -> Option<GenericAddress> {
+        self.register(
+            field!(self, x_pm1b_cnt_blk),
+            self.pm1b_cnt_blk.get(),
+            self.pm1_cnt_len,
+        )
+    }
+
+    /// Returns the PM2 Control Register Block
+    #[inline]
+    pub fn pm2_cnt_blk(&self) -> Option<GenericAddress> {
+        self.register(
+            field!(self, x_pm2_cnt_blk),
+            self.pm2_cnt_blk.get(),
+            self.pm2_cnt_len,
+        )
+    }
+
+    /// Returns the Power Management Timer Control Register Block
+    #[inline]
+    pub fn pm_tmr_blk(&self) -> Option<GenericAddress> {
+        self.register(
+            field!(self, x_pm_tmr_blk),
+            self.pm_tmr_blk.get(),
+            self.pm_tmr_len,
+        )
+    }
+
+    /// Returns the General-Purpose Event 0 Register Block
+    #[inline]
+    pub fn gpe0_blk(&self) -> Option<GenericAddress> {
+        self.register(
+            field!(self, x_gpe0_blk),
+            self.gpe0_blk.get(),
+            self.gpe0_blk_len,
+        )
+    }
+
+    /// Returns the General-Purpose Event 1 Register Block
+    #[inline]
+    pub fn gpe1_blk(&self) -> Option<GenericAddress> {
+        self.register(
+            field!(self, x_gpe1_blk),
+            self.gpe1_blk.get(),
+            self.gpe1_blk_len,
+        )
+    }
+
+    /// Returns the PM1 Status register
+    ///
+    /// This is the first half of each of the PM1a and PM1b event register blocks.
+    pub fn pm1_status(&self) -> Option<RegisterPair> {
+        Some(RegisterPair {
+            a: pm1_evt_half(self.pm1a_evt_blk()?, false),
+            b: self.pm1b_evt_blk().map(|blk| pm1_evt_half(blk, false)),
+        })
+    }
+
+    /// Returns the PM1 Enable register
+    ///
+    /// This is the second half of each of the PM1a and PM1b event register blocks.
+    pub fn pm1_enable(&self) -> Option<RegisterPair> {
+        Some(RegisterPair {
+            a: pm1_evt_half(self.pm1a_evt_blk()?, true),
+            b: self.pm1b_evt_blk().map(|blk| pm1_evt_half(blk, true)),
+        })
+    }
+
+    /// Returns the PM1 Control register
+    pub fn pm1_control(&self) -> Option<RegisterPair> {
+        Some(RegisterPair {
+            a: self.pm1a_cnt_blk()?,
+            b: self.pm1b_cnt_blk(),
+        })
+    }
+
+    /// Signal firmware that the Global Lock has been released
+    ///
+    /// This sets `GBL_RLS` in the PM1 Control register, and must be called after
+    /// [`Facs::release_global_lock()`] returns `true`.
+    pub fn signal_global_lock_release(&self, io: &impl RegisterIo) -> Result<(), Error> {
+        let pm1_control = self.pm1_control().ok_or(Error::NotSupported)?;
+        let value = Pm1Control::from_bits_retain(pm1_control.read(io)? as u16);
+        // Never write `SLP_EN`, which would put the system to sleep.
+        let value = (value | Pm1Control::GBL_RLS) - Pm1Control::SLP_EN;
+        pm1_control.write(io, value.bits() as u64)
+    }
+
+    /// Returns the Sleep Control Register used on HW-reduced ACPI systems
+    #[inline]
+    pub fn sleep_control_reg(&self) -> Option<GenericAddress> {
+        field!(self, sleep_control_reg)
+            .map(GenericAddress::from_bytes)
+            .filter(|gas| !gas.is_null())
+    }
+
+    /// Returns the Sleep Status Register used on HW-reduced ACPI systems
+    #[inline]
+    pub fn sleep_status_reg(&self) -> Option<GenericAddress> {
+        field!(self, sleep_status_reg)
+            .map(GenericAddress::from_bytes)
+            .filter(|gas| !gas.is_null())
+    }
+}
+
+/// Returns the status (first) or enable (second) half of a PM1 event register block
+fn pm1_evt_half(blk: GenericAddress, second: bool) -> GenericAddress {
+    let bit_width = blk.bit_width / 2;
+    let offset = if second { bit_width as u64 / 8 } else { 0 };
+    GenericAddress {
+        bit_width,
+        access_size: AccessSize::UNDEFINED,
+        address: blk.address + offset,
+        ..blk
     }
 }
 
@@ -120,8 +426,22 @@ bitflags::bitflags! {
     }
 }
 
+bitflags::bitflags! {
+    /// PM1 Control Register
+    #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+    pub struct Pm1Control : u16 {
+        const SCI_EN = 1 << 0;
+        const BM_RLD = 1 << 1;
+        const GBL_RLS = 1 << 2;
+        const SLP_TYP = 0b111 << 10;
+        const SLP_EN = 1 << 13;
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum PreferredPmProfile {
+    Unspecified,
     Desktop,
     Mobile,
     Workstation,
@@ -130,6 +450,25 @@ pub enum PreferredPmProfile {
     AppliancePc,
     PerformanceServer,
     Tablet,
+    Reserved(u8),
+}
+
+impl PreferredPmProfile {
+    /// Decode the `preferred_pm_profile` field of the FADT
+    pub fn from_raw(value: u8) -> PreferredPmProfile {
+        match value {
+            0 => Self::Unspecified,
+            1 => Self::Desktop,
+            2 => Self::Mobile,
+            3 => Self::Workstation,
+            4 => Self::EnterpriseServer,
+            5 => Self::SohoServer,
+            6 => Self::AppliancePc,
+            7 => Self::PerformanceServer,
+            8 => Self::Tablet,
+            value => Self::Reserved(value),
+        }
+    }
 }
 
 bitflags::bitflags! {
@@ -155,13 +494,16 @@ bitflags::bitflags! {
 }
 
 /// Firmware ACPI Control Structure
-#[repr(C, packed)]
+///
+/// The FACS is not a [`Sdt`], as its header lacks a checksum and OEM information. It is
+/// found through [`Fadt::facs()`], and is always aligned to 64 bytes in memory.
+#[repr(C)]
 pub struct Facs {
     pub signature: [u8; 4],
     pub length: u32,
-    pub hardware_signature: u64,
-    pub firmware_waking_vector: [u8; 12],
-    pub global_lock: u32,
+    pub hardware_signature: u32,
+    pub firmware_waking_vector: u32,
+    pub global_lock: AtomicU32,
     pub flags: FacsFlags,
     pub x_firmware_waking_vector: u64,
     pub version: u8,
@@ -170,6 +512,205 @@ pub struct Facs {
     pub reserved1: [u8; 24],
 }
 
+impl Facs {
+    pub const SIGNATURE: [u8; 4] = *b"FACS";
+
+    /// Alignment of the FACS in physical memory, in bytes
+    pub const ALIGN: usize = 64;
+
+    /// Map the FACS at `phys`, checking its alignment, signature and length
+    pub fn map<B: Bridge>(phys: usize, bridge: B) -> Result<Mapped<Facs, B>, Error> {
+        if phys & (Self::ALIGN - 1) != 0 {
+            return Err(Error::Misaligned { phys });
+        }
+        let size = size_of::<Facs>();
+        let virt = super::map_nonnull(phys, size, bridge)?;
+        let facs = Mapped::new(ptr::with_exposed_provenance::<Facs>(virt), bridge);
+        if facs.signature != Self::SIGNATURE {
+            return Err(Error::Signature {
+                expected: super::Signature(Self::SIGNATURE),
+                found: super::Signature(facs.signature),
+            });
+        }
+        if (facs.length as usize) < size {
+            return Err(Error::TooShort {
+                signature: super::Signature(Self::SIGNATURE),
+                length: facs.length as usize,
+                min: size,
+            });
+        }
+        Ok(facs)
+    }
+
+    /// Returns the hardware signature
+    ///
+    /// Firmware changes this value when the hardware configuration of the system changes
+    /// across a sleep state, in which case OSPM should not resume from an S4 image.
+    #[inline]
+    pub fn hardware_signature(&self) -> u32 {
+        self.hardware_signature
+    }
+
+    /// Returns the version of the FACS
+    #[inline]
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    #[inline]
+    pub fn flags(&self) -> FacsFlags {
+        self.flags
+    }
+
+    /// Returns the real mode waking vector, or `None` if it is not set
+    #[inline]
+    pub fn firmware_waking_vector(&self) -> Option<u32> {
+        Some(self.firmware_waking_vector).filter(|&vector| vector != 0)
+    }
+
+    /// Returns the extended waking vector, or `None` if it is not set
+    ///
+    /// If set, this vector is used in preference to the real mode waking vector. It only
+    /// exists in version 1 and above.
+    #[inline]
+    pub fn x_firmware_waking_vector(&self) -> Option<u64> {
+        if !self.has_x_firmware_waking_vector() {
+            return None;
+        }
+        Some(self.x_firmware_waking_vector).filter(|&vector| vector != 0)
+    }
+
+    /// Returns the flags set by OSPM, or `None` if the FACS is older than version 2
+    #[inline]
+    pub fn ospm_flags(&self) -> Option<OspmFlags> {
+        self.has_ospm_flags().then_some(self.ospm_flags)
+    }
+
+    /// Returns `true` if firmware supports waking into a 64-bit environment
+    #[inline]
+    pub fn supports_64bit_wake(&self) -> bool {
+        self.has_ospm_flags() && self.flags.contains(FacsFlags::WAKE_64BIT_SUPPORTED_F)
+    }
+
+    fn has_x_firmware_waking_vector(&self) -> bool {
+        self.version >= 1
+            && self.length as usize >= offset_of!(Facs, x_firmware_waking_vector) + size_of::<u64>()
+    }
+
+    fn has_ospm_flags(&self) -> bool {
+        self.version >= 2
+            && self.length as usize >= offset_of!(Facs, ospm_flags) + size_of::<OspmFlags>()
+    }
+
+    /// Set the real mode waking vector
+    ///
+    /// On resume from S3, firmware transfers control to `vector` in real mode, with `CS`
+    /// set to `vector >> 4` and `IP` set to `vector & 0xf`. The extended waking vector is
+    /// cleared, if present, so that firmware does not use it instead.
+    pub fn set_firmware_waking_vector(&mut self, vector: u32) {
+        self.firmware_waking_vector = vector;
+        if self.has_x_firmware_waking_vector() {
+            self.x_firmware_waking_vector = 0;
+        }
+        if self.has_ospm_flags() {
+            self.ospm_flags.remove(OspmFlags::WAKE_64BIT_F);
+        }
+    }
+
+    /// Set the extended waking vector
+    ///
+    /// If firmware supports it, control is transferred to `vector` in 64-bit long mode on
+    /// resume from S3, otherwise it is transferred in 32-bit protected mode with paging
+    /// disabled. The environment which was selected is returned, and the real mode waking
+    /// vector is cleared.
+    ///
+    /// Returns [`Error::NotSupported`] if the FACS does not have an extended waking vector,
+    /// or if 64-bit wake is not supported and `vector` does not fit in 32 bits.
+    pub fn set_x_firmware_waking_vector(&mut self, vector: u64) -> Result<WakeMode, Error> {
+        if !self.has_x_firmware_waking_vector() {
+            return Err(Error::NotSupported);
+        }
+        let mode = if self.supports_64bit_wake() {
+            WakeMode::Long64
+        } else if vector <= u32::MAX as u64 {
+            WakeMode::Protected32
+        } else {
+            return Err(Error::NotSupported);
+        };
+        self.firmware_waking_vector = 0;
+        self.x_firmware_waking_vector = vector;
+        if self.has_ospm_flags() {
+            self.ospm_flags
+                .set(OspmFlags::WAKE_64BIT_F, mode == WakeMode::Long64);
+        }
+        Ok(mode)
+    }
+
+    /// Attempt to acquire the Global Lock
+    ///
+    /// Returns `true` if the lock was acquired. Otherwise, the lock is owned by firmware and
+    /// the pending bit has been set, so firmware will raise an SCI with `GBL_STS` set when it
+    /// releases the lock, after which acquisition should be retried.
+    pub fn try_acquire_global_lock(&self) -> bool {
+        let result = self
+            .global_lock
+            .fetch_update(Ordering::Acquire, Ordering::Relaxed, |lock| {
+                let lock = GlobalLock::from_bits_retain(lock);
+                let mut new = (lock - GlobalLock::PENDING) | GlobalLock::OWNED;
+                if lock.contains(GlobalLock::OWNED) {
+                    new |= GlobalLock::PENDING;
+                }
+                Some(new.bits())
+            });
+        // `fetch_update()` always succeeds here, since the closure never returns `None`.
+        let old = GlobalLock::from_bits_retain(result.unwrap_or_else(|lock| lock));
+        !old.contains(GlobalLock::OWNED)
+    }
+
+    /// Acquire the Global Lock, calling `wait` while it is owned by firmware
+    ///
+    /// `wait` should block until firmware signals that it has released the lock, or until
+    /// a timeout expires, see [`Facs::try_acquire_global_lock()`].
+    pub fn acquire_global_lock(&self, mut wait: impl FnMut()) {
+        while !self.try_acquire_global_lock() {
+            wait();
+        }
+    }
+
+    /// Release the Global Lock
+    ///
+    /// Returns `true` if firmware is waiting for the lock, in which case OSPM must signal its
+    /// release by setting `GBL_RLS`, see [`Fadt::signal_global_lock_release()`].
+    pub fn release_global_lock(&self) -> bool {
+        let old = self.global_lock.fetch_and(
+            !(GlobalLock::PENDING | GlobalLock::OWNED).bits(),
+            Ordering::Release,
+        );
+        GlobalLock::from_bits_retain(old).contains(GlobalLock::PENDING)
+    }
+}
+
+/// Execution environment entered by firmware when jumping to the extended waking vector
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum WakeMode {
+    /// 32-bit protected mode with paging disabled
+    Protected32,
+    /// 64-bit long mode with an identity mapping of the waking vector
+    Long64,
+}
+
+bitflags::bitflags! {
+    /// Global Lock
+    #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+    pub struct GlobalLock : u32 {
+        /// Set by a party waiting for the lock, which must be signaled when it is released
+        const PENDING = 1 << 0;
+        /// Set when the lock is owned by OSPM or firmware
+        const OWNED = 1 << 1;
+    }
+}
+
 bitflags::bitflags! {
     #[repr(transparent)]
     #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
@@ -184,3 +725,191 @@ bitflags::bitflags! {
         const WAKE_64BIT_F = 1 << 0;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        address::tests::{Access, RecordingIo},
+        sdt::Header,
+    };
+    use core::mem::offset_of;
+
+    const IO: AddressSpace = AddressSpace::SYSTEM_IO;
+
+    /// Returns a revision 6 FADT with PM1a and PM1b register blocks in system I/O space
+    fn fadt() -> [u8; size_of::<Fadt>()] {
+        let mut bytes = [0; size_of::<Fadt>()];
+        // SAFETY: `Fadt` only contains integers, and has an alignment of 1.
+        let fadt = unsafe { &mut *bytes.as_mut_ptr().cast::<Fadt>() };
+        fadt.header.length = size_of::<Fadt>() as u32;
+        fadt.header.revision = 6;
+        fadt.pm1_evt_len = 4;
+        fadt.pm1_cnt_len = 2;
+        fadt.x_pm1a_evt_blk = GenericAddress::system_io(0x400, 4).to_bytes();
+        fadt.x_pm1b_evt_blk = GenericAddress::system_io(0x500, 4).to_bytes();
+        fadt.x_pm1a_cnt_blk = GenericAddress::system_io(0x404, 2).to_bytes();
+        fadt.x_pm1b_cnt_blk = GenericAddress::system_io(0x504, 2).to_bytes();
+        bytes
+    }
+
+    fn as_fadt(bytes: &[u8; size_of::<Fadt>()]) -> &Fadt {
+        // SAFETY: See `fadt()`.
+        unsafe { &*bytes.as_ptr().cast::<Fadt>() }
+    }
+
+    #[test]
+    fn preferred_pm_profile() {
+        let mut bytes = fadt();
+        for (revision, raw, profile) in [
+            (2, 2, None),
+            (3, 0, Some(PreferredPmProfile::Unspecified)),
+            (6, 2, Some(PreferredPmProfile::Mobile)),
+            (6, 8, Some(PreferredPmProfile::Tablet)),
+            (6, 9, Some(PreferredPmProfile::Reserved(9))),
+        ] {
+            bytes[offset_of!(Header, revision)] = revision;
+            bytes[offset_of!(Fadt, preferred_pm_profile)] = raw;
+            assert_eq!(as_fadt(&bytes).preferred_pm_profile(), profile);
+        }
+    }
+
+    #[test]
+    fn pm1_status() {
+        let bytes = fadt();
+        let status = as_fadt(&bytes).pm1_status().unwrap();
+        let io = RecordingIo::default();
+        io.set(IO, 0x400, &[0x01, 0x00, 0xff, 0xff]);
+        io.set(IO, 0x500, &[0x00, 0x01, 0xff, 0xff]);
+
+        assert_eq!(status.read(&io), Ok(0x0101));
+        assert_eq!(io.take_accesses(), [
+            Access::Read(IO, 0x400, AccessSize::WORD),
+            Access::Read(IO, 0x500, AccessSize::WORD),
+        ]);
+
+        // Status bits are cleared by writing ones.
+        status.write(&io, 0x0001).unwrap();
+        assert_eq!(io.take_accesses(), [
+            Access::Write(IO, 0x400, AccessSize::WORD, 0x0001),
+            Access::Write(IO, 0x500, AccessSize::WORD, 0x0001),
+        ]);
+    }
+
+    #[test]
+    fn pm1_enable() {
+        let bytes = fadt();
+        let enable = as_fadt(&bytes).pm1_enable().unwrap();
+        let io = RecordingIo::default();
+        io.set(IO, 0x400, &[0xff, 0xff, 0x20, 0x00]);
+        io.set(IO, 0x500, &[0xff, 0xff, 0x00, 0x01]);
+
+        assert_eq!(enable.read(&io), Ok(0x0120));
+        enable.write(&io, 0x0020).unwrap();
+        assert_eq!(io.take_accesses(), [
+            Access::Read(IO, 0x402, AccessSize::WORD),
+            Access::Read(IO, 0x502, AccessSize::WORD),
+            Access::Write(IO, 0x402, AccessSize::WORD, 0x0020),
+            Access::Write(IO, 0x502, AccessSize::WORD, 0x0020),
+        ]);
+        // The status halves are untouched.
+        assert_eq!(io.get(IO, 0x400, 2), [0xff, 0xff]);
+        assert_eq!(io.get(IO, 0x500, 2), [0xff, 0xff]);
+    }
+
+    #[test]
+    fn pm1_control() {
+        let mut bytes = fadt();
+        let io = RecordingIo::default();
+        io.set(IO, 0x404, &[0x01, 0x20]);
+
+        // `SLP_EN` must not be written back when setting `GBL_RLS`.
+        let fadt = as_fadt(&bytes);
+        fadt.signal_global_lock_release(&io).unwrap();
+        assert_eq!(io.take_accesses(), [
+            Access::Read(IO, 0x404, AccessSize::WORD),
+            Access::Read(IO, 0x504, AccessSize::WORD),
+            Access::Write(IO, 0x404, AccessSize::WORD, 0x0005),
+            Access::Write(IO, 0x504, AccessSize::WORD, 0x0005),
+        ]);
+
+        // Without a PM1b block, only PM1a is accessed.
+        bytes[offset_of!(Fadt, x_pm1b_cnt_blk)..][..12].fill(0);
+        let control = as_fadt(&bytes).pm1_control().unwrap();
+        assert_eq!(control.b, None);
+        control.write(&io, 0x0001).unwrap();
+        assert_eq!(io.take_accesses(), [Access::Write(
+            IO,
+            0x404,
+            AccessSize::WORD,
+            0x0001
+        )]);
+    }
+
+    fn facs(global_lock: GlobalLock) -> Facs {
+        Facs {
+            signature: Facs::SIGNATURE,
+            length: size_of::<Facs>() as u32,
+            hardware_signature: 0,
+            firmware_waking_vector: 0,
+            global_lock: AtomicU32::new(global_lock.bits()),
+            flags: FacsFlags::empty(),
+            x_firmware_waking_vector: 0,
+            version: 2,
+            reserved0: [0; 3],
+            ospm_flags: OspmFlags::empty(),
+            reserved1: [0; 24],
+        }
+    }
+
+    fn global_lock(facs: &Facs) -> GlobalLock {
+        GlobalLock::from_bits_retain(facs.global_lock.load(Ordering::Relaxed))
+    }
+
+    #[test]
+    fn acquire_global_lock() {
+        let facs = facs(GlobalLock::empty());
+        assert!(facs.try_acquire_global_lock());
+        assert_eq!(global_lock(&facs), GlobalLock::OWNED);
+
+        assert!(!facs.release_global_lock());
+        assert_eq!(global_lock(&facs), GlobalLock::empty());
+
+        // A stale pending bit is cleared by an uncontended acquisition.
+        facs.global_lock
+            .store(GlobalLock::PENDING.bits(), Ordering::Relaxed);
+        facs.acquire_global_lock(|| panic!("lock is free"));
+        assert_eq!(global_lock(&facs), GlobalLock::OWNED);
+    }
+
+    #[test]
+    fn contended_global_lock() {
+        let facs = facs(GlobalLock::OWNED);
+        assert!(!facs.try_acquire_global_lock());
+        assert_eq!(global_lock(&facs), GlobalLock::OWNED | GlobalLock::PENDING);
+
+        // Firmware releases the lock and signals the waiter, which then acquires it.
+        let mut waits = 0;
+        facs.acquire_global_lock(|| {
+            waits += 1;
+            facs.global_lock.store(0, Ordering::Relaxed);
+        });
+        assert_eq!(waits, 1);
+        assert_eq!(global_lock(&facs), GlobalLock::OWNED);
+    }
+
+    #[test]
+    fn release_global_lock() {
+        let facs = facs(GlobalLock::OWNED);
+        assert!(!facs.release_global_lock());
+        assert_eq!(global_lock(&facs), GlobalLock::empty());
+
+        // Firmware is waiting for the lock, and must be signaled.
+        facs.global_lock.store(
+            (GlobalLock::OWNED | GlobalLock::PENDING).bits(),
+            Ordering::Relaxed,
+        );
+        assert!(facs.release_global_lock());
+        assert_eq!(global_lock(&facs), GlobalLock::empty());
+    }
+}
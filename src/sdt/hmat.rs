@@ -0,0 +1,445 @@
+use crate::Sdt;
+use core::mem::size_of;
+use libsa::endian::{u16_le, u32_le, u64_le};
+
+/// Heterogeneous Memory Attribute Table
+///
+/// Describes the performance of memory attached to each proximity domain, as seen from the
+/// initiators in other proximity domains.
+#[repr(C, packed)]
+pub struct Hmat {
+    header: super::Header,
+    reserved: u32_le,
+    structures: [u8],
+}
+
+unsafe impl Sdt for Hmat {
+    const SIGNATURE: super::Signature = super::Signature(*b"HMAT");
+    const MIN_LENGTH: usize = size_of::<super::Header>() + 4;
+
+    unsafe fn from_header_ptr(header: *const super::Header) -> *const Self {
+        super::from_header_ptr_slice_of::<u8, _>(header)
+    }
+}
+
+impl Hmat {
+    pub fn entries(&self) -> impl Iterator<Item = Entry> + '_ {
+        let header = |bytes: &[u8]| {
+            let length = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+            (u16::from_le_bytes([bytes[0], bytes[1]]), length as usize)
+        };
+        let structures = super::Structure::iter(&self.structures, size_of::<Header>(), header);
+        structures.filter_map(|structure| unsafe {
+            // Structures which are too short for their type are passed through as unknown.
+            let entry = match structure.r#type {
+                0 => structure.cast().map(Entry::MemoryProximityDomain),
+                1 => structure.cast_unsized().map(Entry::SystemLocality),
+                2 => structure.cast_unsized().map(Entry::MemorySideCache),
+                _ => None,
+            };
+            entry.or_else(|| structure.cast_unsized().map(Entry::Unknown))
+        })
+    }
+}
+
+pub enum Entry<'a> {
+    MemoryProximityDomain(&'a MemoryProximityDomain),
+    SystemLocality(&'a SystemLocality),
+    MemorySideCache(&'a MemorySideCache),
+    Unknown(&'a Unknown),
+}
+
+#[repr(C, packed)]
+pub struct Header {
+    pub r#type: u16_le,
+    reserved: u16_le,
+    pub length: u32_le,
+}
+
+#[repr(C, packed)]
+pub struct Unknown {
+    pub header: Header,
+    pub data: [u8],
+}
+
+/// Memory Proximity Domain Attributes
+#[repr(C, packed)]
+pub struct MemoryProximityDomain {
+    header: Header,
+    flags: u16_le,
+    reserved0: u16_le,
+    initiator_proximity_domain: u32_le,
+    memory_proximity_domain: u32_le,
+    reserved1: [u8; 20],
+}
+
+bitflags::bitflags! {
+    #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+    pub struct MemoryProximityDomainFlags : u16 {
+        /// The initiator proximity domain field is valid
+        const INITIATOR_VALID = 1 << 0;
+    }
+}
+
+impl MemoryProximityDomain {
+    #[inline]
+    pub fn flags(&self) -> MemoryProximityDomainFlags {
+        MemoryProximityDomainFlags::from_bits_retain(self.flags.get())
+    }
+
+    /// Returns the proximity domain of the initiator attached to the memory, such as the
+    /// processor containing its memory controller
+    pub fn initiator_proximity_domain(&self) -> Option<u32> {
+        self.flags()
+            .contains(MemoryProximityDomainFlags::INITIATOR_VALID)
+            .then(|| self.initiator_proximity_domain.get())
+    }
+
+    #[inline]
+    pub fn memory_proximity_domain(&self) -> u32 {
+        self.memory_proximity_domain.get()
+    }
+}
+
+/// System Locality Latency and Bandwidth Information
+///
+/// Holds a matrix of latencies or bandwidths, with a row for each initiator proximity
+/// domain and a column for each target proximity domain.
+#[repr(C, packed)]
+pub struct SystemLocality {
+    header: Header,
+    flags: u8,
+    data_type: u8,
+    min_transfer_size: u8,
+    reserved0: u8,
+    initiator_count: u32_le,
+    target_count: u32_le,
+    reserved1: u32_le,
+    entry_base_unit: u64_le,
+    data: [u8],
+}
+
+/// Level of the memory hierarchy described by a [`SystemLocality`] structure
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct MemoryHierarchy(pub u8);
+
+impl MemoryHierarchy {
+    pub const MEMORY: Self = Self(0);
+    pub const FIRST_LEVEL_CACHE: Self = Self(1);
+    pub const SECOND_LEVEL_CACHE: Self = Self(2);
+    pub const THIRD_LEVEL_CACHE: Self = Self(3);
+}
+
+/// Type of the entries of a [`SystemLocality`] structure
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct LocalityDataType(pub u8);
+
+impl LocalityDataType {
+    pub const ACCESS_LATENCY: Self = Self(0);
+    pub const READ_LATENCY: Self = Self(1);
+    pub const WRITE_LATENCY: Self = Self(2);
+    pub const ACCESS_BANDWIDTH: Self = Self(3);
+    pub const READ_BANDWIDTH: Self = Self(4);
+    pub const WRITE_BANDWIDTH: Self = Self(5);
+
+    #[inline]
+    pub const fn is_latency(self) -> bool {
+        self.0 <= 2
+    }
+
+    #[inline]
+    pub const fn is_bandwidth(self) -> bool {
+        3 <= self.0 && self.0 <= 5
+    }
+}
+
+/// Performance between an initiator and a target, decoded from a [`SystemLocality`] entry
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Performance {
+    /// Latency in picoseconds
+    Latency(u64),
+    /// Bandwidth in megabytes per second
+    Bandwidth(u64),
+    /// The target cannot be reached from the initiator
+    Unreachable,
+}
+
+impl SystemLocality {
+    const UNREACHABLE: u16 = 0xffff;
+
+    /// Returns the level of the memory hierarchy the entries describe
+    #[inline]
+    pub fn memory_hierarchy(&self) -> MemoryHierarchy {
+        MemoryHierarchy(self.flags & 0xf)
+    }
+
+    /// Returns the access attributes, in bits 4 and 5 of the flags
+    #[inline]
+    pub fn access_attributes(&self) -> u8 {
+        self.flags >> 4 & 0x3
+    }
+
+    #[inline]
+    pub fn data_type(&self) -> LocalityDataType {
+        LocalityDataType(self.data_type)
+    }
+
+    /// Returns the minimum transfer size of the measurements, in bytes
+    #[inline]
+    pub fn min_transfer_size(&self) -> u8 {
+        self.min_transfer_size
+    }
+
+    /// Returns the unit of each entry, in picoseconds or megabytes per second
+    #[inline]
+    pub fn entry_base_unit(&self) -> u64 {
+        self.entry_base_unit.get()
+    }
+
+    /// Returns the number of initiators and targets, or `(0, 0)` if the structure is too
+    /// short to hold their lists and the entry matrix
+    fn counts(&self) -> (usize, usize) {
+        let initiators = self.initiator_count.get() as usize;
+        let targets = self.target_count.get() as usize;
+        let size = initiators
+            .checked_add(targets)
+            .and_then(|n| n.checked_mul(4))
+            .zip(initiators.checked_mul(targets))
+            .and_then(|(lists, entries)| lists.checked_add(entries.checked_mul(2)?));
+        match size {
+            Some(size) if size <= self.data.len() => (initiators, targets),
+            _ => (0, 0),
+        }
+    }
+
+    fn u32_at(&self, index: usize) -> u32 {
+        let bytes = &self.data[index * 4..index * 4 + 4];
+        u32::from_le_bytes(bytes.try_into().unwrap())
+    }
+
+    /// Returns an iterator over the initiator proximity domains
+    pub fn initiators(&self) -> impl Iterator<Item = u32> + '_ {
+        let (initiators, _) = self.counts();
+        (0..initiators).map(|i| self.u32_at(i))
+    }
+
+    /// Returns an iterator over the target proximity domains
+    pub fn targets(&self) -> impl Iterator<Item = u32> + '_ {
+        let (initiators, targets) = self.counts();
+        (initiators..initiators + targets).map(|i| self.u32_at(i))
+    }
+
+    /// Returns the raw entry for the `i`th initiator and `t`th target
+    pub fn entry(&self, i: usize, t: usize) -> Option<u16> {
+        let (initiators, targets) = self.counts();
+        if i >= initiators || t >= targets {
+            return None;
+        }
+        let offset = (initiators + targets) * 4 + (i * targets + t) * 2;
+        Some(u16::from_le_bytes([
+            self.data[offset],
+            self.data[offset + 1],
+        ]))
+    }
+
+    /// Returns the performance of memory in proximity domain `target` when accessed from
+    /// proximity domain `initiator`
+    ///
+    /// Returns `None` if either domain is not in the structure, the entry is not provided,
+    /// or the data type is unknown.
+    pub fn performance(&self, initiator: u32, target: u32) -> Option<Performance> {
+        let i = self.initiators().position(|pd| pd == initiator)?;
+        let t = self.targets().position(|pd| pd == target)?;
+        let value = match self.entry(i, t)? {
+            0 => return None,
+            Self::UNREACHABLE => return Some(Performance::Unreachable),
+            entry => (entry as u64).saturating_mul(self.entry_base_unit()),
+        };
+        let data_type = self.data_type();
+        if data_type.is_latency() {
+            Some(Performance::Latency(value))
+        } else if data_type.is_bandwidth() {
+            Some(Performance::Bandwidth(value))
+        } else {
+            None
+        }
+    }
+}
+
+/// Memory Side Cache Information
+#[repr(C, packed)]
+pub struct MemorySideCache {
+    header: Header,
+    memory_proximity_domain: u32_le,
+    reserved0: u32_le,
+    size: u64_le,
+    attributes: u32_le,
+    reserved1: u16_le,
+    smbios_handle_count: u16_le,
+    smbios_handles: [u8],
+}
+
+/// Attributes of a [`MemorySideCache`]
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct CacheAttributes(pub u32);
+
+impl CacheAttributes {
+    pub const ASSOCIATIVITY_NONE: u8 = 0;
+    pub const DIRECT_MAPPED: u8 = 1;
+    pub const COMPLEX_CACHE_INDEXING: u8 = 2;
+
+    pub const WRITE_POLICY_NONE: u8 = 0;
+    pub const WRITE_BACK: u8 = 1;
+    pub const WRITE_THROUGH: u8 = 2;
+
+    /// Returns the number of cache levels in front of the memory
+    #[inline]
+    pub const fn total_levels(self) -> u8 {
+        (self.0 & 0xf) as u8
+    }
+
+    /// Returns the level of the cache described by the structure
+    #[inline]
+    pub const fn level(self) -> u8 {
+        (self.0 >> 4 & 0xf) as u8
+    }
+
+    #[inline]
+    pub const fn associativity(self) -> u8 {
+        (self.0 >> 8 & 0xf) as u8
+    }
+
+    #[inline]
+    pub const fn write_policy(self) -> u8 {
+        (self.0 >> 12 & 0xf) as u8
+    }
+
+    /// Returns the size of a cache line in bytes
+    #[inline]
+    pub const fn line_size(self) -> u16 {
+        (self.0 >> 16) as u16
+    }
+}
+
+impl MemorySideCache {
+    #[inline]
+    pub fn memory_proximity_domain(&self) -> u32 {
+        self.memory_proximity_domain.get()
+    }
+
+    /// Returns the size of the cache in bytes
+    #[inline]
+    pub fn size(&self) -> u64 {
+        self.size.get()
+    }
+
+    #[inline]
+    pub fn attributes(&self) -> CacheAttributes {
+        CacheAttributes(self.attributes.get())
+    }
+
+    /// Returns an iterator over the handles of the SMBIOS Type 17 structures describing the
+    /// physical memory devices backing the cache
+    pub fn smbios_handles(&self) -> impl Iterator<Item = u16> + '_ {
+        let count = self.smbios_handle_count.get() as usize;
+        self.smbios_handles
+            .chunks_exact(2)
+            .take(count)
+            .map(|handle| u16::from_le_bytes([handle[0], handle[1]]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::sdt::tests::{as_table, table};
+    use std::vec::Vec;
+
+    /// Returns a System Locality structure holding `data` after its fixed fields
+    fn locality(data_type: u8, counts: (u32, u32), entry_base_unit: u64, data: &[u8]) -> Vec<u8> {
+        let length = 32 + data.len() as u32;
+        let mut bytes = [1, 0, 0, 0].to_vec();
+        bytes.extend_from_slice(&length.to_le_bytes());
+        bytes.extend_from_slice(&[0, data_type, 0, 0]);
+        bytes.extend_from_slice(&counts.0.to_le_bytes());
+        bytes.extend_from_slice(&counts.1.to_le_bytes());
+        bytes.extend_from_slice(&[0; 4]);
+        bytes.extend_from_slice(&entry_base_unit.to_le_bytes());
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    /// Returns the initiator and target lists followed by the entry matrix
+    fn matrix(initiators: &[u32], targets: &[u32], entries: &[u16]) -> Vec<u8> {
+        let domains = initiators
+            .iter()
+            .chain(targets)
+            .flat_map(|pd| pd.to_le_bytes());
+        let entries = entries.iter().flat_map(|entry| entry.to_le_bytes());
+        domains.chain(entries).collect()
+    }
+
+    fn hmat(structures: &[&[u8]]) -> Vec<u8> {
+        table::<Hmat>(&[&[0; 4][..], &structures.concat()].concat())
+    }
+
+    fn system_locality(hmat: &Hmat) -> &SystemLocality {
+        match hmat.entries().next() {
+            Some(Entry::SystemLocality(locality)) => locality,
+            _ => panic!("not a System Locality structure"),
+        }
+    }
+
+    #[test]
+    fn latency() {
+        let data = matrix(&[0, 1], &[0, 2], &[10, 0, 0xffff, 20]);
+        let bytes = hmat(&[&locality(0, (2, 2), 100, &data)]);
+        let locality = system_locality(as_table(&bytes));
+        assert_eq!(locality.data_type(), LocalityDataType::ACCESS_LATENCY);
+        assert_eq!(locality.entry_base_unit(), 100);
+        assert!(locality.initiators().eq([0, 1]));
+        assert!(locality.targets().eq([0, 2]));
+        assert_eq!(locality.entry(1, 1), Some(20));
+        assert_eq!(locality.entry(2, 0), None);
+        assert_eq!(locality.performance(0, 0), Some(Performance::Latency(1000)));
+        assert_eq!(locality.performance(0, 2), None);
+        assert_eq!(locality.performance(1, 0), Some(Performance::Unreachable));
+        assert_eq!(locality.performance(1, 2), Some(Performance::Latency(2000)));
+        assert_eq!(locality.performance(2, 0), None);
+        assert_eq!(locality.performance(0, 1), None);
+    }
+
+    #[test]
+    fn bandwidth() {
+        let data = matrix(&[3], &[4, 5], &[5, 0xffff]);
+        let bytes = hmat(&[&locality(3, (1, 2), 1000, &data)]);
+        let locality = system_locality(as_table(&bytes));
+        assert!(locality.data_type().is_bandwidth());
+        assert_eq!(
+            locality.performance(3, 4),
+            Some(Performance::Bandwidth(5000))
+        );
+        assert_eq!(locality.performance(3, 5), Some(Performance::Unreachable));
+        assert_eq!(locality.performance(4, 3), None);
+    }
+
+    #[test]
+    fn undersized() {
+        // The matrix is missing its last entry.
+        let data = matrix(&[0, 1], &[0, 1], &[10, 20, 20]);
+        for counts in [(2, 2), (2, u32::MAX), (u32::MAX, u32::MAX)] {
+            let bytes = hmat(&[&locality(0, counts, 100, &data)]);
+            let locality = system_locality(as_table(&bytes));
+            assert_eq!(locality.initiators().count(), 0);
+            assert_eq!(locality.targets().count(), 0);
+            assert_eq!(locality.entry(0, 0), None);
+            assert_eq!(locality.performance(0, 0), None);
+        }
+    }
+}
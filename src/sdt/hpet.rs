@@ -0,0 +1,118 @@
+use crate::{GenericAddress, Sdt};
+use core::mem::size_of;
+use libsa::endian::{u16_le, u32_le};
+
+/// High Precision Event Timer Table
+///
+/// Describes one HPET block. Platforms with several blocks have one table for each of them,
+/// distinguished by [`Hpet::hpet_number`].
+#[repr(C, packed)]
+pub struct Hpet {
+    pub header: super::Header,
+    event_timer_block_id: u32_le,
+    base_address: [u8; 12],
+    /// Sequence number of this HPET block
+    pub hpet_number: u8,
+    min_clock_tick: u16_le,
+    page_protection: u8,
+}
+
+unsafe impl Sdt for Hpet {
+    const SIGNATURE: super::Signature = super::Signature(*b"HPET");
+    const MIN_LENGTH: usize = size_of::<Hpet>();
+
+    fn header(&self) -> &super::Header {
+        &self.header
+    }
+
+    unsafe fn from_header_ptr(ptr: *const super::Header) -> *const Self {
+        ptr.cast()
+    }
+}
+
+impl Hpet {
+    /// Returns the ID of the event timer block, a copy of the low half of its capabilities
+    /// register
+    #[inline]
+    pub fn event_timer_block_id(&self) -> EventTimerBlockId {
+        EventTimerBlockId(self.event_timer_block_id.get())
+    }
+
+    /// Returns the address of the event timer block's registers
+    #[inline]
+    pub fn base_address(&self) -> GenericAddress {
+        GenericAddress::from_bytes(self.base_address)
+    }
+
+    /// Returns the minimum number of ticks the main counter may be set to in periodic mode
+    /// without losing interrupts
+    #[inline]
+    pub fn min_clock_tick(&self) -> u16 {
+        self.min_clock_tick.get()
+    }
+
+    /// Returns the page protection of the event timer block
+    #[inline]
+    pub fn page_protection(&self) -> PageProtection {
+        PageProtection(self.page_protection & 0xf)
+    }
+
+    /// Returns the OEM attributes in the upper bits of the page protection field
+    #[inline]
+    pub fn oem_attribute(&self) -> u8 {
+        self.page_protection >> 4
+    }
+}
+
+/// Event Timer Block ID
+///
+/// This is also the low half of the General Capabilities and ID register.
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct EventTimerBlockId(pub u32);
+
+impl EventTimerBlockId {
+    #[inline]
+    pub const fn hardware_revision(self) -> u8 {
+        self.0 as u8
+    }
+
+    /// Returns the number of comparators in the block
+    #[inline]
+    pub const fn comparators(self) -> u8 {
+        (self.0 >> 8 & 0x1f) as u8 + 1
+    }
+
+    /// Returns `true` if the main counter is 64 bits wide
+    #[inline]
+    pub const fn is_64bit(self) -> bool {
+        self.0 & 1 << 13 != 0
+    }
+
+    /// Returns `true` if the block can replace the legacy PIT and RTC interrupts
+    #[inline]
+    pub const fn legacy_replacement(self) -> bool {
+        self.0 & 1 << 15 != 0
+    }
+
+    #[inline]
+    pub const fn pci_vendor_id(self) -> u16 {
+        (self.0 >> 16) as u16
+    }
+}
+
+/// Page protection of an event timer block
+///
+/// Indicates whether the block's registers may be mapped without exposing other registers
+/// in the same page.
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct PageProtection(pub u8);
+
+impl PageProtection {
+    pub const NONE: Self = Self(0);
+    /// No other registers are in the 4 KiB page containing the block
+    pub const PROTECTED_4K: Self = Self(1);
+    /// No other registers are in the 64 KiB page containing the block
+    pub const PROTECTED_64K: Self = Self(2);
+}
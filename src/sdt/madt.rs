@@ -21,6 +21,7 @@ bitflags::bitflags! {
 
 unsafe impl Sdt for Madt {
     const SIGNATURE: super::Signature = super::Signature(*b"APIC");
+    const MIN_LENGTH: usize = size_of::<super::Header>() + 8;
 
     unsafe fn from_header_ptr(header: *const super::Header) -> *const Self {
         super::from_header_ptr_slice_of::<u8, _>(header)
@@ -46,6 +47,9 @@ impl Madt {
                 .get(offset..offset + size_of::<Header>())?
                 .as_ptr()
                 .cast::<Header>();
+            if (header.total_size as usize) < size_of::<Header>() {
+                return None;
+            }
             let bytes = self.ics.get(offset..offset + header.total_size as usize)?;
             offset += header.total_size as usize;
 
@@ -207,6 +211,25 @@ pub struct InterruptSourceFlags(u16);
 impl InterruptSourceFlags {
     pub const POLARITY_MASK: u16 = 0x0003;
     pub const TRIGGER_MASK: u16 = 0x000c;
+
+    pub const POLARITY_CONFORMS: u16 = 0x0000;
+    pub const ACTIVE_HIGH: u16 = 0x0001;
+    pub const ACTIVE_LOW: u16 = 0x0003;
+    pub const TRIGGER_CONFORMS: u16 = 0x0000;
+    pub const EDGE_TRIGGERED: u16 = 0x0004;
+    pub const LEVEL_TRIGGERED: u16 = 0x000c;
+
+    /// Returns the polarity bits, such as [`Self::ACTIVE_LOW`]
+    #[inline]
+    pub const fn polarity(self) -> u16 {
+        self.0 & Self::POLARITY_MASK
+    }
+
+    /// Returns the trigger mode bits, such as [`Self::LEVEL_TRIGGERED`]
+    #[inline]
+    pub const fn trigger(self) -> u16 {
+        self.0 & Self::TRIGGER_MASK
+    }
 }
 
 impl InterruptSourceOverride {
@@ -1,5 +1,5 @@
 use crate::Sdt;
-use core::ptr::addr_of;
+use core::{mem::size_of, ptr::addr_of};
 
 #[repr(C, packed)]
 pub struct Mcfg {
@@ -10,6 +10,7 @@ pub struct Mcfg {
 
 unsafe impl Sdt for Mcfg {
     const SIGNATURE: super::Signature = super::Signature(*b"MCFG");
+    const MIN_LENGTH: usize = size_of::<super::Header>() + 8;
 
     fn header(&self) -> &super::Header {
         &self.header
@@ -1,6 +1,6 @@
 //! RISC-V Hart Capabilities Table
 
-use crate::size_of_unsized;
+use crate::{size_of_unsized, Error};
 use core::{
     mem::size_of,
     ptr::{self, addr_of, Pointee},
@@ -31,6 +31,7 @@ bitflags::bitflags! {
 
 unsafe impl super::Sdt for Rhct {
     const SIGNATURE: super::Signature = super::Signature(*b"RHCT");
+    const MIN_LENGTH: usize = size_of::<super::Header>() + 20;
 
     fn header(&self) -> &super::Header {
         &self.header
@@ -69,28 +70,47 @@ impl Rhct {
         self.len() == 0
     }
 
-    fn get_node(&self, offset: usize) -> Option<*const Header> {
+    fn get_node(&self, offset: usize) -> Result<*const Header, Error> {
         // Offsets are given relative to the start of the table.
         // Relocate it relative to the start of the `nodes` array.
-        let offset = offset - size_of_unsized::<Self>();
-        let node = unsafe { &*self.nodes.get(offset..)?.as_ptr().cast::<Header>() };
+        let start = offset
+            .checked_sub(size_of_unsized::<Self>())
+            .ok_or(Error::OutOfBounds { offset })?;
+        let header = self
+            .nodes
+            .get(start..start + size_of::<Header>())
+            .ok_or(Error::OutOfBounds { offset })?;
+        let node = unsafe { &*header.as_ptr().cast::<Header>() };
         // Create a pointer with provenance over all bytes of the node.
-        let bytes = self.nodes.get(offset..offset + node.len())?;
-        Some(bytes.as_ptr().cast::<Header>())
+        let bytes = self
+            .nodes
+            .get(start..start + node.len())
+            .ok_or(Error::Truncated {
+                offset,
+                len: node.len(),
+            })?;
+        Ok(bytes.as_ptr().cast::<Header>())
     }
 
     /// Returns an iterator over all `HartInfo` nodes
+    ///
+    /// Iteration stops at the first malformed node.
     pub fn nodes(&self) -> impl Iterator<Item = &HartInfo> + '_ {
         let mut offset = self.nodes_offset.get() as usize;
-        (0..self.nodes_len.get()).filter_map(move |_| unsafe {
-            let header = self.get_node(offset)?;
-            offset += (*header).len();
-            if (*header).r#type == NodeType::HART_INFO {
-                Some(HartInfo::from_header(header))
-            } else {
-                None
-            }
-        })
+        (0..self.nodes_len.get())
+            .map_while(move |_| unsafe {
+                let header = self.get_node(offset).ok()?;
+                let node_offset = offset;
+                offset += (*header).len().max(size_of::<Header>());
+                Some((header, node_offset))
+            })
+            .filter_map(|(header, offset)| unsafe {
+                if (*header).r#type == NodeType::HART_INFO {
+                    try_from_header(header, offset).ok()
+                } else {
+                    None
+                }
+            })
     }
 }
 
@@ -155,6 +175,25 @@ trait FromHeader {
     /// provenance over **all** bytes of the entire table, as specified by the `len` field
     /// of `NodeHeader`.
     unsafe fn from_header<'a>(header: *const Header) -> &'a Self;
+
+    /// Minimum value of the `len` field of a valid node
+    const MIN_LEN: usize;
+}
+
+/// Create a reference to a full node, checking that it is not too short
+///
+/// # Safety
+///
+/// `header` must be a valid pointer to a [`Header`], with provenance over `len` bytes.
+unsafe fn try_from_header<'a, N>(header: *const Header, offset: usize) -> Result<&'a N, Error>
+where
+    N: 'a + ?Sized + FromHeader,
+{
+    let len = (*header).len();
+    if len < N::MIN_LEN {
+        return Err(Error::Truncated { offset, len });
+    }
+    Ok(N::from_header(header))
 }
 
 unsafe fn from_header_slice_of<'a, T: 'a, N>(header: *const Header) -> &'a N
@@ -177,6 +216,8 @@ impl FromHeader for HartInfo {
     unsafe fn from_header<'a>(header: *const Header) -> &'a Self {
         from_header_slice_of::<u32_le, _>(header)
     }
+
+    const MIN_LEN: usize = size_of::<Header>() + 6;
 }
 
 impl HartInfo {
@@ -185,17 +226,34 @@ impl HartInfo {
         self.acpi_processor_uid.get()
     }
 
-    pub fn entries<'rhct>(&self, rhct: &'rhct Rhct) -> impl Iterator<Item = Entry> + 'rhct {
+    /// Returns an iterator over the nodes referenced by this `HartInfo`
+    ///
+    /// # Panics
+    ///
+    /// The iterator panics if a node is malformed, see [`HartInfo::try_entries()`] for a
+    /// non-panicking version.
+    pub fn entries<'rhct>(&self, rhct: &'rhct Rhct) -> impl Iterator<Item = Entry<'rhct>> + 'rhct {
+        self.try_entries(rhct).map(|entry| match entry {
+            Ok(entry) => entry,
+            Err(error) => panic!("acpi: RHCT: {error}"),
+        })
+    }
+
+    /// Returns an iterator over the nodes referenced by this `HartInfo`
+    pub fn try_entries<'rhct>(
+        &self,
+        rhct: &'rhct Rhct,
+    ) -> impl Iterator<Item = Result<Entry<'rhct>, Error>> + 'rhct {
         let offsets = addr_of!(self.offsets);
         (0..offsets.len()).map(move |index| unsafe {
-            let offset = offsets.get_unchecked(index).read_unaligned().get();
-            let header = rhct.get_node(offset as usize).unwrap();
-            match (*header).r#type {
-                NodeType::ISA_STRING => Entry::IsaString(IsaString::from_header(header)),
-                NodeType::CMO_INFO => Entry::CmoInfo(CmoInfo::from_header(header)),
-                NodeType::MMU_INFO => Entry::MmuInfo(MmuInfo::from_header(header)),
-                _ => Entry::Unknown(Unknown::from_header(header)),
-            }
+            let offset = offsets.get_unchecked(index).read_unaligned().get() as usize;
+            let header = rhct.get_node(offset)?;
+            Ok(match (*header).r#type {
+                NodeType::ISA_STRING => Entry::IsaString(try_from_header(header, offset)?),
+                NodeType::CMO_INFO => Entry::CmoInfo(try_from_header(header, offset)?),
+                NodeType::MMU_INFO => Entry::MmuInfo(try_from_header(header, offset)?),
+                _ => Entry::Unknown(try_from_header(header, offset)?),
+            })
         })
     }
 }
@@ -216,6 +274,8 @@ impl FromHeader for Unknown {
     unsafe fn from_header<'a>(header: *const Header) -> &'a Self {
         from_header_slice_of::<u8, _>(header)
     }
+
+    const MIN_LEN: usize = size_of::<Header>();
 }
 
 /// RISC-V ISA String Node
@@ -232,12 +292,14 @@ impl FromHeader for IsaString {
     unsafe fn from_header<'a>(header: *const Header) -> &'a Self {
         from_header_slice_of::<u8, _>(header)
     }
+
+    const MIN_LEN: usize = size_of::<Header>() + 2;
 }
 
 impl IsaString {
     #[inline]
     pub fn len(&self) -> usize {
-        self.isa_string_len.get() as usize - 1
+        (self.isa_string_len.get() as usize).saturating_sub(1)
     }
 
     #[inline]
@@ -245,14 +307,44 @@ impl IsaString {
         self.len() == 0
     }
 
+    /// Returns the ISA string as bytes, without the NUL terminator
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the string length is larger than the node, see
+    /// [`IsaString::try_as_bytes()`] for a non-panicking version.
     #[inline]
     pub fn as_bytes(&self) -> &[u8] {
         &self.isa_string[..self.len()]
     }
 
+    /// Returns the ISA string as bytes, without the NUL terminator
+    #[inline]
+    pub fn try_as_bytes(&self) -> Result<&[u8], Error> {
+        self.isa_string.get(..self.len()).ok_or(Error::Truncated {
+            offset: size_of_unsized::<Self>(),
+            len: self.len(),
+        })
+    }
+
+    /// Returns the ISA string
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the string is malformed, see [`IsaString::try_as_str()`]
+    /// for a non-panicking version.
     #[inline]
     pub fn as_str(&self) -> &str {
-        core::str::from_utf8(self.as_bytes()).unwrap()
+        match self.try_as_str() {
+            Ok(s) => s,
+            Err(error) => panic!("acpi: RHCT: {error}"),
+        }
+    }
+
+    /// Returns the ISA string
+    #[inline]
+    pub fn try_as_str(&self) -> Result<&str, Error> {
+        Ok(core::str::from_utf8(self.try_as_bytes()?)?)
     }
 }
 
@@ -270,6 +362,8 @@ impl FromHeader for CmoInfo {
     unsafe fn from_header<'a>(header: *const Header) -> &'a Self {
         &*header.cast()
     }
+
+    const MIN_LEN: usize = size_of::<Self>();
 }
 
 impl CmoInfo {
@@ -310,6 +404,8 @@ impl FromHeader for MmuInfo {
     unsafe fn from_header<'a>(header: *const Header) -> &'a Self {
         &*header.cast()
     }
+
+    const MIN_LEN: usize = size_of::<Self>();
 }
 
 #[repr(C, packed)]
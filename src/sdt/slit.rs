@@ -0,0 +1,130 @@
+use crate::{Error, Sdt};
+use core::mem::size_of;
+use libsa::endian::u64_le;
+
+/// System Locality Information Table
+///
+/// Holds the relative distances between each pair of proximity domains, as an N×N matrix
+/// where N is the [locality count](Slit::locality_count).
+#[repr(C, packed)]
+pub struct Slit {
+    header: super::Header,
+    locality_count: u64_le,
+    matrix: [u8],
+}
+
+unsafe impl Sdt for Slit {
+    const SIGNATURE: super::Signature = super::Signature(*b"SLIT");
+    const MIN_LENGTH: usize = size_of::<super::Header>() + 8;
+
+    unsafe fn from_header_ptr(header: *const super::Header) -> *const Self {
+        super::from_header_ptr_slice_of::<u8, _>(header)
+    }
+}
+
+/// Relative distance between two localities
+///
+/// Distances order from nearest to furthest, with unreachable localities last.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Distance {
+    /// Distance relative to [`Slit::LOCAL_DISTANCE`]
+    Relative(u8),
+    Unreachable,
+}
+
+impl Slit {
+    /// Distance from a locality to itself
+    pub const LOCAL_DISTANCE: u8 = 10;
+
+    const UNREACHABLE: u8 = 0xff;
+
+    /// Returns the number of localities
+    #[inline]
+    pub fn locality_count(&self) -> u64 {
+        self.locality_count.get()
+    }
+
+    /// Returns the distance matrix, with the distances from each locality in a row
+    ///
+    /// Returns an error if the length of the table does not match the locality count.
+    pub fn matrix(&self) -> Result<&[u8], Error> {
+        let count = self.locality_count() as u128;
+        let expected = (Self::MIN_LENGTH as u128).saturating_add(count * count);
+        let length = self.header.length as usize;
+        if length as u128 != expected {
+            return Err(Error::LengthMismatch {
+                signature: Self::SIGNATURE,
+                length,
+                expected: expected.try_into().unwrap_or(usize::MAX),
+            });
+        }
+        Ok(&self.matrix)
+    }
+
+    /// Returns the distance from locality `from` to locality `to`
+    ///
+    /// Returns `None` if either locality is out of bounds, or the table is invalid.
+    pub fn distance(&self, from: u64, to: u64) -> Option<Distance> {
+        let matrix = self.matrix().ok()?;
+        let count = self.locality_count();
+        if from >= count || to >= count {
+            return None;
+        }
+        // The matrix has been checked to hold `count * count` entries, so this cannot
+        // overflow.
+        let index = from * count + to;
+        match *matrix.get(index as usize)? {
+            Self::UNREACHABLE => Some(Distance::Unreachable),
+            distance => Some(Distance::Relative(distance)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::sdt::tests::{as_table, table};
+    use std::vec::Vec;
+
+    fn slit(count: u64, matrix: &[u8]) -> Vec<u8> {
+        table::<Slit>(&[&count.to_le_bytes()[..], matrix].concat())
+    }
+
+    #[test]
+    fn distances() {
+        let bytes = slit(2, &[10, 21, 0xff, 10]);
+        let slit = as_table::<Slit>(&bytes);
+        assert_eq!(slit.locality_count(), 2);
+        assert_eq!(slit.matrix(), Ok(&[10, 21, 0xff, 10][..]));
+        assert_eq!(slit.distance(0, 0), Some(Distance::Relative(10)));
+        assert_eq!(slit.distance(0, 1), Some(Distance::Relative(21)));
+        assert_eq!(slit.distance(1, 0), Some(Distance::Unreachable));
+        assert_eq!(slit.distance(1, 1), Some(Distance::Relative(10)));
+        assert_eq!(slit.distance(0, 2), None);
+        assert_eq!(slit.distance(2, 0), None);
+        assert_eq!(slit.distance(u64::MAX, u64::MAX), None);
+    }
+
+    #[test]
+    fn invalid_length() {
+        let bytes = slit(2, &[10, 21, 21]);
+        let table = as_table::<Slit>(&bytes);
+        assert_eq!(
+            table.matrix(),
+            Err(Error::LengthMismatch {
+                signature: Slit::SIGNATURE,
+                length: 47,
+                expected: 48,
+            })
+        );
+        assert_eq!(table.distance(0, 0), None);
+
+        // The square of the count wraps to zero in 64 bits.
+        let bytes = slit(1 << 32, &[]);
+        let table = as_table::<Slit>(&bytes);
+        assert!(table.matrix().is_err());
+        assert_eq!(table.distance(0, 0), None);
+    }
+}
@@ -0,0 +1,465 @@
+use crate::{address::PciAddress, Sdt};
+use core::mem::size_of;
+use libsa::endian::{u32_le, u64_le};
+
+/// System Resource Affinity Table
+///
+/// Associates processors, memory ranges and generic initiators with proximity domains.
+#[repr(C, packed)]
+pub struct Srat {
+    header: super::Header,
+    table_revision: u32_le,
+    reserved: u64_le,
+    structures: [u8],
+}
+
+unsafe impl Sdt for Srat {
+    const SIGNATURE: super::Signature = super::Signature(*b"SRAT");
+    const MIN_LENGTH: usize = size_of::<super::Header>() + 12;
+
+    unsafe fn from_header_ptr(header: *const super::Header) -> *const Self {
+        super::from_header_ptr_slice_of::<u8, _>(header)
+    }
+}
+
+impl Srat {
+    /// Returns the revision of the table
+    ///
+    /// Before revision 2, only the low 8 bits of the proximity domain of a
+    /// [`LocalApicAffinity`] are valid.
+    #[inline]
+    pub fn revision(&self) -> u8 {
+        self.header.revision
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = Entry> + '_ {
+        let header = |bytes: &[u8]| (bytes[0] as u16, bytes[1] as usize);
+        let structures = super::Structure::iter(&self.structures, size_of::<Header>(), header);
+        structures.filter_map(|structure| unsafe {
+            // Structures which are too short for their type are passed through as unknown.
+            let entry = match structure.r#type {
+                0x00 => structure.cast().map(Entry::LocalApicAffinity),
+                0x01 => structure.cast().map(Entry::MemoryAffinity),
+                0x02 => structure.cast().map(Entry::LocalX2ApicAffinity),
+                0x03 => structure.cast().map(Entry::GiccAffinity),
+                0x04 => structure.cast().map(Entry::GicItsAffinity),
+                0x05 => structure.cast().map(Entry::GenericInitiatorAffinity),
+                0x07 => structure.cast().map(Entry::RintcAffinity),
+                _ => None,
+            };
+            entry.or_else(|| structure.cast_unsized().map(Entry::Unknown))
+        })
+    }
+}
+
+pub enum Entry<'a> {
+    LocalApicAffinity(&'a LocalApicAffinity),
+    MemoryAffinity(&'a MemoryAffinity),
+    LocalX2ApicAffinity(&'a LocalX2ApicAffinity),
+    GiccAffinity(&'a GiccAffinity),
+    GicItsAffinity(&'a GicItsAffinity),
+    GenericInitiatorAffinity(&'a GenericInitiatorAffinity),
+    RintcAffinity(&'a RintcAffinity),
+    Unknown(&'a Unknown),
+}
+
+#[repr(C)]
+pub struct Header {
+    pub r#type: u8,
+    pub length: u8,
+}
+
+#[repr(C, packed)]
+pub struct Unknown {
+    pub header: Header,
+    pub data: [u8],
+}
+
+bitflags::bitflags! {
+    /// Processor Affinity Flags
+    ///
+    /// Shared by the local APIC, local x2APIC, GICC and RINTC affinity structures.
+    #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+    pub struct ProcessorAffinityFlags : u32 {
+        /// The structure is in use, otherwise it must be ignored
+        const ENABLED = 1 << 0;
+    }
+}
+
+/// Processor Local APIC/SAPIC Affinity
+#[repr(C, packed)]
+pub struct LocalApicAffinity {
+    header: Header,
+    proximity_domain_lo: u8,
+    apic_id: u8,
+    flags: u32_le,
+    local_sapic_eid: u8,
+    proximity_domain_hi: [u8; 3],
+    clock_domain: u32_le,
+}
+
+impl LocalApicAffinity {
+    /// Returns the proximity domain of the processor
+    ///
+    /// Only the low 8 bits are valid if the [`Srat::revision()`] is less than 2.
+    #[inline]
+    pub fn proximity_domain(&self) -> u32 {
+        let [b1, b2, b3] = self.proximity_domain_hi;
+        u32::from_le_bytes([self.proximity_domain_lo, b1, b2, b3])
+    }
+
+    #[inline]
+    pub fn apic_id(&self) -> u32 {
+        self.apic_id as u32
+    }
+
+    #[inline]
+    pub fn flags(&self) -> ProcessorAffinityFlags {
+        ProcessorAffinityFlags::from_bits_retain(self.flags.get())
+    }
+
+    #[inline]
+    pub fn local_sapic_eid(&self) -> u8 {
+        self.local_sapic_eid
+    }
+
+    #[inline]
+    pub fn clock_domain(&self) -> u32 {
+        self.clock_domain.get()
+    }
+}
+
+/// Memory Affinity
+#[repr(C, packed)]
+pub struct MemoryAffinity {
+    header: Header,
+    proximity_domain: u32_le,
+    reserved0: [u8; 2],
+    base_addr: u64_le,
+    length: u64_le,
+    reserved1: [u8; 4],
+    flags: u32_le,
+    reserved2: [u8; 8],
+}
+
+bitflags::bitflags! {
+    #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+    pub struct MemoryAffinityFlags : u32 {
+        /// The structure is in use, otherwise it must be ignored
+        const ENABLED = 1 << 0;
+        /// The memory range may be added or removed while the system is running
+        const HOT_PLUGGABLE = 1 << 1;
+        const NON_VOLATILE = 1 << 2;
+    }
+}
+
+impl MemoryAffinity {
+    #[inline]
+    pub fn proximity_domain(&self) -> u32 {
+        self.proximity_domain.get()
+    }
+
+    /// Returns the physical address of the start of the memory range
+    #[inline]
+    pub fn base_addr(&self) -> u64 {
+        self.base_addr.get()
+    }
+
+    /// Returns the length of the memory range in bytes
+    #[inline]
+    pub fn length(&self) -> u64 {
+        self.length.get()
+    }
+
+    #[inline]
+    pub fn flags(&self) -> MemoryAffinityFlags {
+        MemoryAffinityFlags::from_bits_retain(self.flags.get())
+    }
+}
+
+/// Processor Local x2APIC Affinity
+#[repr(C, packed)]
+pub struct LocalX2ApicAffinity {
+    header: Header,
+    reserved0: [u8; 2],
+    proximity_domain: u32_le,
+    x2apic_id: u32_le,
+    flags: u32_le,
+    clock_domain: u32_le,
+    reserved1: [u8; 4],
+}
+
+impl LocalX2ApicAffinity {
+    #[inline]
+    pub fn proximity_domain(&self) -> u32 {
+        self.proximity_domain.get()
+    }
+
+    #[inline]
+    pub fn x2apic_id(&self) -> u32 {
+        self.x2apic_id.get()
+    }
+
+    #[inline]
+    pub fn flags(&self) -> ProcessorAffinityFlags {
+        ProcessorAffinityFlags::from_bits_retain(self.flags.get())
+    }
+
+    #[inline]
+    pub fn clock_domain(&self) -> u32 {
+        self.clock_domain.get()
+    }
+}
+
+/// GICC Affinity
+#[repr(C, packed)]
+pub struct GiccAffinity {
+    header: Header,
+    proximity_domain: u32_le,
+    acpi_processor_uid: u32_le,
+    flags: u32_le,
+    clock_domain: u32_le,
+}
+
+impl GiccAffinity {
+    #[inline]
+    pub fn proximity_domain(&self) -> u32 {
+        self.proximity_domain.get()
+    }
+
+    /// Returns the ACPI Processor UID of the GICC structure in the MADT
+    #[inline]
+    pub fn acpi_processor_uid(&self) -> u32 {
+        self.acpi_processor_uid.get()
+    }
+
+    #[inline]
+    pub fn flags(&self) -> ProcessorAffinityFlags {
+        ProcessorAffinityFlags::from_bits_retain(self.flags.get())
+    }
+
+    #[inline]
+    pub fn clock_domain(&self) -> u32 {
+        self.clock_domain.get()
+    }
+}
+
+/// GIC Interrupt Translation Service Affinity
+#[repr(C, packed)]
+pub struct GicItsAffinity {
+    header: Header,
+    proximity_domain: u32_le,
+    reserved: [u8; 2],
+    its_id: u32_le,
+}
+
+impl GicItsAffinity {
+    #[inline]
+    pub fn proximity_domain(&self) -> u32 {
+        self.proximity_domain.get()
+    }
+
+    /// Returns the ID of the GIC ITS structure in the MADT
+    #[inline]
+    pub fn its_id(&self) -> u32 {
+        self.its_id.get()
+    }
+}
+
+/// Generic Initiator Affinity
+#[repr(C, packed)]
+pub struct GenericInitiatorAffinity {
+    header: Header,
+    reserved0: u8,
+    device_handle_type: u8,
+    proximity_domain: u32_le,
+    device_handle: [u8; 16],
+    flags: u32_le,
+    reserved1: [u8; 4],
+}
+
+bitflags::bitflags! {
+    #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+    pub struct GenericInitiatorFlags : u32 {
+        /// The structure is in use, otherwise it must be ignored
+        const ENABLED = 1 << 0;
+        /// The initiator can issue transactions with the same semantics as processors
+        const ARCHITECTURAL_TRANSACTIONS = 1 << 1;
+    }
+}
+
+/// Device described by a [`GenericInitiatorAffinity`]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DeviceHandle {
+    /// A device in the namespace, identified by its `_HID` and `_UID`
+    Acpi {
+        hid: [u8; 8],
+        uid: u32,
+    },
+    /// A PCI function, with an `offset` of 0
+    Pci(PciAddress),
+    Unknown {
+        r#type: u8,
+        handle: [u8; 16],
+    },
+}
+
+impl GenericInitiatorAffinity {
+    #[inline]
+    pub fn proximity_domain(&self) -> u32 {
+        self.proximity_domain.get()
+    }
+
+    pub fn device_handle(&self) -> DeviceHandle {
+        let handle = self.device_handle;
+        match self.device_handle_type {
+            0 => DeviceHandle::Acpi {
+                hid: handle[..8].try_into().unwrap(),
+                uid: u32::from_le_bytes(handle[8..12].try_into().unwrap()),
+            },
+            1 => {
+                let bdf = u16::from_le_bytes([handle[2], handle[3]]);
+                DeviceHandle::Pci(PciAddress {
+                    segment: u16::from_le_bytes([handle[0], handle[1]]),
+                    bus: (bdf >> 8) as u8,
+                    device: (bdf >> 3 & 0x1f) as u8,
+                    function: (bdf & 0x7) as u8,
+                    offset: 0,
+                })
+            }
+            r#type => DeviceHandle::Unknown { r#type, handle },
+        }
+    }
+
+    #[inline]
+    pub fn flags(&self) -> GenericInitiatorFlags {
+        GenericInitiatorFlags::from_bits_retain(self.flags.get())
+    }
+}
+
+/// RISC-V Hart-Local Interrupt Controller Affinity
+#[repr(C, packed)]
+pub struct RintcAffinity {
+    header: Header,
+    reserved: [u8; 2],
+    proximity_domain: u32_le,
+    acpi_processor_uid: u32_le,
+    flags: u32_le,
+    clock_domain: u32_le,
+}
+
+impl RintcAffinity {
+    #[inline]
+    pub fn proximity_domain(&self) -> u32 {
+        self.proximity_domain.get()
+    }
+
+    /// Returns the ACPI Processor UID of the RINTC structure in the MADT
+    #[inline]
+    pub fn acpi_processor_uid(&self) -> u32 {
+        self.acpi_processor_uid.get()
+    }
+
+    #[inline]
+    pub fn flags(&self) -> ProcessorAffinityFlags {
+        ProcessorAffinityFlags::from_bits_retain(self.flags.get())
+    }
+
+    #[inline]
+    pub fn clock_domain(&self) -> u32 {
+        self.clock_domain.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::sdt::tests::{as_table, table};
+    use std::vec::Vec;
+
+    fn structure(r#type: u8, body: &[u8]) -> Vec<u8> {
+        [&[r#type, 2 + body.len() as u8][..], body].concat()
+    }
+
+    fn srat(structures: &[&[u8]]) -> Vec<u8> {
+        let mut bytes = table::<Srat>(&[&[1, 0, 0, 0][..], &[0; 8], &structures.concat()].concat());
+        bytes[8] = 3;
+        bytes
+    }
+
+    #[test]
+    fn affinity_structures() {
+        let apic = [0x78, 3, 1, 0, 0, 0, 0, 0x56, 0x34, 0x12, 0, 0, 0, 0];
+        let pci = [
+            &[0, 1][..],
+            &2u32.to_le_bytes(),
+            &[1, 0, 0xfd, 0x3a],
+            &[0; 12],
+            &[3, 0, 0, 0, 0, 0, 0, 0],
+        ];
+        let acpi = [
+            &[0, 0][..],
+            &4u32.to_le_bytes(),
+            b"ACPI0016",
+            &7u32.to_le_bytes(),
+            &[0; 4],
+            &[1, 0, 0, 0, 0, 0, 0, 0],
+        ];
+        let bytes = srat(&[
+            &structure(0, &apic),
+            // A memory affinity structure is 40 bytes long.
+            &structure(1, &[0; 6]),
+            &structure(5, &pci.concat()),
+            &structure(5, &acpi.concat()),
+        ]);
+        let srat = as_table::<Srat>(&bytes);
+        assert_eq!(srat.revision(), 3);
+        let mut entries = srat.entries();
+
+        let Some(Entry::LocalApicAffinity(apic)) = entries.next() else {
+            panic!("expected a local APIC affinity structure");
+        };
+        assert_eq!(apic.proximity_domain(), 0x1234_5678);
+        assert_eq!(apic.apic_id(), 3);
+        assert_eq!(apic.flags(), ProcessorAffinityFlags::ENABLED);
+
+        let Some(Entry::Unknown(unknown)) = entries.next() else {
+            panic!("expected a structure of unknown type");
+        };
+        assert_eq!(unknown.header.r#type, 1);
+        assert_eq!(unknown.data.len(), 6);
+
+        let Some(Entry::GenericInitiatorAffinity(pci)) = entries.next() else {
+            panic!("expected a generic initiator affinity structure");
+        };
+        assert_eq!(pci.proximity_domain(), 2);
+        assert_eq!(
+            pci.device_handle(),
+            DeviceHandle::Pci(PciAddress {
+                segment: 1,
+                bus: 0x3a,
+                device: 0x1f,
+                function: 5,
+                offset: 0,
+            })
+        );
+        assert_eq!(
+            pci.flags(),
+            GenericInitiatorFlags::ENABLED | GenericInitiatorFlags::ARCHITECTURAL_TRANSACTIONS
+        );
+
+        let Some(Entry::GenericInitiatorAffinity(acpi)) = entries.next() else {
+            panic!("expected a generic initiator affinity structure");
+        };
+        assert_eq!(acpi.device_handle(), DeviceHandle::Acpi {
+            hid: *b"ACPI0016",
+            uid: 7,
+        });
+        assert!(entries.next().is_none());
+    }
+}